@@ -2,13 +2,40 @@ import init, { StageContainer } from './pkg/client_web.js';
 
 window.addEventListener('load', onWindowLoad);
 
+// resolves with the token from /login or /register
+function logIn() {
+    const form = document.getElementById('login');
+    const error = document.getElementById('login-error');
+    return new Promise((resolve) => {
+        form.addEventListener('submit', async (e) => {
+            e.preventDefault();
+            const path = e.submitter.value === 'register' ? '/register' : '/login';
+            const data = new FormData(form);
+            const response = await fetch(path, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({ name: data.get('name'), password: data.get('password') }),
+            });
+            if (!response.ok) {
+                error.textContent = await response.text();
+                return;
+            }
+            const { token } = await response.json();
+            form.remove();
+            resolve(token);
+        });
+    });
+}
+
 async function onWindowLoad() {
     console.log('Window loaded');
     await init();
     console.log('wasm initialized');
 
+    const token = await logIn();
+
     let wsReady = false;
-    let ws = new WebSocket(`ws://${location.host}/ws`);
+    let ws = new WebSocket(`ws://${location.host}/ws?token=${encodeURIComponent(token)}`);
 
     function wsWrite(data) {
         if (!wsReady) {
@@ -0,0 +1,31 @@
+use web_sys::Window;
+
+use shared::bundle::DataBundle;
+
+// last definitions received from the server, kept in localStorage
+// so they are not downloaded again while the hash stays the same
+const STORAGE_KEY: &str = "game-definitions";
+
+pub fn load_cached(window: &Window) -> Option<DataBundle> {
+    let storage = window.local_storage().ok()??;
+    let data = storage.get_item(STORAGE_KEY).ok()??;
+    serde_json::from_str(&data)
+        .inspect_err(|e| console_log!("Can't read cached definitions: {e}"))
+        .ok()
+}
+
+pub fn save_cached(window: &Window, definitions: &DataBundle) {
+    let Ok(Some(storage)) = window.local_storage() else {
+        return;
+    };
+    let data = match serde_json::to_string(definitions) {
+        Ok(data) => data,
+        Err(e) => {
+            console_log!("Can't encode definitions: {e}");
+            return;
+        }
+    };
+    if let Err(e) = storage.set_item(STORAGE_KEY, &data) {
+        console_log!("Can't cache definitions: {e:?}");
+    }
+}
@@ -73,6 +73,20 @@ impl<'a> HeroView<'a> {
         let width = bar_width * self.hero_info.hp_left_percent() as f64;
         ctx.fill_rect(start_x, start_y, width, bar_height);
     }
+    // placed under the large hp bar
+    pub fn draw_resource_bars(&self, ctx: &CanvasRenderingContext2d) {
+        let start_x = 10.0;
+        let bar_width = 200.0;
+        let bar_height = 10.0;
+        for (index, pool) in self.hero_info.resources.iter().enumerate() {
+            let start_y = 35.0 + index as f64 * 15.0;
+            ctx.set_fill_style_str("gray");
+            ctx.fill_rect(start_x, start_y, bar_width, bar_height);
+            ctx.set_fill_style_str("blue");
+            let width = bar_width * pool.percent_left() as f64;
+            ctx.fill_rect(start_x, start_y, width, bar_height);
+        }
+    }
     fn draw_attack(&self, ctx: &CanvasRenderingContext2d) {
         if let Action::Attack(attack_info) = &self.hero_info.action {
             let attack_view = AttackView::new(attack_info);
@@ -11,6 +11,7 @@ mod console;
 
 mod attack;
 mod boss;
+mod definitions;
 mod dom_helpers;
 mod hero;
 mod scene;
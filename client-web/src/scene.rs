@@ -2,6 +2,8 @@ use web_sys::CanvasRenderingContext2d;
 
 use game_core::scene::Scene;
 
+use shared::resource::PICKUP_SIZE;
+
 use crate::boss::BossView;
 use crate::hero::HeroView;
 
@@ -18,6 +20,14 @@ impl<'a> SceneView<'a> {
     }
     pub fn draw(&self, ctx: &CanvasRenderingContext2d, self_id: u128) {
         let scene = &self.scene_info;
+        ctx.set_fill_style_str("blue");
+        for pickup in scene.pickups.iter() {
+            ctx.begin_path();
+            let x = pickup.position.x as f64;
+            let y = pickup.position.y as f64;
+            let _ = ctx.arc(x, y, PICKUP_SIZE as f64, 0.0, 2.0 * std::f64::consts::PI);
+            ctx.fill();
+        }
         for boss in scene.npc.iter() {
             // console_log!("boss hp {} max {}", boss.hp, boss.max_hp);
             // console_log!("boss hp left percent {}", boss.hp_left_percent());
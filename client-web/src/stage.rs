@@ -7,14 +7,17 @@ use js_sys::Date;
 use nalgebra::Point2;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, KeyboardEvent, Window};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, KeyboardEvent, MouseEvent, Window};
 
 use game_core::boss::Boss;
 use game_core::hero::Hero;
 use game_core::scene::{self, Scene};
 use network::client::{self, KeyActionKind, Move};
 use network::server;
+use shared::bundle::DataBundle;
+use shared::character::{AbilitySlot, CharacterDefinition};
 
+use crate::definitions;
 use crate::dom_helpers::*;
 use crate::hero::HeroView;
 use crate::scene::SceneView;
@@ -23,6 +26,8 @@ use crate::scene::SceneView;
 struct Callbacks {
     onkeydown: Option<Closure<dyn FnMut(KeyboardEvent)>>,
     onkeyup: Option<Closure<dyn FnMut(KeyboardEvent)>>,
+    onmousemove: Option<Closure<dyn FnMut(MouseEvent)>>,
+    onmousedown: Option<Closure<dyn FnMut(MouseEvent)>>,
 }
 
 pub struct Stage {
@@ -35,6 +40,10 @@ pub struct Stage {
     callbacks: Callbacks,
     hero: Hero,
     scene: Scene,
+    character_classes: Vec<CharacterDefinition>,
+    definitions: DataBundle,
+    joined: bool,
+    aim: Point2<f32>,
     last_update: u128,
     pub state_changed: bool,
     last_frame_request: u128,
@@ -60,9 +69,12 @@ impl Stage {
         let ctx = get_context(&canvas)?;
 
         let tmp_id = 0; // will receive a proper one from server when connected
-        let mut hero = Hero::new(tmp_id, Point2::new(250.0, 200.0));
-        hero.character_settings.dash_duration = 100;
-        hero.character_settings.dash_distance = 150;
+        // replaced when a character class is selected
+        let hero = Hero::new(
+            tmp_id,
+            Point2::new(250.0, 200.0),
+            &CharacterDefinition::default(),
+        );
         let scene = Scene::new(scene::Mode::Client);
 
         Ok(Stage {
@@ -75,6 +87,10 @@ impl Stage {
             callbacks: Callbacks::default(),
             hero,
             scene,
+            character_classes: Vec::new(),
+            definitions: DataBundle::default(),
+            joined: false,
+            aim: Point2::new(0.0, 0.0),
             last_update: Date::now() as u128,
             state_changed: true,
             last_frame_request: Date::now() as u128,
@@ -85,7 +101,9 @@ impl Stage {
     pub fn add_listeners(&mut self, self_ref: Rc<RefCell<Stage>>) {
         console_log!("Adding listeners");
         self.set_keydown(self_ref.clone());
-        self.set_keyup(self_ref);
+        self.set_keyup(self_ref.clone());
+        self.set_mousemove(self_ref.clone());
+        self.set_mousedown(self_ref);
     }
 
     pub fn remove_listeners(&mut self) {
@@ -93,6 +111,10 @@ impl Stage {
         self.window.set_onkeyup(None);
         self.callbacks.onkeydown = None;
         self.callbacks.onkeyup = None;
+        self.canvas.set_onmousemove(None);
+        self.canvas.set_onmousedown(None);
+        self.callbacks.onmousemove = None;
+        self.callbacks.onmousedown = None;
     }
 
     fn set_keydown(&mut self, self_ref: Rc<RefCell<Stage>>) {
@@ -115,8 +137,35 @@ impl Stage {
             .set_onkeyup(Some(closure.as_ref().unchecked_ref()));
         self.callbacks.onkeyup = Some(closure);
     }
+    fn set_mousemove(&mut self, self_ref: Rc<RefCell<Stage>>) {
+        let closure = Closure::wrap(Box::new(move |e: MouseEvent| {
+            // canvas is not scaled, so offset matches scene coordinates
+            let aim = Point2::new(e.offset_x() as f32, e.offset_y() as f32);
+            self_ref.borrow_mut().aim = aim;
+        }) as Box<dyn FnMut(MouseEvent)>);
+        self.canvas
+            .set_onmousemove(Some(closure.as_ref().unchecked_ref()));
+        self.callbacks.onmousemove = Some(closure);
+    }
+    fn set_mousedown(&mut self, self_ref: Rc<RefCell<Stage>>) {
+        let closure = Closure::wrap(Box::new(move |e: MouseEvent| {
+            // left button only
+            if e.button() == 0 {
+                self_ref.borrow_mut().hero_attack();
+            }
+        }) as Box<dyn FnMut(MouseEvent)>);
+        self.canvas
+            .set_onmousedown(Some(closure.as_ref().unchecked_ref()));
+        self.callbacks.onmousedown = Some(closure);
+    }
 
     fn process_key_code(&mut self, code: &str, kind: KeyActionKind) {
+        if !self.joined {
+            if let KeyActionKind::Pressed = kind {
+                self.select_character(code);
+            }
+            return;
+        }
         let action = match code {
             "KeyW" => Move::Up,
             "KeyS" => Move::Down,
@@ -133,10 +182,18 @@ impl Stage {
             }
             "Space" => {
                 if let KeyActionKind::Pressed = kind {
-                    self.hero.check_attack();
-                    let message = client::Message::HeroAttack;
-                    self.send_client_message(message);
-                    self.state_changed = true;
+                    self.hero_attack();
+                }
+                return;
+            }
+            "Digit1" | "Digit2" | "Digit3" => {
+                if let KeyActionKind::Pressed = kind {
+                    let slot = match code {
+                        "Digit1" => AbilitySlot::Ability1,
+                        "Digit2" => AbilitySlot::Ability2,
+                        _ => AbilitySlot::Ability3,
+                    };
+                    self.use_ability(slot);
                 }
                 return;
             }
@@ -147,6 +204,41 @@ impl Stage {
         self.handle_move_action(kind, action);
     }
 
+    // classes are listed on the canvas, picked with number keys
+    fn select_character(&mut self, code: &str) {
+        let Some(digit) = code.strip_prefix("Digit") else {
+            return;
+        };
+        let Some(index) = digit.parse::<usize>().ok().and_then(|n| n.checked_sub(1)) else {
+            return;
+        };
+        let Some(character_class) = self.character_classes.get(index) else {
+            return;
+        };
+        let character_id = character_class.id;
+        let position = self.hero.position;
+        self.hero = Hero::new(self.hero.id, position, character_class);
+        self.joined = true;
+        self.send_client_message(client::Message::Join(character_id));
+        self.state_changed = true;
+    }
+
+    fn hero_attack(&mut self) {
+        let aim = self.aim;
+        self.hero.check_attack(aim);
+        let message = client::Message::HeroAttack(aim);
+        self.send_client_message(message);
+        self.state_changed = true;
+    }
+
+    fn use_ability(&mut self, slot: AbilitySlot) {
+        let aim = self.aim;
+        self.hero.use_ability(slot, aim);
+        let message = client::Message::UseAbility(slot, aim);
+        self.send_client_message(message);
+        self.state_changed = true;
+    }
+
     fn handle_move_action(&mut self, kind: KeyActionKind, movement: Move) {
         self.hero.handle_move_action(kind.clone(), movement.clone());
         let message = client::Message::Move(kind, movement);
@@ -192,6 +284,14 @@ impl Stage {
                 console_log!("Got id from server: {id}");
                 self.hero.id = id;
             }
+            server::Message::DefinitionsHash(hash) => {
+                self.check_definitions_hash(hash);
+            }
+            server::Message::Definitions(definitions) => {
+                console_log!("Got definitions {}", definitions.hash);
+                definitions::save_cached(&self.window, &definitions);
+                self.set_definitions(*definitions);
+            }
             server::Message::ResponseFrameNumber(number) => {
                 // console_log!("Got frame number from server: {number}");
                 // console_log!("old frame number {}", self.scene.frame_number);
@@ -221,6 +321,7 @@ impl Stage {
                             if character.id == self.hero.id {
                                 self.hero.position = character.position;
                                 self.hero.hp = character.hp;
+                                self.hero.resources = character.resources.clone();
                                 self.scene.characters.insert(key, character);
                             } else {
                                 self.scene.characters.insert(key, character);
@@ -228,7 +329,13 @@ impl Stage {
                         }
                         // console_log!("New position: {:?}", self.hero.position);
                         // console_log!("scene npc {:?}", scene.npc);
-                        self.scene.npc = scene.npc.into_iter().map(Boss::from_network).collect();
+                        let definitions = &self.definitions.contents;
+                        self.scene.npc = scene
+                            .npc
+                            .into_iter()
+                            .map(|boss| Boss::from_network(boss, definitions))
+                            .collect();
+                        self.scene.pickups = scene.pickups;
                     }
                     other => {
                         console_log!("Got some other update: {:?}", other);
@@ -239,6 +346,34 @@ impl Stage {
         }
     }
 
+    // use cached definitions if they match, otherwise ask the server
+    fn check_definitions_hash(&mut self, hash: String) {
+        if self.definitions.hash == hash {
+            return;
+        }
+        if let Some(cached) = definitions::load_cached(&self.window)
+            && cached.hash == hash
+        {
+            console_log!("Using cached definitions {hash}");
+            self.set_definitions(cached);
+            return;
+        }
+        self.send_client_message(client::Message::RequestDefinitions);
+    }
+
+    fn set_definitions(&mut self, definitions: DataBundle) {
+        self.character_classes = definitions.contents.character_definitions();
+        if self.joined
+            && let Some(definition) = definitions
+                .contents
+                .character_definition(self.hero.character_id)
+        {
+            self.hero.apply_definition(&definition);
+        }
+        self.definitions = definitions;
+        self.state_changed = true;
+    }
+
     pub fn draw(&self) {
         if !self.state_changed {
             return;
@@ -246,16 +381,52 @@ impl Stage {
         self.ctx
             .clear_rect(0.0, 0.0, f64::from(self.width), f64::from(self.height));
 
+        if !self.joined {
+            self.draw_character_select();
+            return;
+        }
+
         let hero_view = HeroView::new(&self.hero);
         hero_view.draw(&self.ctx);
         if crate::scene::DRAW_LARGE_HP_BAR {
             hero_view.draw_hp_bar(&self.ctx);
+            hero_view.draw_resource_bars(&self.ctx);
         } else {
             hero_view.draw_small_hp_bar(&self.ctx);
         }
 
         SceneView::new(&self.scene).draw(&self.ctx, self.hero.id);
     }
+
+    fn draw_character_select(&self) {
+        self.ctx.set_fill_style_str("black");
+        self.ctx.set_font("24px sans-serif");
+        let title = if self.character_classes.is_empty() {
+            "Waiting for the server..."
+        } else {
+            "Choose your hero"
+        };
+        let _ = self.ctx.fill_text(title, 100.0, 100.0);
+        self.ctx.set_font("18px sans-serif");
+        for (index, item) in self.character_classes.iter().enumerate() {
+            let line = format!(
+                "{}: {} (hp {})",
+                index + 1,
+                item.settings.name,
+                item.settings.hp
+            );
+            let y = 150.0 + index as f64 * 30.0;
+            let _ = self.ctx.fill_text(&line, 100.0, y);
+        }
+        let hash = &self.definitions.hash;
+        if !hash.is_empty() {
+            self.ctx.set_font("12px sans-serif");
+            let version = format!("data {}", &hash[..8.min(hash.len())]);
+            let _ = self
+                .ctx
+                .fill_text(&version, 100.0, f64::from(self.height) - 20.0);
+        }
+    }
 }
 
 impl Drop for Stage {
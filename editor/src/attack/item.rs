@@ -1,29 +1,23 @@
-use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use std::time::Instant;
+
+use iced::widget::{canvas, column, container, pick_list, row, text, text_input};
 use iced::{Alignment, Element};
 
 use shared::attack::{AttackConstructor, AttackKind, AttackOrder};
+use shared::validation::FieldError;
 
-use super::get_item_file_path;
-use crate::common::editor_row;
-
-pub struct Page {
-    id: u32,
-    data: AttackConstructor,
-}
+use super::preview::{self, Preview};
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+use crate::item;
+use crate::playback::{self, HEIGHT, Playback, WIDTH};
 
-impl Page {
-    pub fn load_by_id(id: u32) -> Self {
-        Page {
-            id,
-            data: load_by_id(id),
-        }
-    }
+pub struct Form {
+    playback: Playback,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    ReadFile,
-    WriteFile,
     ChangeName(String),
     ChangeDelay(String),
     ChangeTimeToComplete(String),
@@ -33,158 +27,152 @@ pub enum Message {
     ChangeRangeTo(String),
     ChangeWidthAngle(String),
     ChangeKind(AttackKind),
+    Playback(playback::Message),
 }
 
-fn load_by_id(id: u32) -> AttackConstructor {
-    let file_path = get_item_file_path(id);
-    let contents = std::fs::read(file_path).expect("Should read AttackConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode AttackConstructor")
-}
-
-pub fn save_by_id(attack: &AttackConstructor, id: u32) {
-    let file_path = get_item_file_path(id);
-    let contents = serde_json::to_vec_pretty(attack).expect("Should encode AttackConstructor");
-    std::fs::write(file_path, contents).expect("Should write AttackConstructor to a file");
-}
+impl item::Form for Form {
+    type Entity = AttackConstructor;
+    type Message = Message;
 
-pub(super) fn delete_file_by_id(id: u32) {
-    let file_path = get_item_file_path(id);
-    if let Err(e) = std::fs::remove_file(file_path) {
-        println!("Error removing file for attack {id}: {e}");
+    fn new(data: &AttackConstructor) -> Self {
+        let target_distance = (data.range.from + data.range.to) / 2.0;
+        Form {
+            playback: Playback::new(target_distance),
+        }
     }
-}
-
-impl Page {
-    pub fn update(&mut self, message: Message) {
+    fn is_playing(&self) -> bool {
+        self.playback.is_playing()
+    }
+    fn tick(&mut self, data: &AttackConstructor, now: Instant) {
+        let duration = preview::duration(data);
+        self.playback.update(playback::Message::Tick(now), duration);
+    }
+    fn update(&mut self, message: Message, data: &mut AttackConstructor, drafts: &mut Drafts) {
         match message {
-            Message::ReadFile => {
-                self.data = load_by_id(self.id);
-            }
-            Message::WriteFile => {
-                save_by_id(&self.data, self.id);
-                super::list::update_name_for(self.id, self.data.name.clone());
-            }
             Message::ChangeName(value) => {
-                self.data.name = value;
+                data.name = value;
             }
             Message::ChangeDelay(value) => {
-                let parsed = value.parse::<u128>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.delay = parsed;
+                if let Some(parsed) = drafts.parse("delay", value) {
+                    data.delay = parsed;
+                }
             }
             Message::ChangeTimeToComplete(value) => {
-                let parsed = value.parse::<u128>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.time_to_complete = parsed;
+                if let Some(parsed) = drafts.parse("time_to_complete", value) {
+                    data.time_to_complete = parsed;
+                }
             }
             Message::ChangeAftercast(value) => {
-                let parsed = value.parse::<u128>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.aftercast = parsed;
+                if let Some(parsed) = drafts.parse("aftercast", value) {
+                    data.aftercast = parsed;
+                }
             }
             Message::ChangeOrder(order) => {
-                self.data.order = order;
+                data.order = order;
             }
             Message::ChangeRangeFrom(value) => {
-                let parsed = value.parse::<f32>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.range.from = parsed;
+                if let Some(parsed) = drafts.parse("range.from", value) {
+                    data.range.from = parsed;
+                }
             }
             Message::ChangeRangeTo(value) => {
-                let parsed = value.parse::<f32>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.range.to = parsed;
+                if let Some(parsed) = drafts.parse("range.to", value) {
+                    data.range.to = parsed;
+                }
             }
             Message::ChangeWidthAngle(value) => {
-                let parsed = value.parse::<f32>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.width_angle = parsed;
+                if let Some(parsed) = drafts.parse("width_angle", value) {
+                    data.width_angle = parsed;
+                }
             }
             Message::ChangeKind(kind) => {
-                self.data.kind = kind;
+                data.kind = kind;
+            }
+            Message::Playback(message) => {
+                let duration = preview::duration(data);
+                self.playback.update(message, duration);
             }
         }
     }
-
-    pub fn view(&self) -> Element<'_, Message> {
-        let mut contents = column![
-            row![
-                button("Reload from disk").on_press(Message::ReadFile),
-                button("Save").on_press(Message::WriteFile),
-            ]
-            .spacing(10)
-        ]
-        .align_x(Alignment::Center)
-        .spacing(10);
-
+    fn view<'a>(
+        &'a self,
+        data: &'a AttackConstructor,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
         let attack_details_column = column![
-            text(format!("Id {}", self.id)),
             editor_row(
                 "Name",
-                text_input("Attack name", &self.data.name).on_input(Message::ChangeName)
+                text_input("Attack name", &data.name).on_input(Message::ChangeName)
             ),
-            editor_row(
+            checked_row(
                 "Delay",
-                text_input("Attack delay", &format!("{}", self.data.delay))
-                    .on_input(Message::ChangeDelay),
+                text_input(
+                    "Attack delay",
+                    &drafts.text("delay", format!("{}", data.delay))
+                )
+                .on_input(Message::ChangeDelay),
+                drafts.errors(errors, "delay")
             ),
-            editor_row(
+            checked_row(
                 "Time to complete",
                 text_input(
                     "Attack time to complete",
-                    &format!("{}", self.data.time_to_complete)
+                    &drafts.text("time_to_complete", format!("{}", data.time_to_complete))
                 )
                 .on_input(Message::ChangeTimeToComplete),
+                drafts.errors(errors, "time_to_complete")
             ),
-            editor_row(
+            checked_row(
                 "Aftercast",
-                text_input("Attack aftercast", &format!("{}", self.data.aftercast))
-                    .on_input(Message::ChangeAftercast),
+                text_input(
+                    "Attack aftercast",
+                    &drafts.text("aftercast", format!("{}", data.aftercast))
+                )
+                .on_input(Message::ChangeAftercast),
+                drafts.errors(errors, "aftercast")
             ),
             editor_row(
                 "Order",
                 pick_list(
                     AttackOrder::options(),
-                    Some(self.data.order.clone()),
+                    Some(data.order.clone()),
                     Message::ChangeOrder
                 )
                 .placeholder("Attack order"),
             ),
-            editor_row(
+            checked_row(
                 "Range from",
-                text_input("Attack range from", &format!("{}", self.data.range.from))
-                    .on_input(Message::ChangeRangeFrom),
+                text_input(
+                    "Attack range from",
+                    &drafts.text("range.from", format!("{}", data.range.from))
+                )
+                .on_input(Message::ChangeRangeFrom),
+                drafts.errors(errors, "range.from")
             ),
-            editor_row(
+            checked_row(
                 "Range to",
-                text_input("Attack range to", &format!("{}", self.data.range.to))
-                    .on_input(Message::ChangeRangeTo),
+                text_input(
+                    "Attack range to",
+                    &drafts.text("range.to", format!("{}", data.range.to))
+                )
+                .on_input(Message::ChangeRangeTo),
+                drafts.errors(errors, "range.to")
             ),
-            editor_row(
+            checked_row(
                 "Width angle",
                 text_input(
                     "Attack width angle",
-                    &format!("{:?}", self.data.width_angle)
+                    &drafts.text("width_angle", format!("{:?}", data.width_angle))
                 )
                 .on_input(Message::ChangeWidthAngle),
+                drafts.errors(errors, "width_angle"),
             ),
             editor_row(
                 "Kind",
                 pick_list(
                     AttackKind::options(),
-                    Some(self.data.kind.clone()),
+                    Some(data.kind.clone()),
                     Message::ChangeKind
                 )
                 .placeholder("Attack kind")
@@ -193,8 +181,17 @@ impl Page {
         .align_x(Alignment::Start)
         .spacing(10);
         let attack_details = container(attack_details_column).width(300);
-        contents = contents.push(attack_details);
 
-        contents.into()
+        let duration = preview::duration(data);
+        let attack_preview = Preview::new(data, &self.playback);
+        let status = attack_preview.status();
+        let preview_column = column![
+            canvas(attack_preview).width(WIDTH).height(HEIGHT),
+            text(status),
+            self.playback.view(duration).map(Message::Playback),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10);
+        row![attack_details, preview_column].spacing(20).into()
     }
 }
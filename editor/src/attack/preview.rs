@@ -0,0 +1,130 @@
+use std::f32::consts::TAU;
+
+use iced::widget::canvas::{self, Frame, Geometry, Path};
+use iced::{Rectangle, Renderer, Theme, mouse};
+use nalgebra::Point2;
+
+use shared::attack::{AttackConstructor, AttackInfo, AttackKind, AttackState};
+use shared::check_hit;
+
+use crate::playback::{self, Playback, STEP, TARGET_SIZE, point};
+
+// delay, attack and aftercast, plus the step which completes it
+pub fn duration(constructor: &AttackConstructor) -> u128 {
+    constructor.delay + constructor.time_to_complete + constructor.aftercast + STEP
+}
+
+// a frame of the attack, updated the same way the boss does it
+pub struct Preview {
+    attack: AttackInfo,
+    attacker: Point2<f32>,
+    target: Point2<f32>,
+    in_hitbox: bool,
+    hit_at: Option<u128>,
+    completed: bool,
+}
+
+impl Preview {
+    pub fn new(constructor: &AttackConstructor, playback: &Playback) -> Self {
+        let attacker = playback.attacker_position();
+        let target = playback.target_position();
+        let reach = constructor.range.to;
+        let mut attack = AttackInfo::aimed(constructor.clone(), attacker, target, reach);
+        let mut hit_at = None;
+        let mut completed = false;
+        for step in 1..=playback.steps(duration(constructor)) {
+            attack.update(STEP);
+            if !attack.damage_done && check_hit(&attack, attack.distance, target, TARGET_SIZE) {
+                attack.damage_done = true;
+                hit_at = Some(step * STEP);
+            }
+            if attack.completed() {
+                completed = true;
+                break;
+            }
+        }
+        let in_hitbox = !completed && check_hit(&attack, attack.distance, target, TARGET_SIZE);
+        Self {
+            attack,
+            attacker,
+            target,
+            in_hitbox,
+            hit_at,
+            completed,
+        }
+    }
+    pub fn status(&self) -> String {
+        let phase = match self.attack.state {
+            _ if self.completed => "Completed",
+            AttackState::Selected => "Delay",
+            AttackState::Attacking if self.attack.time_passed > self.attack.time_to_complete => {
+                "Aftercast"
+            }
+            AttackState::Attacking => "Attacking",
+        };
+        match self.hit_at {
+            Some(time) => format!("{phase}, target hit at {time} ms"),
+            None => format!("{phase}, target not hit"),
+        }
+    }
+    // the main segment only, the game draws some orders in two parts
+    fn draw_attack(&self, frame: &mut Frame) {
+        let info = &self.attack;
+        let center = point(info.position);
+        let radius = info.get_radius();
+        let path = match info.kind {
+            AttackKind::Pizza => {
+                let angle = info.get_base_angle();
+                let (start_angle, end_angle) = info.get_angles(angle, info.width_radian());
+                playback::circle_segment(center, radius, start_angle, end_angle)
+            }
+            AttackKind::Circle => Path::circle(center, radius),
+        };
+        playback::fill_attack(frame, &path);
+    }
+    // the area where check_hit is true for the center of the target
+    fn draw_hitbox(&self, frame: &mut Frame) {
+        let info = &self.attack;
+        let center = point(info.position);
+        let radius = info.distance + TARGET_SIZE;
+        match info.kind {
+            AttackKind::Pizza => {
+                let angle = info.get_base_angle();
+                let (start_angle, end_angle) = info.get_angles(angle, info.width_radian());
+                // the angle to the target is in 0..2π, so the rest never hits
+                let (start_angle, end_angle) = (start_angle.max(0.0), end_angle.min(TAU));
+                if start_angle < end_angle {
+                    let path = playback::circle_segment(center, radius, start_angle, end_angle);
+                    playback::stroke_hitbox(frame, &path);
+                }
+            }
+            AttackKind::Circle => playback::stroke_hitbox(frame, &Path::circle(center, radius)),
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for Preview {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        playback::draw_background(&mut frame);
+        playback::draw_attacker(&mut frame, self.attacker);
+        playback::draw_target(&mut frame, self.target, self.in_hitbox);
+        if !self.completed {
+            if let AttackState::Attacking = self.attack.state {
+                self.draw_attack(&mut frame);
+            }
+            self.draw_hitbox(&mut frame);
+        }
+        playback::draw_target_center(&mut frame, self.target);
+        vec![frame.into_geometry()]
+    }
+}
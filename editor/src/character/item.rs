@@ -0,0 +1,335 @@
+use iced::widget::{Scrollable, button, column, container, pick_list, row, text, text_input};
+use iced::{Alignment, Element};
+
+use shared::attack::AttackConstructor;
+use shared::character::{
+    AbilityAttackInfo, AbilityResourceInfo, AbilitySlot, CharacterSettings, HeroAbility,
+};
+use shared::list::EntryInfo;
+use shared::resource::ResourceConstructor;
+use shared::validation::FieldError;
+
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+use crate::item;
+use crate::list::load_available;
+
+fn make_attack_picker_items(attack_list: Vec<EntryInfo>) -> Vec<AbilityAttackInfo> {
+    attack_list
+        .into_iter()
+        .filter(|item| item.status.is_active())
+        .map(|EntryInfo { id, name, .. }| AbilityAttackInfo { id, name })
+        .collect()
+}
+
+fn make_resource_picker_items(resource_list: Vec<EntryInfo>) -> Vec<AbilityResourceInfo> {
+    resource_list
+        .into_iter()
+        .filter(|item| item.status.is_active())
+        .map(|EntryInfo { id, name, .. }| AbilityResourceInfo { id, name })
+        .collect()
+}
+
+// the ability which is being prepared in the "Add ability" form
+#[derive(Default)]
+struct NewAbility {
+    slot: Option<AbilitySlot>,
+    attack: Option<AbilityAttackInfo>,
+    cooldown: u128,
+    resource: Option<AbilityResourceInfo>,
+    cost: u32,
+}
+
+impl NewAbility {
+    fn build(&self) -> Option<HeroAbility> {
+        Some(HeroAbility {
+            slot: self.slot?,
+            attack: self.attack.clone()?,
+            cooldown: self.cooldown,
+            resource: self.resource.clone(),
+            cost: self.cost,
+        })
+    }
+}
+
+pub struct Form {
+    new_ability: NewAbility,
+    available_attack_list: Vec<AbilityAttackInfo>,
+    available_resource_list: Vec<AbilityResourceInfo>,
+}
+
+impl item::Form for Form {
+    type Entity = CharacterSettings;
+    type Message = Message;
+
+    fn new(_data: &CharacterSettings) -> Self {
+        let attack_list = load_available::<AttackConstructor>();
+        let resource_list = load_available::<ResourceConstructor>();
+        Form {
+            new_ability: NewAbility::default(),
+            available_attack_list: make_attack_picker_items(attack_list),
+            available_resource_list: make_resource_picker_items(resource_list),
+        }
+    }
+    fn update(&mut self, message: Message, data: &mut CharacterSettings, drafts: &mut Drafts) {
+        match message {
+            Message::ChangeName(value) => {
+                data.name = value;
+            }
+            Message::ChangeHp(value) => {
+                if let Some(parsed) = drafts.parse("hp", value) {
+                    data.hp = parsed;
+                }
+            }
+            Message::ChangeSpeed(value) => {
+                if let Some(parsed) = drafts.parse("speed", value) {
+                    data.speed = parsed;
+                }
+            }
+            Message::ChangeSize(value) => {
+                if let Some(parsed) = drafts.parse("size", value) {
+                    data.size = parsed;
+                }
+            }
+            Message::ChangeMeleeAttackDistance(value) => {
+                if let Some(parsed) = drafts.parse("melee_attack_distance", value) {
+                    data.melee_attack_distance = parsed;
+                }
+            }
+            Message::ChangeRangedAttackDistance(value) => {
+                if let Some(parsed) = drafts.parse("ranged_attack_distance", value) {
+                    data.ranged_attack_distance = parsed;
+                }
+            }
+            Message::ChangeDashDuration(value) => {
+                if let Some(parsed) = drafts.parse("dash_duration", value) {
+                    data.dash_duration = parsed;
+                }
+            }
+            Message::ChangeDashDistance(value) => {
+                if let Some(parsed) = drafts.parse("dash_distance", value) {
+                    data.dash_distance = parsed;
+                }
+            }
+            Message::ChangeDashCooldown(value) => {
+                if let Some(parsed) = drafts.parse("dash_cooldown", value) {
+                    data.dash_cooldown = parsed;
+                }
+            }
+            Message::SelectSlot(slot) => {
+                self.new_ability.slot = Some(slot);
+            }
+            Message::SelectAttack(attack) => {
+                self.new_ability.attack = Some(attack);
+            }
+            Message::ChangeCooldown(value) => {
+                if let Ok(parsed) = value.parse() {
+                    self.new_ability.cooldown = parsed;
+                }
+            }
+            Message::SelectResource(resource) => {
+                self.new_ability.resource = Some(resource);
+            }
+            Message::ClearResource => {
+                self.new_ability.resource = None;
+            }
+            Message::ChangeCost(value) => {
+                if let Ok(parsed) = value.parse() {
+                    self.new_ability.cost = parsed;
+                }
+            }
+            Message::AddAbility => {
+                let Some(ability) = self.new_ability.build() else {
+                    return;
+                };
+                // only one ability per slot
+                data.abilities.retain(|item| item.slot != ability.slot);
+                data.abilities.push(ability);
+                data.abilities.sort_by_key(|item| item.slot as u8);
+            }
+            Message::RemoveAbility(index) => {
+                if index < data.abilities.len() {
+                    data.abilities.remove(index);
+                }
+            }
+        }
+    }
+    fn view<'a>(
+        &'a self,
+        data: &'a CharacterSettings,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let mut ability_list = column![].align_x(Alignment::Start).spacing(10);
+        for (index, ability) in data.abilities.iter().enumerate() {
+            let resource = match &ability.resource {
+                Some(resource) => format!(", costs {} of {}", ability.cost, resource.name),
+                None => "".to_owned(),
+            };
+            let ability_row = row![
+                text(format!(
+                    "{}: {}, cooldown {} ms{resource}",
+                    ability.slot, ability.attack, ability.cooldown
+                )),
+                button("delete").on_press(Message::RemoveAbility(index)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10);
+            ability_list = ability_list.push(ability_row);
+            for error in drafts.errors(errors, &format!("abilities.{index}.slot")) {
+                ability_list = ability_list.push(text(error).style(text::danger));
+            }
+        }
+
+        let message_add_ability = self.new_ability.build().map(|_| Message::AddAbility);
+        let add_ability_column = column![
+            editor_row(
+                "Slot",
+                pick_list(
+                    AbilitySlot::options(),
+                    self.new_ability.slot,
+                    Message::SelectSlot
+                ),
+            ),
+            editor_row(
+                "Attack",
+                pick_list(
+                    &self.available_attack_list[..],
+                    self.new_ability.attack.clone(),
+                    Message::SelectAttack
+                ),
+            ),
+            editor_row(
+                "Cooldown, ms",
+                text_input("Cooldown, ms", &format!("{}", self.new_ability.cooldown))
+                    .on_input(Message::ChangeCooldown),
+            ),
+            row![
+                text("Resource"),
+                pick_list(
+                    &self.available_resource_list[..],
+                    self.new_ability.resource.clone(),
+                    Message::SelectResource
+                ),
+                button("none").on_press(Message::ClearResource),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            editor_row(
+                "Cost",
+                text_input("Cost", &format!("{}", self.new_ability.cost))
+                    .on_input(Message::ChangeCost),
+            ),
+            button("add").on_press_maybe(message_add_ability),
+        ]
+        .spacing(10);
+
+        let character_details_column = column![
+            editor_row(
+                "Name",
+                text_input("Character name", &data.name).on_input(Message::ChangeName)
+            ),
+            checked_row(
+                "Hp",
+                text_input("Hp", &drafts.text("hp", format!("{}", data.hp)))
+                    .on_input(Message::ChangeHp),
+                drafts.errors(errors, "hp")
+            ),
+            checked_row(
+                "Speed, px/ms",
+                text_input(
+                    "Speed, px/ms",
+                    &drafts.text("speed", format!("{}", data.speed))
+                )
+                .on_input(Message::ChangeSpeed),
+                drafts.errors(errors, "speed")
+            ),
+            checked_row(
+                "Size",
+                text_input("Size", &drafts.text("size", format!("{}", data.size)))
+                    .on_input(Message::ChangeSize),
+                drafts.errors(errors, "size")
+            ),
+            checked_row(
+                "Melee attack distance",
+                text_input(
+                    "Melee attack distance",
+                    &drafts.text(
+                        "melee_attack_distance",
+                        format!("{}", data.melee_attack_distance)
+                    )
+                )
+                .on_input(Message::ChangeMeleeAttackDistance),
+                drafts.errors(errors, "melee_attack_distance")
+            ),
+            checked_row(
+                "Ranged attack distance",
+                text_input(
+                    "Ranged attack distance",
+                    &drafts.text(
+                        "ranged_attack_distance",
+                        format!("{}", data.ranged_attack_distance)
+                    )
+                )
+                .on_input(Message::ChangeRangedAttackDistance),
+                drafts.errors(errors, "ranged_attack_distance")
+            ),
+            checked_row(
+                "Dash duration",
+                text_input(
+                    "Dash duration",
+                    &drafts.text("dash_duration", format!("{}", data.dash_duration))
+                )
+                .on_input(Message::ChangeDashDuration),
+                drafts.errors(errors, "dash_duration")
+            ),
+            checked_row(
+                "Dash distance",
+                text_input(
+                    "Dash distance",
+                    &drafts.text("dash_distance", format!("{}", data.dash_distance))
+                )
+                .on_input(Message::ChangeDashDistance),
+                drafts.errors(errors, "dash_distance")
+            ),
+            checked_row(
+                "Dash cooldown, ms",
+                text_input(
+                    "Dash cooldown, ms",
+                    &drafts.text("dash_cooldown", format!("{}", data.dash_cooldown))
+                )
+                .on_input(Message::ChangeDashCooldown),
+                drafts.errors(errors, "dash_cooldown")
+            ),
+            text("Add ability:"),
+            add_ability_column,
+            text("Abilities:"),
+            ability_list,
+        ]
+        .align_x(Alignment::Start)
+        .spacing(10);
+        let scrollable_details = Scrollable::new(character_details_column);
+        container(scrollable_details).width(500).into()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ChangeName(String),
+    ChangeHp(String),
+    ChangeSpeed(String),
+    ChangeSize(String),
+    ChangeMeleeAttackDistance(String),
+    ChangeRangedAttackDistance(String),
+    ChangeDashDuration(String),
+    ChangeDashDistance(String),
+    ChangeDashCooldown(String),
+    SelectSlot(AbilitySlot),
+    SelectAttack(AbilityAttackInfo),
+    ChangeCooldown(String),
+    SelectResource(AbilityResourceInfo),
+    ClearResource,
+    ChangeCost(String),
+    AddAbility,
+    RemoveAbility(usize),
+}
@@ -1,8 +1,79 @@
-use iced::widget::{Row, row, text};
+use iced::widget::{Column, Row, button, column, row, text};
 use iced::{Alignment, Element};
 
+use game_data::{Entity, Usage};
+
+use crate::game_data;
+
 pub fn editor_row<'a, M, T: Into<Element<'a, M>>>(label: &'a str, element: T) -> Row<'a, M> {
     row![text(label), element.into()]
         .align_y(Alignment::Center)
         .spacing(10)
 }
+
+// editor_row with errors of the field under it
+pub fn checked_row<'a, M: 'a, T: Into<Element<'a, M>>>(
+    label: &'a str,
+    element: T,
+    errors: Vec<String>,
+) -> Column<'a, M> {
+    let mut rows = column![editor_row(label, element)].spacing(5);
+    for error in errors {
+        rows = rows.push(text(error).style(text::danger));
+    }
+    rows
+}
+
+// swaps an item with the previous one, used to reorder lists
+pub fn move_up<T>(list: &mut [T], index: usize) {
+    if index > 0 && index < list.len() {
+        list.swap(index - 1, index);
+    }
+}
+
+pub fn move_down<T>(list: &mut [T], index: usize) {
+    if index + 1 < list.len() {
+        list.swap(index, index + 1);
+    }
+}
+
+pub fn load_used_by<T: Entity>(id: u32) -> Vec<Usage> {
+    game_data()
+        .used_by::<T>(id)
+        .inspect_err(|e| println!("Error checking where {} {id} is used: {e}", T::FOLDER))
+        .unwrap_or_default()
+}
+
+// "where is this used" section of an item page
+pub fn used_by_view<'a, M: 'a>(used_by: &[Usage]) -> Column<'a, M> {
+    if used_by.is_empty() {
+        return column![text("Not used anywhere")];
+    }
+    let mut rows = column![text("Used by:")].spacing(5);
+    for usage in used_by {
+        rows = rows.push(text(usage.to_string()));
+    }
+    rows
+}
+
+// shown instead of deleting an entity which is still referenced
+pub fn confirm_delete_view<'a, M: Clone + 'a>(
+    id: u32,
+    used_by: &[Usage],
+    confirm: M,
+    cancel: M,
+) -> Column<'a, M> {
+    let buttons = row![
+        button("Delete and remove references")
+            .on_press(confirm)
+            .style(button::danger),
+        button("Cancel").on_press(cancel),
+    ]
+    .spacing(10);
+    column![
+        text(format!("Item {id} is still used")),
+        used_by_view(used_by),
+        buttons,
+    ]
+    .spacing(10)
+}
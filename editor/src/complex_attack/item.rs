@@ -0,0 +1,287 @@
+use std::time::Instant;
+
+use iced::widget::{Scrollable, button, canvas, column, container, row, text, text_input};
+use iced::{Alignment, Element};
+
+use shared::attack::{AttackPartConstructor, AttackSequenceConstructor, ComplexAttackConstructor};
+use shared::validation::FieldError;
+
+use super::part;
+use super::preview::{self, Preview};
+use crate::common::{checked_row, editor_row, move_down, move_up};
+use crate::drafts::Drafts;
+use crate::item;
+use crate::playback::{self, HEIGHT, Playback, WIDTH};
+
+pub struct Form {
+    playback: Playback,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ChangeName(String),
+    ChangeRangeFrom(String),
+    ChangeRangeTo(String),
+    AddSequence,
+    RemoveSequence(usize),
+    MoveSequenceUp(usize),
+    MoveSequenceDown(usize),
+    ChangeOffsetX(usize, String),
+    ChangeOffsetY(usize, String),
+    AddPart(usize),
+    RemovePart(usize, usize),
+    MovePartUp(usize, usize),
+    MovePartDown(usize, usize),
+    Part(usize, usize, part::Message), // sequence index, part index
+    Playback(playback::Message),
+}
+
+fn sequence_mut(
+    data: &mut ComplexAttackConstructor,
+    index: usize,
+) -> Option<&mut AttackSequenceConstructor> {
+    data.sequences.get_mut(index)
+}
+
+impl item::Form for Form {
+    type Entity = ComplexAttackConstructor;
+    type Message = Message;
+
+    fn new(data: &ComplexAttackConstructor) -> Self {
+        let target_distance = (data.range.from + data.range.to) / 2.0;
+        Form {
+            playback: Playback::new(target_distance),
+        }
+    }
+    fn is_playing(&self) -> bool {
+        self.playback.is_playing()
+    }
+    fn tick(&mut self, data: &ComplexAttackConstructor, now: Instant) {
+        let duration = preview::duration(data);
+        self.playback.update(playback::Message::Tick(now), duration);
+    }
+    fn update(
+        &mut self,
+        message: Message,
+        data: &mut ComplexAttackConstructor,
+        drafts: &mut Drafts,
+    ) {
+        match message {
+            Message::ChangeName(value) => {
+                data.name = value;
+            }
+            Message::ChangeRangeFrom(value) => {
+                if let Some(parsed) = drafts.parse("range.from", value) {
+                    data.range.from = parsed;
+                }
+            }
+            Message::ChangeRangeTo(value) => {
+                if let Some(parsed) = drafts.parse("range.to", value) {
+                    data.range.to = parsed;
+                }
+            }
+            Message::AddSequence => {
+                let sequence = AttackSequenceConstructor {
+                    parts: vec![AttackPartConstructor::default()],
+                    ..AttackSequenceConstructor::default()
+                };
+                data.sequences.push(sequence);
+            }
+            Message::RemoveSequence(index) => {
+                // drafts are named by index
+                drafts.clear();
+                if index < data.sequences.len() {
+                    data.sequences.remove(index);
+                }
+            }
+            Message::MoveSequenceUp(index) => {
+                drafts.clear();
+                move_up(&mut data.sequences, index);
+            }
+            Message::MoveSequenceDown(index) => {
+                drafts.clear();
+                move_down(&mut data.sequences, index);
+            }
+            Message::ChangeOffsetX(index, value) => {
+                let field = format!("sequences.{index}.position_offset.x");
+                let Some(parsed) = drafts.parse(field, value) else {
+                    return;
+                };
+                if let Some(sequence) = sequence_mut(data, index) {
+                    sequence.position_offset.x = parsed;
+                }
+            }
+            Message::ChangeOffsetY(index, value) => {
+                let field = format!("sequences.{index}.position_offset.y");
+                let Some(parsed) = drafts.parse(field, value) else {
+                    return;
+                };
+                if let Some(sequence) = sequence_mut(data, index) {
+                    sequence.position_offset.y = parsed;
+                }
+            }
+            Message::AddPart(index) => {
+                if let Some(sequence) = sequence_mut(data, index) {
+                    sequence.parts.push(AttackPartConstructor::default());
+                }
+            }
+            Message::RemovePart(index, part_index) => {
+                drafts.clear();
+                let Some(sequence) = sequence_mut(data, index) else {
+                    return;
+                };
+                if part_index < sequence.parts.len() {
+                    sequence.parts.remove(part_index);
+                }
+            }
+            Message::MovePartUp(index, part_index) => {
+                drafts.clear();
+                if let Some(sequence) = sequence_mut(data, index) {
+                    move_up(&mut sequence.parts, part_index);
+                }
+            }
+            Message::MovePartDown(index, part_index) => {
+                drafts.clear();
+                if let Some(sequence) = sequence_mut(data, index) {
+                    move_down(&mut sequence.parts, part_index);
+                }
+            }
+            Message::Part(index, part_index, message) => {
+                let Some(sequence) = data.sequences.get_mut(index) else {
+                    return;
+                };
+                if let Some(part) = sequence.parts.get_mut(part_index) {
+                    let prefix = format!("sequences.{index}.parts.{part_index}");
+                    part::update(part, message, drafts, &prefix);
+                }
+            }
+            Message::Playback(message) => {
+                let duration = preview::duration(data);
+                self.playback.update(message, duration);
+            }
+        }
+    }
+    fn view<'a>(
+        &'a self,
+        data: &'a ComplexAttackConstructor,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let mut details_column = column![
+            editor_row(
+                "Name",
+                text_input("Attack name", &data.name).on_input(Message::ChangeName)
+            ),
+            checked_row(
+                "Range from",
+                text_input(
+                    "Attack range from",
+                    &drafts.text("range.from", format!("{}", data.range.from))
+                )
+                .on_input(Message::ChangeRangeFrom),
+                drafts.errors(errors, "range.from")
+            ),
+            checked_row(
+                "Range to",
+                text_input(
+                    "Attack range to",
+                    &drafts.text("range.to", format!("{}", data.range.to))
+                )
+                .on_input(Message::ChangeRangeTo),
+                drafts.errors(errors, "range.to")
+            ),
+        ]
+        .align_x(Alignment::Start)
+        .spacing(10);
+        for error in drafts.errors(errors, "sequences") {
+            details_column = details_column.push(text(error).style(text::danger));
+        }
+        for (index, sequence) in data.sequences.iter().enumerate() {
+            let sequence_view = view_sequence(index, sequence, drafts, errors);
+            details_column = details_column.push(sequence_view);
+        }
+        details_column = details_column.push(button("Add sequence").on_press(Message::AddSequence));
+
+        let scrollable_details = Scrollable::new(details_column);
+        let details = container(scrollable_details).width(500);
+
+        let duration = preview::duration(data);
+        let attack_preview = Preview::new(data, &self.playback);
+        let status = attack_preview.status();
+        let preview_column = column![
+            canvas(attack_preview).width(WIDTH).height(HEIGHT),
+            text(status),
+            self.playback.view(duration).map(Message::Playback),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10);
+        row![details, preview_column].spacing(20).into()
+    }
+}
+
+fn view_sequence<'a>(
+    index: usize,
+    sequence: &'a AttackSequenceConstructor,
+    drafts: &Drafts,
+    errors: &[FieldError],
+) -> Element<'a, Message> {
+    let field = |name: &str| format!("sequences.{index}.{name}");
+    let heading_row = row![
+        text(format!("Sequence {}", index + 1)),
+        button("up").on_press(Message::MoveSequenceUp(index)),
+        button("down").on_press(Message::MoveSequenceDown(index)),
+        button("delete").on_press(Message::RemoveSequence(index)),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(10);
+    let offset = sequence.position_offset;
+    let mut sequence_column = column![
+        heading_row,
+        checked_row(
+            "Offset x",
+            text_input(
+                "Offset x",
+                &drafts.text(&field("position_offset.x"), format!("{}", offset.x))
+            )
+            .on_input(move |value| Message::ChangeOffsetX(index, value)),
+            drafts.errors(errors, &field("position_offset.x")),
+        ),
+        checked_row(
+            "Offset y",
+            text_input(
+                "Offset y",
+                &drafts.text(&field("position_offset.y"), format!("{}", offset.y))
+            )
+            .on_input(move |value| Message::ChangeOffsetY(index, value)),
+            drafts.errors(errors, &field("position_offset.y")),
+        ),
+    ]
+    .align_x(Alignment::Start)
+    .spacing(10);
+    for error in drafts.errors(errors, &field("parts")) {
+        sequence_column = sequence_column.push(text(error).style(text::danger));
+    }
+    for (part_index, item) in sequence.parts.iter().enumerate() {
+        let part_heading = row![
+            text(format!("Part {}", part_index + 1)),
+            button("up").on_press(Message::MovePartUp(index, part_index)),
+            button("down").on_press(Message::MovePartDown(index, part_index)),
+            button("delete").on_press(Message::RemovePart(index, part_index)),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10);
+        let prefix = field(&format!("parts.{part_index}"));
+        let part_view = part::view(item, drafts, errors, &prefix)
+            .map(move |message| Message::Part(index, part_index, message));
+        let part_column = column![part_heading, part_view].spacing(10);
+        let part_container = container(part_column)
+            .padding(10)
+            .style(container::bordered_box);
+        sequence_column = sequence_column.push(part_container);
+    }
+    sequence_column = sequence_column.push(button("Add part").on_press(Message::AddPart(index)));
+    container(sequence_column)
+        .padding(10)
+        .style(container::bordered_box)
+        .into()
+}
@@ -0,0 +1,30 @@
+use shared::attack::ComplexAttackConstructor;
+
+use crate::EditorState;
+use crate::entity::EditorEntity;
+use crate::page;
+
+mod item;
+mod part;
+mod preview;
+
+pub type Page = page::Page<item::Form>;
+pub type Message = page::Message<item::Message>;
+
+pub fn load_state() -> EditorState {
+    EditorState::ComplexAttack(Box::new(Page::load()))
+}
+
+impl EditorEntity for ComplexAttackConstructor {
+    const LABEL: &'static str = "complex attack";
+
+    fn create(name: String) -> Self {
+        Self::new(name)
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+}
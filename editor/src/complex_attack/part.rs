@@ -0,0 +1,294 @@
+use std::fmt::{self, Display};
+
+use iced::widget::{checkbox, column, pick_list, text, text_input};
+use iced::{Alignment, Element};
+
+use shared::attack::{
+    AttackDamageConstructor, AttackOrder, AttackPartConstructor, AttackShapeConstructor,
+    CircleConstructor, PizzaConstructor,
+};
+use shared::validation::FieldError;
+
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+
+// shapes which are implemented in game-core
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShapeKind {
+    Circle,
+    Pizza,
+}
+
+impl ShapeKind {
+    const fn options() -> [ShapeKind; 2] {
+        [ShapeKind::Circle, ShapeKind::Pizza]
+    }
+    fn of(shape: &AttackShapeConstructor) -> Option<Self> {
+        match shape {
+            AttackShapeConstructor::Circle(_) => Some(ShapeKind::Circle),
+            AttackShapeConstructor::Pizza(_) => Some(ShapeKind::Pizza),
+            _ => None,
+        }
+    }
+    // a new shape keeps the radius of the part
+    fn make(self, part: &AttackPartConstructor) -> AttackShapeConstructor {
+        match self {
+            ShapeKind::Circle => AttackShapeConstructor::Circle(CircleConstructor {
+                radius: part.radius,
+                time_to_complete: part.time_to_complete,
+            }),
+            ShapeKind::Pizza => AttackShapeConstructor::Pizza(PizzaConstructor {
+                radius: part.radius,
+                width_angle: 0.5,
+                order: AttackOrder::CloseToFar,
+            }),
+        }
+    }
+}
+
+impl Display for ShapeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ChangeTimeToComplete(String),
+    ChangeRadius(String),
+    ChangeShape(ShapeKind),
+    ChangeShapeRadius(String),
+    ChangeShapeTimeToComplete(String),
+    ChangeWidthAngle(String),
+    ChangeOrder(AttackOrder),
+    ToggleDamage(bool),
+    ChangeDamageValue(String),
+    ChangeDamageInstances(String),
+    ChangeDamageDelay(String),
+}
+
+// fields are named with a prefix like `sequences.0.parts.1`, as in the errors of validate
+pub fn update(
+    part: &mut AttackPartConstructor,
+    message: Message,
+    drafts: &mut Drafts,
+    prefix: &str,
+) {
+    let field = |name: &str| format!("{prefix}.{name}");
+    match message {
+        Message::ChangeTimeToComplete(value) => {
+            if let Some(parsed) = drafts.parse(field("time_to_complete"), value) {
+                part.time_to_complete = parsed;
+            }
+        }
+        Message::ChangeRadius(value) => {
+            if let Some(parsed) = drafts.parse(field("radius"), value) {
+                part.radius = parsed;
+            }
+        }
+        Message::ChangeShape(kind) => {
+            if ShapeKind::of(&part.shape) != Some(kind) {
+                part.shape = kind.make(part);
+                drafts.discard_prefix(&field("shape."));
+            }
+        }
+        Message::ChangeShapeRadius(value) => {
+            let Some(parsed) = drafts.parse(field("shape.radius"), value) else {
+                return;
+            };
+            match &mut part.shape {
+                AttackShapeConstructor::Circle(circle) => circle.radius = parsed,
+                AttackShapeConstructor::Pizza(pizza) => pizza.radius = parsed,
+                _ => (),
+            }
+        }
+        Message::ChangeShapeTimeToComplete(value) => {
+            let Some(parsed) = drafts.parse(field("shape.time_to_complete"), value) else {
+                return;
+            };
+            if let AttackShapeConstructor::Circle(circle) = &mut part.shape {
+                circle.time_to_complete = parsed;
+            }
+        }
+        Message::ChangeWidthAngle(value) => {
+            let Some(parsed) = drafts.parse(field("shape.width_angle"), value) else {
+                return;
+            };
+            if let AttackShapeConstructor::Pizza(pizza) = &mut part.shape {
+                pizza.width_angle = parsed;
+            }
+        }
+        Message::ChangeOrder(order) => {
+            if let AttackShapeConstructor::Pizza(pizza) = &mut part.shape {
+                pizza.order = order;
+            }
+        }
+        Message::ToggleDamage(enabled) => {
+            let damage = AttackDamageConstructor {
+                value: 10,
+                instances: 1,
+                delay_between_instances: 0,
+            };
+            part.damage = enabled.then_some(damage);
+            drafts.discard_prefix(&field("damage."));
+        }
+        Message::ChangeDamageValue(value) => {
+            let Some(damage) = &mut part.damage else {
+                return;
+            };
+            if let Some(parsed) = drafts.parse(field("damage.value"), value) {
+                damage.value = parsed;
+            }
+        }
+        Message::ChangeDamageInstances(value) => {
+            let Some(damage) = &mut part.damage else {
+                return;
+            };
+            if let Some(parsed) = drafts.parse(field("damage.instances"), value) {
+                damage.instances = parsed;
+            }
+        }
+        Message::ChangeDamageDelay(value) => {
+            let Some(damage) = &mut part.damage else {
+                return;
+            };
+            if let Some(parsed) = drafts.parse(field("damage.delay_between_instances"), value) {
+                damage.delay_between_instances = parsed;
+            }
+        }
+    }
+}
+
+pub fn view<'a>(
+    part: &'a AttackPartConstructor,
+    drafts: &Drafts,
+    errors: &[FieldError],
+    prefix: &str,
+) -> Element<'a, Message> {
+    let field = |name: &str| format!("{prefix}.{name}");
+    let input = |name: &str, value: String| drafts.text(&field(name), value);
+    let errors = |name: &str| drafts.errors(errors, &field(name));
+    let mut fields = column![
+        checked_row(
+            "Time to complete",
+            text_input(
+                "Part time to complete",
+                &input("time_to_complete", format!("{}", part.time_to_complete))
+            )
+            .on_input(Message::ChangeTimeToComplete),
+            errors("time_to_complete")
+        ),
+        checked_row(
+            "Radius",
+            text_input("Part radius", &input("radius", format!("{}", part.radius)))
+                .on_input(Message::ChangeRadius),
+            errors("radius")
+        ),
+        editor_row(
+            "Shape",
+            pick_list(
+                ShapeKind::options(),
+                ShapeKind::of(&part.shape),
+                Message::ChangeShape
+            )
+            .placeholder("Part shape"),
+        ),
+    ]
+    .align_x(Alignment::Start)
+    .spacing(10);
+
+    match &part.shape {
+        AttackShapeConstructor::Circle(circle) => {
+            fields = fields.push(checked_row(
+                "Circle radius",
+                text_input(
+                    "Circle radius",
+                    &input("shape.radius", format!("{}", circle.radius)),
+                )
+                .on_input(Message::ChangeShapeRadius),
+                errors("shape.radius"),
+            ));
+            fields = fields.push(checked_row(
+                "Circle time to complete",
+                text_input(
+                    "Circle time to complete",
+                    &input(
+                        "shape.time_to_complete",
+                        format!("{}", circle.time_to_complete),
+                    ),
+                )
+                .on_input(Message::ChangeShapeTimeToComplete),
+                errors("shape.time_to_complete"),
+            ));
+        }
+        AttackShapeConstructor::Pizza(pizza) => {
+            fields = fields.push(checked_row(
+                "Pizza radius",
+                text_input(
+                    "Pizza radius",
+                    &input("shape.radius", format!("{}", pizza.radius)),
+                )
+                .on_input(Message::ChangeShapeRadius),
+                errors("shape.radius"),
+            ));
+            fields = fields.push(checked_row(
+                "Width angle",
+                text_input(
+                    "Pizza width angle",
+                    &input("shape.width_angle", format!("{:?}", pizza.width_angle)),
+                )
+                .on_input(Message::ChangeWidthAngle),
+                errors("shape.width_angle"),
+            ));
+            fields = fields.push(editor_row(
+                "Order",
+                pick_list(
+                    AttackOrder::options(),
+                    Some(pizza.order.clone()),
+                    Message::ChangeOrder,
+                )
+                .placeholder("Pizza order"),
+            ));
+        }
+        other => {
+            let message = format!("{other:?} is not supported, pick another shape");
+            fields = fields.push(text(message));
+        }
+    }
+
+    fields = fields
+        .push(checkbox("Deals damage", part.damage.is_some()).on_toggle(Message::ToggleDamage));
+    if let Some(damage) = &part.damage {
+        fields = fields.push(checked_row(
+            "Damage",
+            text_input(
+                "Damage value",
+                &input("damage.value", format!("{}", damage.value)),
+            )
+            .on_input(Message::ChangeDamageValue),
+            errors("damage.value"),
+        ));
+        fields = fields.push(checked_row(
+            "Instances",
+            text_input(
+                "Damage instances",
+                &input("damage.instances", format!("{}", damage.instances)),
+            )
+            .on_input(Message::ChangeDamageInstances),
+            errors("damage.instances"),
+        ));
+        fields = fields.push(checked_row(
+            "Delay between instances",
+            text_input(
+                "Delay between instances",
+                &input(
+                    "damage.delay_between_instances",
+                    format!("{}", damage.delay_between_instances),
+                ),
+            )
+            .on_input(Message::ChangeDamageDelay),
+            errors("damage.delay_between_instances"),
+        ));
+    }
+    fields.into()
+}
@@ -0,0 +1,141 @@
+use iced::widget::canvas::{self, Frame, Geometry, Path};
+use iced::{Rectangle, Renderer, Theme, mouse};
+use nalgebra::Point2;
+
+use shared::attack::{AttackPart, AttackShape, ComplexAttack, ComplexAttackConstructor};
+
+use crate::playback::{self, Playback, STEP, TARGET_SIZE, point};
+
+// the longest sequence, every part takes one more step to complete
+pub fn duration(constructor: &ComplexAttackConstructor) -> u128 {
+    constructor
+        .sequences
+        .iter()
+        .map(|sequence| {
+            sequence
+                .parts
+                .iter()
+                .map(|part| part.time_to_complete + STEP)
+                .sum()
+        })
+        .max()
+        .unwrap_or(0)
+        .max(STEP)
+}
+
+// a frame of the attack, updated the same way the boss does it
+pub struct Preview {
+    attack: ComplexAttack,
+    attacker: Point2<f32>,
+    target: Point2<f32>,
+    in_hitbox: bool,
+    touched_at: Option<u128>,
+    completed: bool,
+}
+
+fn active_parts(attack: &ComplexAttack) -> impl Iterator<Item = &AttackPart> {
+    attack
+        .sequences
+        .iter()
+        .filter_map(|sequence| sequence.active_part())
+}
+
+fn touches(attack: &ComplexAttack, target: Point2<f32>) -> bool {
+    active_parts(attack).any(|part| part.intersects_with_circle(target, TARGET_SIZE))
+}
+
+impl Preview {
+    pub fn new(constructor: &ComplexAttackConstructor, playback: &Playback) -> Self {
+        let attacker = playback.attacker_position();
+        let target = playback.target_position();
+        let mut attack = ComplexAttack::aimed(constructor.clone(), attacker, target);
+        let mut touched_at = None;
+        let mut completed = false;
+        for step in 1..=playback.steps(duration(constructor)) {
+            attack.update(STEP);
+            if touched_at.is_none() && touches(&attack, target) {
+                touched_at = Some(step * STEP);
+            }
+            if attack.completed() {
+                completed = true;
+                break;
+            }
+        }
+        let in_hitbox = touches(&attack, target);
+        Self {
+            attack,
+            attacker,
+            target,
+            in_hitbox,
+            touched_at,
+            completed,
+        }
+    }
+    pub fn status(&self) -> String {
+        let phase = if self.completed {
+            "Completed"
+        } else {
+            "Attacking"
+        };
+        match self.touched_at {
+            Some(time) => format!("{phase}, target touched at {time} ms"),
+            None => format!("{phase}, target not touched"),
+        }
+    }
+}
+
+fn draw_part(frame: &mut Frame, part: &AttackPart) {
+    match &part.shape {
+        AttackShape::Circle(circle) => {
+            let center = point(circle.position);
+            playback::fill_attack(frame, &Path::circle(center, circle.radius));
+            // where intersects_with_circle is true for the center of the target
+            let hitbox = Path::circle(center, circle.radius + TARGET_SIZE);
+            playback::stroke_hitbox(frame, &hitbox);
+        }
+        AttackShape::Pizza(pizza) => {
+            let center = point(pizza.position);
+            let angle = pizza.get_base_angle();
+            let (start_angle, end_angle) = pizza.get_angles(angle, pizza.width_radian());
+            let path = playback::circle_segment(center, pizza.get_radius(), start_angle, end_angle);
+            playback::fill_attack(frame, &path);
+            // intersects_with_circle only checks the two sides of the slice
+            let side = |angle: f32| {
+                iced::Point::new(
+                    center.x + pizza.radius * angle.cos(),
+                    center.y + pizza.radius * angle.sin(),
+                )
+            };
+            let hitbox = Path::new(|b| {
+                b.move_to(side(start_angle));
+                b.line_to(center);
+                b.line_to(side(end_angle));
+            });
+            playback::stroke_hitbox(frame, &hitbox);
+        }
+        _ => (),
+    }
+}
+
+impl<Message> canvas::Program<Message> for Preview {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        playback::draw_background(&mut frame);
+        playback::draw_attacker(&mut frame, self.attacker);
+        playback::draw_target(&mut frame, self.target, self.in_hitbox);
+        for part in active_parts(&self.attack) {
+            draw_part(&mut frame, part);
+        }
+        playback::draw_target_center(&mut frame, self.target);
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use shared::validation::FieldError;
+
+// text of inputs which can't be parsed, it's shown instead of
+// the value until the input is fixed, fields are named as in FieldError
+#[derive(Default)]
+pub struct Drafts {
+    texts: HashMap<String, String>,
+}
+
+impl Drafts {
+    pub fn parse<T: FromStr>(&mut self, field: impl Into<String>, value: String) -> Option<T> {
+        let field = field.into();
+        match value.parse() {
+            Ok(parsed) => {
+                self.texts.remove(&field);
+                Some(parsed)
+            }
+            Err(_) => {
+                self.texts.insert(field, value);
+                None
+            }
+        }
+    }
+    // for optional fields, where an empty input is a valid value
+    pub fn discard(&mut self, field: &str) {
+        self.texts.remove(field);
+    }
+    // when the inputs of a part of the form go away, like fields of
+    // a replaced shape, their text can't be fixed anymore
+    pub fn discard_prefix(&mut self, prefix: &str) {
+        self.texts.retain(|field, _| !field.starts_with(prefix));
+    }
+    pub fn text(&self, field: &str, value: String) -> String {
+        self.texts.get(field).cloned().unwrap_or(value)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.texts.clear();
+    }
+    // messages shown under the input of a field
+    pub fn errors(&self, errors: &[FieldError], field: &str) -> Vec<String> {
+        if self.texts.contains_key(field) {
+            return vec!["not a number".to_owned()];
+        }
+        errors
+            .iter()
+            .filter(|error| error.field == field)
+            .map(|error| error.to_string())
+            .collect()
+    }
+}
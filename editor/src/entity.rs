@@ -0,0 +1,24 @@
+use game_data::Entity;
+use shared::validation::Validate;
+
+use crate::play_test::Subject;
+
+// a kind of entity edited with the generic list and item pages,
+// implemented next to the page of every kind
+pub trait EditorEntity: Entity + Validate + PartialEq {
+    // used in messages, like "Error loading attack 1"
+    const LABEL: &'static str;
+
+    fn create(name: String) -> Self;
+    fn name(&self) -> &str;
+    fn set_name(&mut self, name: String);
+
+    // values the list page can filter by, as (label, value)
+    fn properties(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+    // what the play-test button of the item page starts, if it has one
+    fn play_test(&self, _id: u32) -> Option<Subject> {
+        None
+    }
+}
@@ -0,0 +1,113 @@
+use iced::widget::{button, row, text};
+use iced::{Alignment, Element};
+
+const LIMIT: usize = 200;
+
+// undo and redo for the data of an item page, every change
+// is stored as a snapshot of the data before it
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    saved: T,
+    confirm_read_file: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Undo,
+    Redo,
+    ReadFile,
+    ConfirmReadFile,
+    CancelReadFile,
+    WriteFile,
+}
+
+// file operations are done by the page
+pub enum Action {
+    ReadFile,
+    WriteFile,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    pub fn new(data: &T) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            saved: data.clone(),
+            confirm_read_file: false,
+        }
+    }
+    // stores the data as it was before a message, if the message changed it
+    pub fn record(&mut self, before: T, data: &T) {
+        if &before == data {
+            return;
+        }
+        self.undo.push(before);
+        if self.undo.len() > LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+    pub fn is_dirty(&self, data: &T) -> bool {
+        &self.saved != data
+    }
+    // call after the data was saved
+    pub fn mark_saved(&mut self, data: &T) {
+        self.saved = data.clone();
+    }
+    // call after the data was loaded from disk
+    pub fn reset(&mut self, data: &T) {
+        *self = Self::new(data);
+    }
+    pub fn update(&mut self, message: Message, data: &mut T) -> Option<Action> {
+        match message {
+            Message::Undo => {
+                if let Some(previous) = self.undo.pop() {
+                    self.redo.push(std::mem::replace(data, previous));
+                }
+            }
+            Message::Redo => {
+                if let Some(next) = self.redo.pop() {
+                    self.undo.push(std::mem::replace(data, next));
+                }
+            }
+            Message::ReadFile if self.is_dirty(data) => self.confirm_read_file = true,
+            Message::ReadFile | Message::ConfirmReadFile => {
+                self.confirm_read_file = false;
+                return Some(Action::ReadFile);
+            }
+            Message::CancelReadFile => self.confirm_read_file = false,
+            Message::WriteFile => return Some(Action::WriteFile),
+        }
+        None
+    }
+    // saving is disabled while the data is invalid
+    pub fn view(&self, data: &T, can_save: bool) -> Element<'_, Message> {
+        if self.confirm_read_file {
+            return row![
+                text("Discard unsaved changes?"),
+                button("Discard and reload")
+                    .on_press(Message::ConfirmReadFile)
+                    .style(button::danger),
+                button("Cancel").on_press(Message::CancelReadFile),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .into();
+        }
+        let undo = (!self.undo.is_empty()).then_some(Message::Undo);
+        let redo = (!self.redo.is_empty()).then_some(Message::Redo);
+        let mut toolbar = row![
+            button("Undo").on_press_maybe(undo),
+            button("Redo").on_press_maybe(redo),
+            button("Reload from disk").on_press(Message::ReadFile),
+            button("Save").on_press_maybe(can_save.then_some(Message::WriteFile)),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10);
+        if self.is_dirty(data) {
+            toolbar = toolbar.push(text("Unsaved changes"));
+        }
+        toolbar.into()
+    }
+}
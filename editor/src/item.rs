@@ -0,0 +1,202 @@
+use std::fmt;
+use std::time::Instant;
+
+use iced::widget::{button, column, row, text};
+use iced::{Alignment, Element};
+
+use game_data::Usage;
+use shared::validation::{FieldError, Validate};
+
+use crate::common::{load_used_by, used_by_view};
+use crate::drafts::Drafts;
+use crate::entity::EditorEntity;
+use crate::game_data;
+use crate::history::{self, History};
+use crate::list;
+use crate::play_test::{self, PlayTest};
+
+// the fields of an item page for one kind of entity,
+// loading, saving, history and validation are done by `Page`
+pub trait Form: Sized {
+    type Entity: EditorEntity;
+    type Message: fmt::Debug + Clone;
+
+    fn new(data: &Self::Entity) -> Self;
+    fn update(&mut self, message: Self::Message, data: &mut Self::Entity, drafts: &mut Drafts);
+    fn view<'a>(
+        &'a self,
+        data: &'a Self::Entity,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Self::Message>;
+
+    // only attack previews are animated
+    fn is_playing(&self) -> bool {
+        false
+    }
+    fn tick(&mut self, _data: &Self::Entity, _now: Instant) {}
+}
+
+pub struct Page<F: Form> {
+    id: u32,
+    data: F::Entity,
+    form: F,
+    drafts: Drafts,
+    history: History<F::Entity>,
+    used_by: Vec<Usage>,
+    play_test: Option<PlayTest>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message<M> {
+    History(history::Message),
+    Form(M),
+    StartPlayTest,
+    StopPlayTest,
+    PlayTest(play_test::Message),
+}
+
+pub fn load_by_id<T: EditorEntity>(id: u32) -> Option<T> {
+    game_data()
+        .reload(id)
+        .inspect_err(|e| println!("Error loading {} {id}: {e}", T::LABEL))
+        .ok()
+}
+
+pub fn save_by_id<T: EditorEntity>(item: &T, id: u32) {
+    if let Err(e) = game_data().save(id, item) {
+        println!("Error saving {} {id}: {e}", T::LABEL);
+    }
+}
+
+pub fn delete_file_by_id<T: EditorEntity>(id: u32) {
+    if let Err(e) = game_data().delete::<T>(id) {
+        println!("Error removing file for {} {id}: {e}", T::LABEL);
+    }
+}
+
+impl<F: Form> Page<F> {
+    pub fn load_by_id(id: u32) -> Option<Self> {
+        let data = load_by_id(id)?;
+        Some(Page {
+            id,
+            form: F::new(&data),
+            drafts: Drafts::default(),
+            history: History::new(&data),
+            used_by: load_used_by::<F::Entity>(id),
+            play_test: None,
+            data,
+        })
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.history.is_dirty(&self.data)
+    }
+    pub fn is_playing(&self) -> bool {
+        self.form.is_playing() || self.play_test.is_some()
+    }
+    pub fn tick(&mut self, now: Instant) {
+        self.form.tick(&self.data, now);
+        if let Some(play_test) = &mut self.play_test {
+            play_test.tick(now);
+        }
+    }
+    pub fn update(&mut self, message: Message<F::Message>) {
+        let before = self.data.clone();
+        match message {
+            Message::History(message) => {
+                self.drafts.clear();
+                match self.history.update(message, &mut self.data) {
+                    Some(history::Action::ReadFile) => {
+                        if let Some(data) = load_by_id(self.id) {
+                            self.data = data;
+                            self.history.reset(&self.data);
+                        }
+                    }
+                    Some(history::Action::WriteFile) => {
+                        save_by_id(&self.data, self.id);
+                        let name = self.data.name().to_owned();
+                        list::update_name_for::<F::Entity>(self.id, name);
+                        self.history.mark_saved(&self.data);
+                    }
+                    None => (),
+                }
+                self.apply_to_play_test(&before);
+                return;
+            }
+            Message::Form(message) => {
+                self.form.update(message, &mut self.data, &mut self.drafts);
+            }
+            Message::StartPlayTest => {
+                let Some(subject) = self.data.play_test(self.id) else {
+                    return;
+                };
+                self.play_test = PlayTest::start(&subject)
+                    .inspect_err(|e| println!("Error starting play-test: {e}"))
+                    .ok();
+                return;
+            }
+            Message::StopPlayTest => {
+                self.play_test = None;
+                return;
+            }
+            Message::PlayTest(message) => {
+                if let Some(play_test) = &mut self.play_test {
+                    play_test.update(message);
+                }
+                return;
+            }
+        }
+        self.apply_to_play_test(&before);
+        self.history.record(before, &self.data);
+    }
+    // a running play-test picks up every change of the data
+    fn apply_to_play_test(&mut self, before: &F::Entity) {
+        if before == &self.data {
+            return;
+        }
+        if let Some(play_test) = &mut self.play_test
+            && let Some(subject) = self.data.play_test(self.id)
+        {
+            play_test.apply(&subject);
+        }
+    }
+    fn play_test_view(&self) -> Option<Element<'_, Message<F::Message>>> {
+        self.data.play_test(self.id)?;
+        let Some(play_test) = &self.play_test else {
+            return Some(button("Play-test").on_press(Message::StartPlayTest).into());
+        };
+        let buttons = row![
+            button("Restart").on_press(Message::StartPlayTest),
+            button("Stop").on_press(Message::StopPlayTest),
+        ]
+        .spacing(10);
+        let view = column![buttons, play_test.view().map(Message::PlayTest)]
+            .align_x(Alignment::Center)
+            .spacing(10);
+        Some(view.into())
+    }
+    pub fn view(&self) -> Element<'_, Message<F::Message>> {
+        let errors = self.data.validate();
+        let can_save = errors.is_empty() && self.drafts.is_empty();
+        let mut content = row![
+            self.form
+                .view(&self.data, &self.drafts, &errors)
+                .map(Message::Form),
+        ]
+        .spacing(20);
+        if let Some(play_test) = self.play_test_view() {
+            content = content.push(play_test);
+        }
+        column![
+            self.history
+                .view(&self.data, can_save)
+                .map(Message::History),
+            text(format!("Id {}", self.id)),
+            content,
+            used_by_view(&self.used_by),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+}
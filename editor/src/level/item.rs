@@ -1,116 +1,387 @@
-use iced::widget::{button, column, container, pick_list, row, text, text_input};
+use iced::widget::{button, canvas, column, container, pick_list, row, text, text_input};
 use iced::{Alignment, Element};
+use nalgebra::Point2;
 
-use shared::level::{Level, LevelNpcInfo};
+use shared::level::{ActivationTrigger, Level, LevelNpcInfo, Obstacle};
+use shared::list::EntryInfo;
+use shared::npc::NpcConstructor;
+use shared::validation::FieldError;
 
-use super::get_item_file_path;
-use crate::common::editor_row;
-use crate::npc::list::{NpcInfo, load_available_npc_list};
+use super::layout::{self, HEIGHT, Layout, Target, WIDTH};
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+use crate::item;
+use crate::list::load_available;
 
-pub struct Page {
-    id: u32,
-    data: Level,
+pub struct Form {
     selected: Option<LevelNpcInfo>,
     available_npc_list: Vec<LevelNpcInfo>,
-}
-
-impl Page {
-    pub fn load_by_id(id: u32) -> Self {
-        let available_npc_list = make_picker_items(load_available_npc_list());
-        Page {
-            id,
-            data: load_by_id(id),
-            selected: None,
-            available_npc_list,
-        }
-    }
+    selected_target: Option<Target>, // shown in the inspector
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    ReadFile,
-    WriteFile,
     ChangeName(String),
+    ChangeBoundsWidth(String),
+    ChangeBoundsHeight(String),
     SelectNpc(LevelNpcInfo),
     AddNpc(LevelNpcInfo),
     RemoveNpc(usize),
+    ChangeNpcHp(usize, String),
+    ChangeNpcFacing(usize, String),
+    ChangeNpcTrigger(usize, ActivationTrigger),
+    ChangeTriggerDistance(usize, String),
+    ChangeTriggerDelay(usize, String),
+    AddPlayerSpawn,
+    RemovePlayerSpawn(usize),
+    AddObstacle,
+    RemoveObstacle(usize),
+    ChangeObstacleWidth(usize, String),
+    ChangeObstacleHeight(usize, String),
+    Layout(layout::Message),
 }
 
-fn load_by_id(id: u32) -> Level {
-    let file_path = get_item_file_path(id);
-    let contents = std::fs::read(file_path).expect("Should read Level from a file");
-    serde_json::from_slice(&contents).expect("Should decode Level")
+fn make_picker_items(npc_list: Vec<EntryInfo>) -> Vec<LevelNpcInfo> {
+    npc_list
+        .into_iter()
+        .filter(|item| item.status.is_active())
+        .map(|EntryInfo { id, name, .. }| LevelNpcInfo {
+            id,
+            name,
+            ..LevelNpcInfo::default()
+        })
+        .collect()
 }
 
-pub fn save_by_id(level: &Level, id: u32) {
-    let file_path = get_item_file_path(id);
-    let contents = serde_json::to_vec_pretty(level).expect("Should encode Level");
-    std::fs::write(file_path, contents).expect("Should write Level to a file");
+fn center(level: &Level) -> Point2<f32> {
+    Point2::new(
+        (level.bounds.width / 2.0).round(),
+        (level.bounds.height / 2.0).round(),
+    )
 }
 
-pub(super) fn delete_file_by_id(id: u32) {
-    let file_path = get_item_file_path(id);
-    if let Err(e) = std::fs::remove_file(file_path) {
-        println!("Error removing file for level {id}: {e}");
-    }
+fn position_text(position: Point2<f32>) -> String {
+    format!("Position: {}, {}", position.x, position.y)
 }
 
-fn make_picker_items(npc_list: Vec<NpcInfo>) -> Vec<LevelNpcInfo> {
-    npc_list
-        .into_iter()
-        .filter(|item| item.status.is_active())
-        .map(|NpcInfo { id, name, .. }| LevelNpcInfo { id, name })
-        .collect()
+// errors of things which are changed by dragging on the layout
+fn is_layout_error(error: &FieldError) -> bool {
+    error.field.starts_with("player_spawns")
+        || error.field == "npc_list"
+        || error.field.ends_with(".position")
 }
 
-impl Page {
-    pub fn update(&mut self, message: Message) {
-        match message {
-            Message::ReadFile => {
-                self.data = load_by_id(self.id);
+impl Form {
+    // indexes of the selection change when something is removed
+    fn removed(&mut self, drafts: &mut Drafts) {
+        self.selected_target = None;
+        drafts.clear();
+    }
+    // the inspector shows only the selected target, drafts of the previous one are dropped
+    fn select(&mut self, target: Option<Target>, drafts: &mut Drafts) {
+        if self.selected_target == target {
+            return;
+        }
+        match self.selected_target {
+            Some(Target::Npc(index)) => drafts.discard_prefix(&format!("npc_list.{index}.")),
+            Some(Target::Obstacle(index)) => drafts.discard_prefix(&format!("obstacles.{index}.")),
+            _ => (),
+        }
+        self.selected_target = target;
+    }
+    fn drop(data: &mut Level, target: Target, point: Point2<f32>) {
+        match target {
+            Target::Npc(index) => {
+                if let Some(npc) = data.npc_list.get_mut(index) {
+                    npc.position = point;
+                }
             }
-            Message::WriteFile => {
-                save_by_id(&self.data, self.id);
-                super::list::update_name_for(self.id, self.data.name.clone());
+            Target::PlayerSpawn(index) => {
+                if let Some(spawn) = data.player_spawns.get_mut(index) {
+                    *spawn = point;
+                }
             }
+            Target::Obstacle(index) => {
+                if let Some(obstacle) = data.obstacles.get_mut(index) {
+                    obstacle.position = point;
+                }
+            }
+            Target::ObstacleCorner(index) => {
+                if let Some(obstacle) = data.obstacles.get_mut(index) {
+                    layout::resize_obstacle(obstacle, point);
+                }
+            }
+            Target::BoundsCorner => layout::resize_bounds(data, point),
+        }
+    }
+    fn npc_view<'a>(
+        index: usize,
+        npc: &'a LevelNpcInfo,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let field = |name: &str| format!("npc_list.{index}.{name}");
+        let hp = npc.hp.map(|hp| hp.to_string()).unwrap_or_default();
+        let facing = format!("{}", npc.facing.to_degrees().round());
+        let mut trigger = column![editor_row(
+            "Trigger",
+            pick_list(
+                ActivationTrigger::options(),
+                Some(npc.trigger.clone()),
+                move |trigger| Message::ChangeNpcTrigger(index, trigger)
+            )
+        )]
+        .spacing(10);
+        match &npc.trigger {
+            ActivationTrigger::OnStart => (),
+            ActivationTrigger::PlayerInRange { distance } => {
+                let field = field("trigger.distance");
+                trigger = trigger.push(checked_row(
+                    "Distance",
+                    text_input(
+                        "Player distance",
+                        &drafts.text(&field, distance.to_string()),
+                    )
+                    .on_input(move |value| Message::ChangeTriggerDistance(index, value)),
+                    drafts.errors(errors, &field),
+                ));
+            }
+            ActivationTrigger::AfterDelay { delay } => {
+                let field = field("trigger.delay");
+                trigger = trigger.push(checked_row(
+                    "Delay, ms",
+                    text_input("Delay after start", &drafts.text(&field, delay.to_string()))
+                        .on_input(move |value| Message::ChangeTriggerDelay(index, value)),
+                    drafts.errors(errors, &field),
+                ));
+            }
+        }
+        column![
+            text(format!("Npc {npc}")),
+            text(position_text(npc.position)),
+            checked_row(
+                "Hp",
+                text_input("Npc hp if empty", &drafts.text(&field("hp"), hp))
+                    .on_input(move |value| Message::ChangeNpcHp(index, value)),
+                drafts.errors(errors, &field("hp"))
+            ),
+            checked_row(
+                "Facing, degrees",
+                text_input("Facing", &drafts.text(&field("facing"), facing))
+                    .on_input(move |value| Message::ChangeNpcFacing(index, value)),
+                drafts.errors(errors, &field("facing"))
+            ),
+            trigger,
+            button("delete").on_press(Message::RemoveNpc(index)),
+        ]
+        .spacing(10)
+        .into()
+    }
+    fn obstacle_view<'a>(
+        index: usize,
+        obstacle: &Obstacle,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let width = format!("obstacles.{index}.width");
+        let height = format!("obstacles.{index}.height");
+        column![
+            text(format!("Obstacle {}", index + 1)),
+            text(position_text(obstacle.position)),
+            checked_row(
+                "Width",
+                text_input(
+                    "Obstacle width",
+                    &drafts.text(&width, obstacle.width.to_string())
+                )
+                .on_input(move |value| Message::ChangeObstacleWidth(index, value)),
+                drafts.errors(errors, &width)
+            ),
+            checked_row(
+                "Height",
+                text_input(
+                    "Obstacle height",
+                    &drafts.text(&height, obstacle.height.to_string())
+                )
+                .on_input(move |value| Message::ChangeObstacleHeight(index, value)),
+                drafts.errors(errors, &height)
+            ),
+            button("delete").on_press(Message::RemoveObstacle(index)),
+        ]
+        .spacing(10)
+        .into()
+    }
+    fn inspector_view<'a>(
+        &self,
+        data: &'a Level,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let hint = || text("Select something on the layout to change it").into();
+        match self.selected_target {
+            Some(Target::Npc(index)) => match data.npc_list.get(index) {
+                Some(npc) => Self::npc_view(index, npc, drafts, errors),
+                None => hint(),
+            },
+            Some(Target::PlayerSpawn(index)) => match data.player_spawns.get(index) {
+                Some(spawn) => column![
+                    text(format!("Player spawn {}", index + 1)),
+                    text(position_text(*spawn)),
+                    button("delete").on_press(Message::RemovePlayerSpawn(index)),
+                ]
+                .spacing(10)
+                .into(),
+                None => hint(),
+            },
+            Some(Target::Obstacle(index)) => match data.obstacles.get(index) {
+                Some(obstacle) => Self::obstacle_view(index, obstacle, drafts, errors),
+                None => hint(),
+            },
+            _ => hint(),
+        }
+    }
+}
+
+impl item::Form for Form {
+    type Entity = Level;
+    type Message = Message;
+
+    fn new(_data: &Level) -> Self {
+        Form {
+            selected: None,
+            available_npc_list: make_picker_items(load_available::<NpcConstructor>()),
+            selected_target: None,
+        }
+    }
+    fn update(&mut self, message: Message, data: &mut Level, drafts: &mut Drafts) {
+        match message {
             Message::ChangeName(value) => {
-                self.data.name = value;
+                data.name = value;
+            }
+            Message::ChangeBoundsWidth(value) => {
+                if let Some(parsed) = drafts.parse("bounds.width", value) {
+                    data.bounds.width = parsed;
+                }
+            }
+            Message::ChangeBoundsHeight(value) => {
+                if let Some(parsed) = drafts.parse("bounds.height", value) {
+                    data.bounds.height = parsed;
+                }
             }
             Message::SelectNpc(npc) => {
                 self.selected = Some(npc);
             }
             Message::AddNpc(npc) => {
-                self.data.npc_list.push(npc);
+                let position = center(data);
+                data.npc_list.push(LevelNpcInfo { position, ..npc });
+                self.select(Some(Target::Npc(data.npc_list.len() - 1)), drafts);
             }
             Message::RemoveNpc(index) => {
-                if index >= self.data.npc_list.len() {
+                if index < data.npc_list.len() {
+                    data.npc_list.remove(index);
+                    self.removed(drafts);
+                }
+            }
+            Message::ChangeNpcHp(index, value) => {
+                let field = format!("npc_list.{index}.hp");
+                let Some(npc) = data.npc_list.get_mut(index) else {
                     return;
+                };
+                if value.trim().is_empty() {
+                    drafts.discard(&field);
+                    npc.hp = None;
+                } else if let Some(parsed) = drafts.parse(field, value) {
+                    npc.hp = Some(parsed);
+                }
+            }
+            Message::ChangeNpcFacing(index, value) => {
+                let field = format!("npc_list.{index}.facing");
+                if let Some(npc) = data.npc_list.get_mut(index)
+                    && let Some(parsed) = drafts.parse::<f32>(field, value)
+                {
+                    npc.facing = parsed.to_radians();
+                }
+            }
+            Message::ChangeNpcTrigger(index, trigger) => {
+                if let Some(npc) = data.npc_list.get_mut(index)
+                    && !npc.trigger.same_kind(&trigger)
+                {
+                    npc.trigger = trigger;
+                    // the inputs of the previous kind are gone
+                    drafts.discard_prefix(&format!("npc_list.{index}.trigger."));
+                }
+            }
+            Message::ChangeTriggerDistance(index, value) => {
+                let field = format!("npc_list.{index}.trigger.distance");
+                if let Some(npc) = data.npc_list.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    npc.trigger = ActivationTrigger::PlayerInRange { distance: parsed };
+                }
+            }
+            Message::ChangeTriggerDelay(index, value) => {
+                let field = format!("npc_list.{index}.trigger.delay");
+                if let Some(npc) = data.npc_list.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    npc.trigger = ActivationTrigger::AfterDelay { delay: parsed };
+                }
+            }
+            Message::AddPlayerSpawn => {
+                data.player_spawns.push(center(data));
+                self.select(
+                    Some(Target::PlayerSpawn(data.player_spawns.len() - 1)),
+                    drafts,
+                );
+            }
+            Message::RemovePlayerSpawn(index) => {
+                if index < data.player_spawns.len() {
+                    data.player_spawns.remove(index);
+                    self.removed(drafts);
+                }
+            }
+            Message::AddObstacle => {
+                data.obstacles.push(Obstacle::new(center(data)));
+                self.select(Some(Target::Obstacle(data.obstacles.len() - 1)), drafts);
+            }
+            Message::RemoveObstacle(index) => {
+                if index < data.obstacles.len() {
+                    data.obstacles.remove(index);
+                    self.removed(drafts);
                 }
-                self.data.npc_list.remove(index);
+            }
+            Message::ChangeObstacleWidth(index, value) => {
+                let field = format!("obstacles.{index}.width");
+                if let Some(obstacle) = data.obstacles.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    obstacle.width = parsed;
+                }
+            }
+            Message::ChangeObstacleHeight(index, value) => {
+                let field = format!("obstacles.{index}.height");
+                if let Some(obstacle) = data.obstacles.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    obstacle.height = parsed;
+                }
+            }
+            Message::Layout(layout::Message::Select(target)) => {
+                self.select(target, drafts);
+            }
+            Message::Layout(layout::Message::Drop(target, point)) => {
+                Self::drop(data, target, point);
+                // the inputs show the dropped values
+                drafts.clear();
             }
         }
     }
-
-    pub fn view(&self) -> Element<'_, Message> {
-        let mut contents = column![
-            row![
-                button("Reload from disk").on_press(Message::ReadFile),
-                button("Save").on_press(Message::WriteFile),
-            ]
-            .spacing(10)
-        ]
-        .align_x(Alignment::Center)
-        .spacing(10);
-
-        let mut npc_list = column![].align_x(Alignment::Center).spacing(10);
-        for (index, npc_id) in self.data.npc_list.iter().enumerate() {
-            let npc_row = row![
-                text(format!("Npc id: {npc_id}")),
-                button("delete").on_press(Message::RemoveNpc(index)),
-            ]
-            .spacing(10);
-            npc_list = npc_list.push(npc_row);
-        }
+    fn view<'a>(
+        &'a self,
+        data: &'a Level,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
         let message_add = self.selected.clone().map(Message::AddNpc);
         let add_npc_row = row![
             pick_list(
@@ -122,21 +393,53 @@ impl Page {
         ]
         .spacing(10);
         let level_details_column = column![
-            text(format!("Id {}", self.id)),
             editor_row(
                 "Name",
-                text_input("Level name", &self.data.name).on_input(Message::ChangeName)
+                text_input("Level name", &data.name).on_input(Message::ChangeName)
+            ),
+            checked_row(
+                "Width",
+                text_input(
+                    "Arena width",
+                    &drafts.text("bounds.width", data.bounds.width.to_string())
+                )
+                .on_input(Message::ChangeBoundsWidth),
+                drafts.errors(errors, "bounds.width")
+            ),
+            checked_row(
+                "Height",
+                text_input(
+                    "Arena height",
+                    &drafts.text("bounds.height", data.bounds.height.to_string())
+                )
+                .on_input(Message::ChangeBoundsHeight),
+                drafts.errors(errors, "bounds.height")
             ),
             text("Add npc:"),
             add_npc_row,
-            text("Level npc list:"),
-            npc_list,
+            row![
+                button("Add player spawn").on_press(Message::AddPlayerSpawn),
+                button("Add obstacle").on_press(Message::AddObstacle),
+            ]
+            .spacing(10),
+            self.inspector_view(data, drafts, errors),
         ]
         .align_x(Alignment::Start)
         .spacing(10);
         let level_details = container(level_details_column).width(300);
-        contents = contents.push(level_details);
 
-        contents.into()
+        let mut layout_column = column![
+            canvas(Layout::new(data, self.selected_target))
+                .width(WIDTH)
+                .height(HEIGHT),
+            text("Drag to move, drag the corners to resize"),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10);
+        for error in errors.iter().filter(|error| is_layout_error(error)) {
+            layout_column = layout_column.push(text(error.to_string()).style(text::danger));
+        }
+        let layout = Element::from(layout_column).map(Message::Layout);
+        row![level_details, layout].spacing(20).into()
     }
 }
@@ -0,0 +1,336 @@
+use iced::widget::canvas::{self, Event, Frame, Geometry, Path, Stroke, Text, stroke};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme, Vector, mouse};
+use nalgebra::Point2;
+
+use shared::level::{Level, Obstacle};
+
+pub const WIDTH: f32 = 600.0;
+pub const HEIGHT: f32 = 450.0;
+// radius of markers in level units, same as the default npc and hero
+const NPC_SIZE: f32 = 30.0;
+const PLAYER_SIZE: f32 = 20.0;
+// corners are grabbed within this distance in pixels
+const HANDLE_SIZE: f32 = 6.0;
+// room around the bounds for making them bigger
+const MARGIN: f32 = 1.25;
+
+// something on the layout which can be dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Npc(usize),
+    PlayerSpawn(usize),
+    Obstacle(usize),
+    ObstacleCorner(usize),
+    BoundsCorner,
+}
+
+impl Target {
+    // corners resize what is selected, the selection stays
+    fn selection(self) -> Option<Target> {
+        match self {
+            Target::ObstacleCorner(index) => Some(Target::Obstacle(index)),
+            Target::BoundsCorner => None,
+            target => Some(target),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Option<Target>),
+    // a drag ended, the point is in level units
+    Drop(Target, Point2<f32>),
+}
+
+// a drag changes the level only when the button is released,
+// so one drag is one step of undo
+pub struct Drag {
+    target: Target,
+    offset: Vector, // from the grabbed point to the cursor, level units
+    point: Point,   // where the target is dropped if released now
+}
+
+#[derive(Default)]
+pub struct State {
+    drag: Option<Drag>,
+}
+
+pub struct Layout<'a> {
+    level: &'a Level,
+    selected: Option<Target>,
+}
+
+fn point(position: Point2<f32>) -> Point {
+    Point::new(position.x, position.y)
+}
+
+impl<'a> Layout<'a> {
+    pub fn new(level: &'a Level, selected: Option<Target>) -> Self {
+        Self { level, selected }
+    }
+    // pixels per level unit
+    fn scale(&self) -> f32 {
+        let width = self.level.bounds.width.max(1.0) * MARGIN;
+        let height = self.level.bounds.height.max(1.0) * MARGIN;
+        (WIDTH / width).min(HEIGHT / height)
+    }
+    fn to_level(&self, position: Point) -> Point {
+        Point::new(position.x / self.scale(), position.y / self.scale())
+    }
+    // the grabbed point of a target in level units
+    fn anchor(&self, target: Target) -> Option<Point> {
+        let level = self.level;
+        let anchor = match target {
+            Target::Npc(index) => point(level.npc_list.get(index)?.position),
+            Target::PlayerSpawn(index) => point(*level.player_spawns.get(index)?),
+            Target::Obstacle(index) => point(level.obstacles.get(index)?.position),
+            Target::ObstacleCorner(index) => {
+                let obstacle = level.obstacles.get(index)?;
+                point(obstacle.position) + Vector::new(obstacle.width, obstacle.height)
+            }
+            Target::BoundsCorner => Point::new(level.bounds.width, level.bounds.height),
+        };
+        Some(anchor)
+    }
+    // corners first, they are small and drawn over everything
+    fn target_at(&self, position: Point) -> Option<Target> {
+        let level = self.level;
+        let handle = HANDLE_SIZE / self.scale();
+        let near = |target: Target| {
+            self.anchor(target)
+                .is_some_and(|anchor| anchor.distance(position) <= handle)
+        };
+        if let Some(Target::Obstacle(index)) = self.selected
+            && near(Target::ObstacleCorner(index))
+        {
+            return Some(Target::ObstacleCorner(index));
+        }
+        if near(Target::BoundsCorner) {
+            return Some(Target::BoundsCorner);
+        }
+        let position_2 = Point2::new(position.x, position.y);
+        let npc = level
+            .npc_list
+            .iter()
+            .rposition(|npc| point(npc.position).distance(position) <= NPC_SIZE)
+            .map(Target::Npc);
+        let spawn = || {
+            level
+                .player_spawns
+                .iter()
+                .rposition(|spawn| point(*spawn).distance(position) <= PLAYER_SIZE)
+                .map(Target::PlayerSpawn)
+        };
+        let obstacle = || {
+            level
+                .obstacles
+                .iter()
+                .rposition(|obstacle| obstacle.contains(position_2))
+                .map(Target::Obstacle)
+        };
+        npc.or_else(spawn).or_else(obstacle)
+    }
+    // the level with the dragged target moved to the cursor
+    fn dragged(&self, state: &State) -> Level {
+        let mut level = self.level.clone();
+        let Some(drag) = &state.drag else {
+            return level;
+        };
+        let moved = Point2::new(drag.point.x, drag.point.y);
+        match drag.target {
+            Target::Npc(index) => {
+                if let Some(npc) = level.npc_list.get_mut(index) {
+                    npc.position = moved;
+                }
+            }
+            Target::PlayerSpawn(index) => {
+                if let Some(spawn) = level.player_spawns.get_mut(index) {
+                    *spawn = moved;
+                }
+            }
+            Target::Obstacle(index) => {
+                if let Some(obstacle) = level.obstacles.get_mut(index) {
+                    obstacle.position = moved;
+                }
+            }
+            Target::ObstacleCorner(index) => {
+                if let Some(obstacle) = level.obstacles.get_mut(index) {
+                    resize_obstacle(obstacle, moved);
+                }
+            }
+            Target::BoundsCorner => resize_bounds(&mut level, moved),
+        }
+        level
+    }
+}
+
+// dropped points are rounded to whole level units
+pub fn snap(position: Point) -> Point2<f32> {
+    Point2::new(position.x.round(), position.y.round())
+}
+
+pub fn resize_obstacle(obstacle: &mut Obstacle, corner: Point2<f32>) {
+    obstacle.width = (corner.x - obstacle.position.x).max(1.0);
+    obstacle.height = (corner.y - obstacle.position.y).max(1.0);
+}
+
+pub fn resize_bounds(level: &mut Level, corner: Point2<f32>) {
+    level.bounds.width = corner.x.max(1.0);
+    level.bounds.height = corner.y.max(1.0);
+}
+
+fn outline(color: Color) -> Stroke<'static> {
+    Stroke {
+        style: stroke::Style::Solid(color),
+        width: 2.0,
+        ..Stroke::default()
+    }
+}
+
+const SELECTED: Color = Color::from_rgb(1.0, 0.86, 0.0);
+
+fn draw_handle(frame: &mut Frame, position: Point) {
+    let size = Size::new(HANDLE_SIZE * 2.0, HANDLE_SIZE * 2.0);
+    let corner = position - Vector::new(HANDLE_SIZE, HANDLE_SIZE);
+    frame.fill(&Path::rectangle(corner, size), SELECTED);
+}
+
+fn draw_label(frame: &mut Frame, content: String, position: Point) {
+    frame.fill_text(Text {
+        content,
+        position,
+        color: Color::WHITE,
+        size: 12.0.into(),
+        ..Text::default()
+    });
+}
+
+impl canvas::Program<Message> for Layout<'_> {
+    type State = State;
+
+    fn update(
+        &self,
+        state: &mut State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        let Event::Mouse(event) = event else {
+            return None;
+        };
+        match event {
+            mouse::Event::ButtonPressed(mouse::Button::Left) => {
+                let position = self.to_level(cursor.position_in(bounds)?);
+                let target = self.target_at(position);
+                if let Some(target) = target
+                    && let Some(anchor) = self.anchor(target)
+                {
+                    state.drag = Some(Drag {
+                        target,
+                        offset: position - anchor,
+                        point: anchor,
+                    });
+                }
+                let selected = match target {
+                    Some(target) => target.selection().or(self.selected),
+                    None => None,
+                };
+                Some(canvas::Action::publish(Message::Select(selected)).and_capture())
+            }
+            mouse::Event::CursorMoved { .. } => {
+                let drag = state.drag.as_mut()?;
+                let position = self.to_level(cursor.position_from(bounds.position())?);
+                drag.point = position - drag.offset;
+                Some(canvas::Action::request_redraw().and_capture())
+            }
+            mouse::Event::ButtonReleased(mouse::Button::Left) => {
+                let drag = state.drag.take()?;
+                let message = Message::Drop(drag.target, snap(drag.point));
+                Some(canvas::Action::publish(message).and_capture())
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        frame.fill(
+            &Path::rectangle(Point::ORIGIN, frame.size()),
+            Color::from_rgb8(20, 20, 25),
+        );
+        let level = self.dragged(state);
+        let scale = self.scale();
+        let to_pixels = |position: Point| Point::new(position.x * scale, position.y * scale);
+        frame.scale(scale);
+        // labels and handles keep their size in pixels
+        let mut overlay = Frame::new(renderer, bounds.size());
+
+        let arena = Size::new(level.bounds.width, level.bounds.height);
+        frame.fill(
+            &Path::rectangle(Point::ORIGIN, arena),
+            Color::from_rgb8(30, 30, 40),
+        );
+        for (index, obstacle) in level.obstacles.iter().enumerate() {
+            let size = Size::new(obstacle.width, obstacle.height);
+            let path = Path::rectangle(point(obstacle.position), size);
+            frame.fill(&path, Color::from_rgb8(90, 90, 90));
+            if self.selected == Some(Target::Obstacle(index)) {
+                frame.stroke(&path, outline(SELECTED));
+                let corner =
+                    point(obstacle.position) + Vector::new(obstacle.width, obstacle.height);
+                draw_handle(&mut overlay, to_pixels(corner));
+            }
+        }
+        for (index, spawn) in level.player_spawns.iter().enumerate() {
+            let path = Path::circle(point(*spawn), PLAYER_SIZE);
+            frame.fill(&path, Color::from_rgb8(80, 200, 80));
+            if self.selected == Some(Target::PlayerSpawn(index)) {
+                frame.stroke(&path, outline(SELECTED));
+            }
+            let label = format!("P{}", index + 1);
+            draw_label(&mut overlay, label, to_pixels(point(*spawn)));
+        }
+        for (index, npc) in level.npc_list.iter().enumerate() {
+            let center = point(npc.position);
+            let path = Path::circle(center, NPC_SIZE);
+            frame.fill(&path, Color::from_rgb8(200, 80, 80));
+            let facing = Vector::new(npc.facing.cos(), npc.facing.sin()) * NPC_SIZE * 1.5;
+            frame.stroke(&Path::line(center, center + facing), outline(Color::WHITE));
+            if self.selected == Some(Target::Npc(index)) {
+                frame.stroke(&path, outline(SELECTED));
+            }
+            draw_label(&mut overlay, npc.to_string(), to_pixels(center));
+        }
+        let corner = Point::new(level.bounds.width, level.bounds.height);
+        draw_handle(&mut overlay, to_pixels(corner));
+        vec![frame.into_geometry(), overlay.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if state.drag.is_some() {
+            return mouse::Interaction::Grabbing;
+        }
+        let Some(position) = cursor.position_in(bounds) else {
+            return mouse::Interaction::default();
+        };
+        match self.target_at(self.to_level(position)) {
+            Some(Target::ObstacleCorner(_) | Target::BoundsCorner) => {
+                mouse::Interaction::ResizingDiagonallyDown
+            }
+            Some(_) => mouse::Interaction::Grab,
+            None => mouse::Interaction::default(),
+        }
+    }
+}
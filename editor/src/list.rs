@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+
+use iced::widget::{
+    Container, button, checkbox, column, container, horizontal_space, pick_list, row, text,
+    text_input, vertical_rule,
+};
+use iced::{Alignment, Element, Length};
+
+use game_data::Usage;
+use shared::list::{EntryInfo, EntryList, EntryStatus};
+
+use crate::common::confirm_delete_view;
+use crate::entity::EditorEntity;
+use crate::game_data;
+use crate::item;
+
+// list of every entity of one kind, from its list.json
+pub struct Page<T> {
+    data: EntryList,
+    // see EditorEntity::properties, by id
+    properties: HashMap<u32, Vec<(&'static str, String)>>,
+    filter: Filter,
+    sort_by: SortBy,
+    // id of the entry and its tags separated by commas
+    editing_tags: Option<(u32, String)>,
+    deleting: Option<(u32, Vec<Usage>)>,
+    kind: PhantomData<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Id,
+    Name,
+    Status,
+}
+
+impl SortBy {
+    const fn options() -> [SortBy; 3] {
+        [SortBy::Id, SortBy::Name, SortBy::Status]
+    }
+    fn sort(self, entries: &mut [&EntryInfo]) {
+        match self {
+            SortBy::Id => entries.sort_by_key(|entry| entry.id),
+            SortBy::Name => entries.sort_by_key(|entry| entry.name.to_lowercase()),
+            // active first
+            SortBy::Status => entries.sort_by_key(|entry| !entry.status.is_active()),
+        }
+    }
+}
+
+impl Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    Any,
+    Active,
+    Hidden,
+}
+
+impl StatusFilter {
+    const fn options() -> [StatusFilter; 3] {
+        [
+            StatusFilter::Any,
+            StatusFilter::Active,
+            StatusFilter::Hidden,
+        ]
+    }
+    fn matches(self, status: &EntryStatus) -> bool {
+        match self {
+            StatusFilter::Any => true,
+            StatusFilter::Active => status.is_active(),
+            StatusFilter::Hidden => !status.is_active(),
+        }
+    }
+}
+
+impl Display for StatusFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusFilter::Any => write!(f, "Any status"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Filter {
+    search: String,
+    status: StatusFilter,
+    tag: Option<String>,
+    properties: BTreeMap<&'static str, String>,
+}
+
+impl Filter {
+    // search looks for the text in the id, name and tags
+    fn matches(&self, entry: &EntryInfo, properties: &[(&'static str, String)]) -> bool {
+        let search = self.search.trim().to_lowercase();
+        let found = search.is_empty()
+            || entry.id.to_string() == search
+            || entry.name.to_lowercase().contains(&search)
+            || entry
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&search));
+        found
+            && self.status.matches(&entry.status)
+            && self.tag.as_ref().is_none_or(|tag| entry.tags.contains(tag))
+            && self.properties.iter().all(|(label, value)| {
+                properties
+                    .iter()
+                    .any(|item| item.0 == *label && &item.1 == value)
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ReadFile,
+    ChangeSortBy(SortBy),
+    Search(String),
+    FilterStatus(StatusFilter),
+    FilterTag(String),
+    FilterProperty(&'static str, String),
+    ClearFilters,
+    ToggleEntryStatus(u32, bool),
+    EditTags(u32),
+    ChangeTags(String),
+    SaveTags,
+    CancelTags,
+    Duplicate(u32),
+    DeleteEntry(u32),
+    ConfirmDelete,
+    CancelDelete,
+    CreateNew,
+    Edit(u32),
+}
+
+// opening an item is done by the parent page
+pub enum Action {
+    Edit(u32),
+}
+
+fn read_file<T: EditorEntity>() -> Option<EntryList> {
+    game_data()
+        .reload_list::<T>()
+        .inspect_err(|e| println!("Error reading {} list: {e}", T::LABEL))
+        .ok()
+}
+
+fn write_file<T: EditorEntity>(list: &EntryList) {
+    if let Err(e) = game_data().save_list::<T>(list) {
+        println!("Error writing {} list: {e}", T::LABEL);
+    }
+}
+
+fn find_entry_mut(list: &mut [EntryInfo], id: u32) -> Option<&mut EntryInfo> {
+    list.iter_mut().find(|item| item.id == id)
+}
+
+// entries for pickers of the item pages
+pub fn load_available<T: EditorEntity>() -> Vec<EntryInfo> {
+    read_file::<T>().map(|data| data.list).unwrap_or_default()
+}
+
+fn set_entry_status<T: EditorEntity>(data: &mut EntryList, id: u32, is_active: bool) {
+    let Some(entry) = find_entry_mut(&mut data.list, id) else {
+        return;
+    };
+    if entry.status.is_active() == is_active {
+        return;
+    }
+    entry.status = if is_active {
+        EntryStatus::Active
+    } else {
+        EntryStatus::Hidden
+    };
+    write_file::<T>(data);
+}
+
+fn delete_entry<T: EditorEntity>(data: &mut EntryList, id: u32) {
+    if let Some(entry) = data.remove(id) {
+        item::delete_file_by_id::<T>(entry.id);
+    }
+    write_file::<T>(data);
+}
+
+fn load_data<T: EditorEntity>() -> EntryList {
+    read_file::<T>().unwrap_or_default()
+}
+
+fn load_properties<T: EditorEntity>(data: &EntryList) -> HashMap<u32, Vec<(&'static str, String)>> {
+    data.list
+        .iter()
+        .filter_map(|entry| {
+            let item = item::load_by_id::<T>(entry.id)?;
+            Some((entry.id, item.properties()))
+        })
+        .collect()
+}
+
+// tags are separated by commas
+fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in value.split(',').map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|item| item == tag) {
+            tags.push(tag.to_owned());
+        }
+    }
+    tags
+}
+
+pub fn update_name_for<T: EditorEntity>(id: u32, name: String) {
+    let mut data = load_data::<T>();
+    if let Some(entry) = find_entry_mut(&mut data.list, id) {
+        entry.name = name;
+    }
+    write_file::<T>(&data);
+}
+
+impl<T: EditorEntity> Page<T> {
+    pub fn load() -> Self {
+        let data = load_data::<T>();
+        Self {
+            properties: load_properties::<T>(&data),
+            data,
+            filter: Filter::default(),
+            sort_by: SortBy::Id,
+            editing_tags: None,
+            deleting: None,
+            kind: PhantomData,
+        }
+    }
+    // a copy of the entity with a new id, with the same tags
+    fn duplicate(&mut self, id: u32) -> Option<u32> {
+        let entry = self.data.list.iter().find(|entry| entry.id == id)?.clone();
+        let mut copy = item::load_by_id::<T>(id)?;
+        let name = format!("{} copy", entry.name);
+        copy.set_name(name.clone());
+        let new_id = self.data.next_id();
+        item::save_by_id(&copy, new_id);
+        self.properties.insert(new_id, copy.properties());
+        self.data.list.push(EntryInfo {
+            id: new_id,
+            name,
+            status: EntryStatus::Active,
+            tags: entry.tags,
+        });
+        write_file::<T>(&self.data);
+        Some(new_id)
+    }
+    pub fn update(&mut self, message: Message) -> Option<Action> {
+        match message {
+            Message::ReadFile => {
+                self.data = load_data::<T>();
+                self.properties = load_properties::<T>(&self.data);
+            }
+            Message::ChangeSortBy(sort_by) => {
+                self.sort_by = sort_by;
+            }
+            Message::Search(value) => {
+                self.filter.search = value;
+            }
+            Message::FilterStatus(status) => {
+                self.filter.status = status;
+            }
+            Message::FilterTag(tag) => {
+                self.filter.tag = Some(tag);
+            }
+            Message::FilterProperty(label, value) => {
+                self.filter.properties.insert(label, value);
+            }
+            Message::ClearFilters => {
+                self.filter = Filter::default();
+            }
+            Message::ToggleEntryStatus(id, is_active) => {
+                set_entry_status::<T>(&mut self.data, id, is_active);
+            }
+            Message::EditTags(id) => {
+                let entry = self.data.list.iter().find(|entry| entry.id == id)?;
+                self.editing_tags = Some((id, entry.tags.join(", ")));
+            }
+            Message::ChangeTags(value) => {
+                if let Some((_, tags)) = &mut self.editing_tags {
+                    *tags = value;
+                }
+            }
+            Message::SaveTags => {
+                let (id, tags) = self.editing_tags.take()?;
+                let entry = find_entry_mut(&mut self.data.list, id)?;
+                entry.tags = parse_tags(&tags);
+                write_file::<T>(&self.data);
+            }
+            Message::CancelTags => {
+                self.editing_tags = None;
+            }
+            Message::Duplicate(id) => {
+                let new_id = self.duplicate(id)?;
+                return Some(Action::Edit(new_id));
+            }
+            Message::DeleteEntry(id) => {
+                let used_by = game_data()
+                    .used_by::<T>(id)
+                    .inspect_err(|e| {
+                        println!("Error checking where {} {id} is used: {e}", T::LABEL)
+                    })
+                    .ok()?;
+                if used_by.is_empty() {
+                    delete_entry::<T>(&mut self.data, id);
+                } else {
+                    self.deleting = Some((id, used_by));
+                }
+            }
+            Message::ConfirmDelete => {
+                let (id, _) = self.deleting.take()?;
+                if let Err(e) = game_data().remove_references::<T>(id) {
+                    println!("Error removing references to {} {id}: {e}", T::LABEL);
+                    return None;
+                }
+                delete_entry::<T>(&mut self.data, id);
+            }
+            Message::CancelDelete => {
+                self.deleting = None;
+            }
+            Message::CreateNew => {
+                let id = self.data.next_id();
+                let name = String::new();
+                item::save_by_id(&T::create(name.clone()), id);
+                let new_entry = EntryInfo {
+                    id,
+                    name,
+                    status: EntryStatus::Active,
+                    tags: Vec::new(),
+                };
+                self.data.list.push(new_entry);
+                write_file::<T>(&self.data);
+                return Some(Action::Edit(id));
+            }
+            Message::Edit(id) => {
+                return Some(Action::Edit(id));
+            }
+        }
+        None
+    }
+    pub fn view(&self) -> Element<'_, Message> {
+        let heading_row = row![
+            text(format!("Last item id: {}", self.data.last_id)),
+            horizontal_space(),
+            pick_list(SortBy::options(), Some(self.sort_by), Message::ChangeSortBy),
+            button("Refresh").on_press(Message::ReadFile),
+            button("Create new").on_press(Message::CreateNew),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10);
+        let mut details_column = column![heading_row, self.view_filters()]
+            .align_x(Alignment::Start)
+            .spacing(10);
+        if let Some((id, used_by)) = &self.deleting {
+            details_column = details_column.push(confirm_delete_view(
+                *id,
+                used_by,
+                Message::ConfirmDelete,
+                Message::CancelDelete,
+            ));
+        }
+        let mut entries: Vec<&EntryInfo> = self
+            .data
+            .list
+            .iter()
+            .filter(|entry| {
+                let properties = self.properties.get(&entry.id);
+                self.filter
+                    .matches(entry, properties.map_or(&[], Vec::as_slice))
+            })
+            .collect();
+        self.sort_by.sort(&mut entries);
+        for item in entries {
+            details_column = details_column.push(view_entry(item));
+            if let Some((id, tags)) = &self.editing_tags
+                && *id == item.id
+            {
+                details_column = details_column.push(view_tags_input(tags));
+            }
+        }
+        container(details_column).width(600).into()
+    }
+    fn view_filters(&self) -> Element<'_, Message> {
+        let mut filters = row![
+            text_input("Search", &self.filter.search)
+                .on_input(Message::Search)
+                .width(150),
+            pick_list(
+                StatusFilter::options(),
+                Some(self.filter.status),
+                Message::FilterStatus
+            ),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10);
+        let tags: BTreeSet<&String> = self
+            .data
+            .list
+            .iter()
+            .flat_map(|entry| &entry.tags)
+            .collect();
+        if !tags.is_empty() {
+            let options: Vec<String> = tags.into_iter().cloned().collect();
+            filters = filters.push(
+                pick_list(options, self.filter.tag.clone(), Message::FilterTag).placeholder("Tag"),
+            );
+        }
+        filters = filters.push(button("Clear filters").on_press(Message::ClearFilters));
+
+        let mut property_filters = row![].align_y(Alignment::Center).spacing(10);
+        let mut properties: BTreeMap<&'static str, BTreeSet<&String>> = BTreeMap::new();
+        for (label, value) in self.properties.values().flatten() {
+            properties.entry(*label).or_default().insert(value);
+        }
+        for (label, values) in properties {
+            let options: Vec<String> = values.into_iter().cloned().collect();
+            let selected = self.filter.properties.get(label).cloned();
+            let on_select = move |value| Message::FilterProperty(label, value);
+            property_filters =
+                property_filters.push(pick_list(options, selected, on_select).placeholder(label));
+        }
+        column![filters, property_filters].spacing(10).into()
+    }
+}
+
+fn view_entry(item: &EntryInfo) -> Element<'_, Message> {
+    let id = item.id;
+    let mut name = column![text(&item.name)];
+    if !item.tags.is_empty() {
+        name = name.push(text(item.tags.join(", ")).size(12));
+    }
+    let item_row = row![
+        text(format!("{}", item.id))
+            .width(portion(1))
+            .align_x(Alignment::Center),
+        make_rule(1, Alignment::Start),
+        name.width(portion(5)),
+        make_rule(1, Alignment::End),
+        checkbox("", item.status.is_active())
+            .on_toggle(move |value| Message::ToggleEntryStatus(id, value)),
+        entry_button("Edit", Message::Edit(id)),
+        entry_button("Duplicate", Message::Duplicate(id)),
+        entry_button("Tags", Message::EditTags(id)),
+        entry_button("Delete", Message::DeleteEntry(id)),
+    ]
+    .spacing(5)
+    .padding([0, 5])
+    .align_y(Alignment::Center)
+    .height(Length::Shrink);
+    container(item_row).style(container::bordered_box).into()
+}
+
+fn entry_button(label: &str, message: Message) -> Element<'_, Message> {
+    button(text(label).align_x(Alignment::Center))
+        .on_press(message)
+        .width(portion(3))
+        .into()
+}
+
+fn view_tags_input(tags: &str) -> Element<'_, Message> {
+    row![
+        text_input("Tags, separated by commas", tags)
+            .on_input(Message::ChangeTags)
+            .on_submit(Message::SaveTags),
+        button("Save").on_press(Message::SaveTags),
+        button("Cancel").on_press(Message::CancelTags),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(10)
+    .into()
+}
+
+fn portion(value: u16) -> Length {
+    Length::FillPortion(value)
+}
+
+fn make_rule(part: u16, alignment: Alignment) -> Container<'static, Message> {
+    container(vertical_rule(5))
+        .align_x(alignment)
+        .width(portion(part))
+        .height(23)
+}
@@ -1,22 +1,42 @@
 use std::fmt::{self, Display};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
-use iced::widget::{Button, Space, button, column, container, row};
-use iced::{Alignment, Element, Length, Task, Theme};
+use iced::widget::{Button, Space, button, column, container, row, text};
+use iced::{Alignment, Element, Length, Subscription, Task, Theme, keyboard};
+
+use game_data::GameData;
 
 mod attack;
 mod character;
 mod common;
+mod complex_attack;
+mod drafts;
+mod entity;
+mod history;
+mod item;
 mod level;
+mod list;
 mod npc;
+mod page;
+mod play_test;
+mod playback;
 mod resource;
-mod utils;
 
-const DATA_PATH: &str = "../data/";
+static GAME_DATA: OnceLock<GameData> = OnceLock::new();
+
+fn game_data() -> &'static GameData {
+    GAME_DATA.get_or_init(|| {
+        GameData::resolve(game_data::data_path_arg()).expect("Should open game data folder")
+    })
+}
 
 fn main() {
+    println!("Editing game data in {}", game_data().root().display());
     iced::application(App::new, App::update, App::view)
         .title(App::title)
         .theme(App::theme)
+        .subscription(App::subscription)
         .run()
         .expect("Should run the app");
 }
@@ -24,7 +44,13 @@ fn main() {
 #[derive(Debug, Clone)]
 enum Message {
     SelectKind(EditorKind),
+    ConfirmSelectKind,
+    CancelSelectKind,
+    History(history::Message),
+    Tick(Instant),
+    PlayTest(play_test::Message),
     Attack(attack::Message),
+    ComplexAttack(complex_attack::Message),
     Npc(npc::Message),
     Level(level::Message),
     Character(character::Message),
@@ -34,6 +60,7 @@ enum Message {
 #[derive(Debug, Clone, PartialEq)]
 enum EditorKind {
     Attack,
+    ComplexAttack,
     Npc,
     Level,
     Character,
@@ -48,6 +75,7 @@ impl EditorKind {
         use EditorKind::*;
         match self {
             Attack => "Attack",
+            ComplexAttack => "Complex attack",
             Npc => "Npc",
             Level => "Level",
             Character => "Character",
@@ -79,40 +107,120 @@ impl Display for EditorKind {
 enum EditorState {
     NotSelected,
     Attack(Box<attack::Page>),
+    ComplexAttack(Box<complex_attack::Page>),
     Npc(Box<npc::Page>),
     Level(Box<level::Page>),
     Character(Box<character::Page>),
     Resource(Box<resource::Page>),
 }
 
+impl EditorState {
+    fn is_dirty(&self) -> bool {
+        match self {
+            EditorState::NotSelected => false,
+            EditorState::Attack(page) => page.is_dirty(),
+            EditorState::ComplexAttack(page) => page.is_dirty(),
+            EditorState::Npc(page) => page.is_dirty(),
+            EditorState::Level(page) => page.is_dirty(),
+            EditorState::Character(page) => page.is_dirty(),
+            EditorState::Resource(page) => page.is_dirty(),
+        }
+    }
+    fn update_history(&mut self, message: history::Message) {
+        match self {
+            EditorState::NotSelected => (),
+            EditorState::Attack(page) => page.update_history(message),
+            EditorState::ComplexAttack(page) => page.update_history(message),
+            EditorState::Npc(page) => page.update_history(message),
+            EditorState::Level(page) => page.update_history(message),
+            EditorState::Character(page) => page.update_history(message),
+            EditorState::Resource(page) => page.update_history(message),
+        }
+    }
+    fn update_play_test(&mut self, message: play_test::Message) {
+        match self {
+            EditorState::NotSelected => (),
+            EditorState::Attack(page) => page.update_play_test(message),
+            EditorState::ComplexAttack(page) => page.update_play_test(message),
+            EditorState::Npc(page) => page.update_play_test(message),
+            EditorState::Level(page) => page.update_play_test(message),
+            EditorState::Character(page) => page.update_play_test(message),
+            EditorState::Resource(page) => page.update_play_test(message),
+        }
+    }
+    fn is_playing(&self) -> bool {
+        match self {
+            EditorState::NotSelected => false,
+            EditorState::Attack(page) => page.is_playing(),
+            EditorState::ComplexAttack(page) => page.is_playing(),
+            EditorState::Npc(page) => page.is_playing(),
+            EditorState::Level(page) => page.is_playing(),
+            EditorState::Character(page) => page.is_playing(),
+            EditorState::Resource(page) => page.is_playing(),
+        }
+    }
+    fn tick(&mut self, now: Instant) {
+        match self {
+            EditorState::NotSelected => (),
+            EditorState::Attack(page) => page.tick(now),
+            EditorState::ComplexAttack(page) => page.tick(now),
+            EditorState::Npc(page) => page.tick(now),
+            EditorState::Level(page) => page.tick(now),
+            EditorState::Character(page) => page.tick(now),
+            EditorState::Resource(page) => page.tick(now),
+        }
+    }
+}
+
 struct App {
     state: EditorState,
+    // waits for a confirmation to drop unsaved changes
+    leaving_for: Option<EditorKind>,
 }
 
 impl App {
     fn new() -> (Self, Task<Message>) {
         let app = App {
             state: EditorState::NotSelected,
+            leaving_for: None,
         };
         (app, Task::none())
     }
     fn title(&self) -> String {
-        "Editor".into()
+        if self.state.is_dirty() {
+            "Editor *".into()
+        } else {
+            "Editor".into()
+        }
     }
     fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::SelectKind(kind) => match kind {
-                EditorKind::Attack => self.state = attack::load_state(),
-                EditorKind::Npc => self.state = npc::load_state(),
-                EditorKind::Level => self.state = level::load_state(),
-                EditorKind::Character => self.state = character::load_state(),
-                EditorKind::Resource => self.state = resource::load_state(),
-            },
+            Message::SelectKind(kind) => {
+                if self.state.is_dirty() {
+                    self.leaving_for = Some(kind);
+                } else {
+                    self.select_kind(kind);
+                }
+            }
+            Message::ConfirmSelectKind => {
+                if let Some(kind) = self.leaving_for.take() {
+                    self.select_kind(kind);
+                }
+            }
+            Message::CancelSelectKind => self.leaving_for = None,
+            Message::History(message) => self.state.update_history(message),
+            Message::Tick(now) => self.state.tick(now),
+            Message::PlayTest(message) => self.state.update_play_test(message),
             Message::Attack(message) => {
                 if let EditorState::Attack(attack) = &mut self.state {
                     attack.update(message);
                 }
             }
+            Message::ComplexAttack(message) => {
+                if let EditorState::ComplexAttack(page) = &mut self.state {
+                    page.update(message);
+                }
+            }
             Message::Npc(message) => {
                 if let EditorState::Npc(page) = &mut self.state {
                     page.update(message);
@@ -140,6 +248,7 @@ impl App {
         let selected_kind = self.selected_kind();
         let editor_kind_picker = row![
             EditorKind::Attack.make_button(&selected_kind),
+            EditorKind::ComplexAttack.make_button(&selected_kind),
             EditorKind::Npc.make_button(&selected_kind),
             EditorKind::Level.make_button(&selected_kind),
             EditorKind::Character.make_button(&selected_kind),
@@ -149,12 +258,29 @@ impl App {
         .spacing(10);
         let mut contents = column![].align_x(Alignment::Center).spacing(10);
 
+        if self.leaving_for.is_some() {
+            let confirm_row = row![
+                text("Discard unsaved changes?"),
+                button("Discard and leave")
+                    .on_press(Message::ConfirmSelectKind)
+                    .style(button::danger),
+                button("Cancel").on_press(Message::CancelSelectKind),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10);
+            contents = contents.push(confirm_row);
+        }
+
         match &self.state {
             EditorState::NotSelected => (),
             EditorState::Attack(page) => {
                 let element = page.view().map(Message::Attack);
                 contents = contents.push(element);
             }
+            EditorState::ComplexAttack(page) => {
+                let element = page.view().map(Message::ComplexAttack);
+                contents = contents.push(element);
+            }
             EditorState::Npc(page) => {
                 let element = page.view().map(Message::Npc);
                 contents = contents.push(element);
@@ -189,14 +315,51 @@ impl App {
     fn theme(&self) -> Theme {
         Theme::TokyoNight
     }
+    fn subscription(&self) -> Subscription<Message> {
+        let shortcuts = keyboard::on_key_press(shortcut);
+        if self.state.is_playing() {
+            // about 60 frames per second for attack previews and play-tests
+            let tick = iced::time::every(Duration::from_millis(16)).map(Message::Tick);
+            // keys are ignored by pages without a running play-test
+            let keys = play_test::subscription().map(Message::PlayTest);
+            Subscription::batch([shortcuts, tick, keys])
+        } else {
+            shortcuts
+        }
+    }
+}
+
+// ctrl+z to undo, ctrl+shift+z or ctrl+y to redo
+fn shortcut(key: keyboard::Key, modifiers: keyboard::Modifiers) -> Option<Message> {
+    if !modifiers.command() {
+        return None;
+    }
+    let message = match key.as_ref() {
+        keyboard::Key::Character("z" | "Z") if modifiers.shift() => history::Message::Redo,
+        keyboard::Key::Character("z" | "Z") => history::Message::Undo,
+        keyboard::Key::Character("y" | "Y") => history::Message::Redo,
+        _ => return None,
+    };
+    Some(Message::History(message))
 }
 
 impl App {
+    fn select_kind(&mut self, kind: EditorKind) {
+        self.state = match kind {
+            EditorKind::Attack => attack::load_state(),
+            EditorKind::ComplexAttack => complex_attack::load_state(),
+            EditorKind::Npc => npc::load_state(),
+            EditorKind::Level => level::load_state(),
+            EditorKind::Character => character::load_state(),
+            EditorKind::Resource => resource::load_state(),
+        };
+    }
     fn selected_kind(&self) -> Option<EditorKind> {
         use EditorKind::*;
         match self.state {
             EditorState::NotSelected => return None,
             EditorState::Attack(_) => Attack,
+            EditorState::ComplexAttack(_) => ComplexAttack,
             EditorState::Npc(_) => Npc,
             EditorState::Level(_) => Level,
             EditorState::Character(_) => Character,
@@ -1,123 +1,299 @@
-use iced::widget::{Scrollable, button, column, container, pick_list, row, text, text_input};
+use std::collections::HashMap;
+
+use iced::widget::{
+    Scrollable, button, column, container, mouse_area, pick_list, row, text, text_input,
+};
 use iced::{Alignment, Element};
 
+use shared::attack::AttackConstructor;
+use shared::character::HIT_DAMAGE;
+use shared::list::EntryInfo;
 use shared::npc::{NpcAttackInfo, NpcConstructor};
+use shared::validation::FieldError;
 
-use super::get_item_file_path;
-use crate::attack::list::{AttackInfo, load_available_attack_list};
-use crate::common::editor_row;
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+use crate::item;
+use crate::list::load_available;
 
-pub struct Page {
-    id: u32,
-    data: NpcConstructor,
+pub struct Form {
     selected_attack: Option<NpcAttackInfo>,
     available_attack_list: Vec<NpcAttackInfo>,
-}
-
-impl Page {
-    pub fn load_by_id(id: u32) -> Self {
-        let available_attack_list = make_picker_items(load_available_attack_list());
-        Page {
-            id,
-            data: load_by_id(id),
-            selected_attack: None,
-            available_attack_list,
-        }
-    }
+    attacks: HashMap<u32, AttackConstructor>, // for computed stats
+    dragging: Option<usize>,
+    drag_over: Option<usize>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    ReadFile,
-    WriteFile,
     ChangeName(String),
     ChangeRespawnTime(String),
+    ChangeHp(String),
+    ChangeSize(String),
+    ChangeSpeed(String),
     SelectAttack(NpcAttackInfo),
     AddAttack(NpcAttackInfo),
     RemoveAttack(usize),
+    ChangeAttackWeight(usize, String),
+    ChangeAttackCooldown(usize, String),
+    StartDrag(usize),
+    DragOver(usize),
+    DropAttack(usize),
+    CancelDrag,
+}
+
+fn make_picker_items(attack_list: Vec<EntryInfo>) -> Vec<NpcAttackInfo> {
+    attack_list
+        .into_iter()
+        .filter(|item| item.status.is_active())
+        .map(|EntryInfo { id, name, .. }| NpcAttackInfo {
+            id,
+            name,
+            ..NpcAttackInfo::default()
+        })
+        .collect()
 }
 
-fn load_by_id(id: u32) -> NpcConstructor {
-    let file_path = get_item_file_path(id);
-    let contents = std::fs::read(file_path).expect("Should read NpcConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode NpcConstructor")
+fn load_attack(id: u32) -> Option<(u32, AttackConstructor)> {
+    item::load_by_id::<AttackConstructor>(id).map(|attack| (id, attack))
 }
 
-pub fn save_by_id(npc: &NpcConstructor, id: u32) {
-    let file_path = get_item_file_path(id);
-    let contents = serde_json::to_vec_pretty(npc).expect("Should encode NpcConstructor");
-    std::fs::write(file_path, contents).expect("Should write NpcConstructor to a file");
+// from the start of an attack until the next one can start
+fn cycle_time(attack: &AttackConstructor) -> u128 {
+    attack.delay + attack.time_to_complete + attack.aftercast
 }
 
-pub(super) fn delete_file_by_id(id: u32) {
-    let file_path = get_item_file_path(id);
-    if let Err(e) = std::fs::remove_file(file_path) {
-        println!("Error removing file for npc {id}: {e}");
+// attacks are picked by weight one after another, every attack
+// waits for its cooldown, and every hit lands
+fn dps_estimate(data: &NpcConstructor, attacks: &HashMap<u32, AttackConstructor>) -> f32 {
+    let mut weights = 0.0;
+    let mut time = 0.0;
+    for info in data.attacks.iter() {
+        let Some(attack) = attacks.get(&info.id) else {
+            continue;
+        };
+        let weight = info.weight as f32;
+        weights += weight;
+        time += weight * cycle_time(attack).max(info.cooldown).max(1) as f32;
     }
+    if time == 0.0 {
+        return 0.0;
+    }
+    HIT_DAMAGE as f32 * weights / time * 1000.0
 }
 
-fn make_picker_items(npc_list: Vec<AttackInfo>) -> Vec<NpcAttackInfo> {
-    npc_list
-        .into_iter()
-        .filter(|item| item.status.is_active())
-        .map(|AttackInfo { id, name, .. }| NpcAttackInfo { id, name })
-        .collect()
+fn max_range(data: &NpcConstructor, attacks: &HashMap<u32, AttackConstructor>) -> f32 {
+    data.attacks
+        .iter()
+        .filter_map(|info| attacks.get(&info.id))
+        .map(|attack| attack.range.to)
+        .fold(0.0, f32::max)
 }
 
-impl Page {
-    pub fn update(&mut self, message: Message) {
+impl Form {
+    fn stats_view(&self, data: &NpcConstructor) -> Element<'_, Message> {
+        let missing = data
+            .attacks
+            .iter()
+            .filter(|info| !self.attacks.contains_key(&info.id))
+            .count();
+        let mut stats = column![
+            text(format!(
+                "Max attack range: {}",
+                max_range(data, &self.attacks)
+            )),
+            text(format!(
+                "DPS if every attack hits: {:.1}",
+                dps_estimate(data, &self.attacks)
+            )),
+        ]
+        .spacing(5);
+        if missing > 0 {
+            stats = stats.push(text(format!("{missing} attacks couldn't be loaded")));
+        }
+        stats.into()
+    }
+    fn attack_row<'a>(
+        &self,
+        index: usize,
+        info: &'a NpcAttackInfo,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let weight = format!("attacks.{index}.weight");
+        let cooldown = format!("attacks.{index}.cooldown");
+        let details = match self.attacks.get(&info.id) {
+            Some(attack) => format!(
+                "range {} - {}, {} ms",
+                attack.range.from,
+                attack.range.to,
+                cycle_time(attack)
+            ),
+            None => "not loaded".to_owned(),
+        };
+        let marker = match (self.dragging, self.drag_over) {
+            (Some(dragging), _) if dragging == index => "=",
+            (Some(_), Some(over)) if over == index => ">",
+            _ => "≡",
+        };
+        let mut attack_column = column![
+            row![
+                text(marker),
+                text(info.to_string()),
+                text(details),
+                button("delete").on_press(Message::RemoveAttack(index)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            row![
+                editor_row(
+                    "Weight",
+                    text_input("1", &drafts.text(&weight, info.weight.to_string()))
+                        .on_input(move |value| Message::ChangeAttackWeight(index, value))
+                        .width(60)
+                ),
+                editor_row(
+                    "Cooldown, ms",
+                    text_input("0", &drafts.text(&cooldown, info.cooldown.to_string()))
+                        .on_input(move |value| Message::ChangeAttackCooldown(index, value))
+                        .width(80)
+                ),
+            ]
+            .spacing(20),
+        ]
+        .spacing(5);
+        for error in drafts
+            .errors(errors, &weight)
+            .into_iter()
+            .chain(drafts.errors(errors, &cooldown))
+        {
+            attack_column = attack_column.push(text(error).style(text::danger));
+        }
+        let attack_row = container(attack_column)
+            .padding(5)
+            .style(container::bordered_box);
+        // inputs and buttons take the press, dragging starts anywhere else
+        mouse_area(attack_row)
+            .on_press(Message::StartDrag(index))
+            .on_enter(Message::DragOver(index))
+            .on_release(Message::DropAttack(index))
+            .into()
+    }
+}
+
+impl item::Form for Form {
+    type Entity = NpcConstructor;
+    type Message = Message;
+
+    fn new(data: &NpcConstructor) -> Self {
+        let available_attack_list = make_picker_items(load_available::<AttackConstructor>());
+        let attacks = available_attack_list
+            .iter()
+            .chain(data.attacks.iter())
+            .filter_map(|info| load_attack(info.id))
+            .collect();
+        Form {
+            selected_attack: None,
+            available_attack_list,
+            attacks,
+            dragging: None,
+            drag_over: None,
+        }
+    }
+    fn update(&mut self, message: Message, data: &mut NpcConstructor, drafts: &mut Drafts) {
         match message {
-            Message::ReadFile => {
-                self.data = load_by_id(self.id);
-            }
-            Message::WriteFile => {
-                save_by_id(&self.data, self.id);
-                super::list::update_name_for(self.id, self.data.name.clone());
-            }
             Message::ChangeName(value) => {
-                self.data.name = value;
+                data.name = value;
             }
             Message::ChangeRespawnTime(value) => {
-                let parsed = value.parse::<u128>().ok();
-                let Some(parsed) = parsed else {
-                    return;
-                };
-                self.data.respawn_time = parsed;
+                if let Some(parsed) = drafts.parse("respawn_time", value) {
+                    data.respawn_time = parsed;
+                }
+            }
+            Message::ChangeHp(value) => {
+                if let Some(parsed) = drafts.parse("hp", value) {
+                    data.hp = parsed;
+                }
+            }
+            Message::ChangeSize(value) => {
+                if let Some(parsed) = drafts.parse("size", value) {
+                    data.size = parsed;
+                }
+            }
+            Message::ChangeSpeed(value) => {
+                if let Some(parsed) = drafts.parse("speed", value) {
+                    data.speed = parsed;
+                }
             }
             Message::SelectAttack(attack) => {
                 self.selected_attack = Some(attack);
             }
             Message::AddAttack(attack) => {
-                self.data.attacks.push(attack);
+                if !self.attacks.contains_key(&attack.id)
+                    && let Some((id, constructor)) = load_attack(attack.id)
+                {
+                    self.attacks.insert(id, constructor);
+                }
+                data.attacks.push(attack);
             }
             Message::RemoveAttack(index) => {
-                if index >= self.data.attacks.len() {
-                    return;
+                if index < data.attacks.len() {
+                    data.attacks.remove(index);
+                    drafts.clear();
+                }
+            }
+            Message::ChangeAttackWeight(index, value) => {
+                let field = format!("attacks.{index}.weight");
+                if let Some(attack) = data.attacks.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    attack.weight = parsed;
                 }
-                self.data.attacks.remove(index);
+            }
+            Message::ChangeAttackCooldown(index, value) => {
+                let field = format!("attacks.{index}.cooldown");
+                if let Some(attack) = data.attacks.get_mut(index)
+                    && let Some(parsed) = drafts.parse(field, value)
+                {
+                    attack.cooldown = parsed;
+                }
+            }
+            Message::StartDrag(index) => {
+                self.dragging = Some(index);
+                self.drag_over = Some(index);
+            }
+            Message::DragOver(index) => {
+                if self.dragging.is_some() {
+                    self.drag_over = Some(index);
+                }
+            }
+            Message::DropAttack(index) => {
+                if let Some(from) = self.dragging.take()
+                    && from != index
+                    && from < data.attacks.len()
+                    && index < data.attacks.len()
+                {
+                    let attack = data.attacks.remove(from);
+                    data.attacks.insert(index, attack);
+                    drafts.clear();
+                }
+                self.drag_over = None;
+            }
+            Message::CancelDrag => {
+                self.dragging = None;
+                self.drag_over = None;
             }
         }
     }
-
-    pub fn view(&self) -> Element<'_, Message> {
-        let mut contents = column![
-            row![
-                button("Reload from disk").on_press(Message::ReadFile),
-                button("Save").on_press(Message::WriteFile),
-            ]
-            .spacing(10)
-        ]
-        .align_x(Alignment::Center)
-        .spacing(10);
-
-        let mut attack_list = column![].align_x(Alignment::Center).spacing(10);
-        for (index, attack_id) in self.data.attacks.iter().enumerate() {
-            let attack_row = row![
-                text(format!("Attack id: {attack_id}")),
-                button("delete").on_press(Message::RemoveAttack(index)),
-            ]
-            .spacing(10);
-            attack_list = attack_list.push(attack_row);
+    fn view<'a>(
+        &'a self,
+        data: &'a NpcConstructor,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        let mut attack_list = column![].spacing(10);
+        for (index, info) in data.attacks.iter().enumerate() {
+            attack_list = attack_list.push(self.attack_row(index, info, drafts, errors));
         }
         let message_add_attack = self.selected_attack.clone().map(Message::AddAttack);
         let add_attack_row = row![
@@ -131,27 +307,51 @@ impl Page {
         .spacing(10);
 
         let npc_details_column = column![
-            text(format!("Id {}", self.id)),
             editor_row(
                 "Name",
-                text_input("Npc name", &self.data.name).on_input(Message::ChangeName)
+                text_input("Npc name", &data.name).on_input(Message::ChangeName)
             ),
-            editor_row(
+            checked_row(
+                "Hp",
+                text_input("Npc hp", &drafts.text("hp", format!("{}", data.hp)))
+                    .on_input(Message::ChangeHp),
+                drafts.errors(errors, "hp")
+            ),
+            checked_row(
+                "Size",
+                text_input("Npc size", &drafts.text("size", format!("{}", data.size)))
+                    .on_input(Message::ChangeSize),
+                drafts.errors(errors, "size")
+            ),
+            checked_row(
+                "Speed, pixels per ms",
+                text_input(
+                    "0 for npc which don't move",
+                    &drafts.text("speed", format!("{}", data.speed))
+                )
+                .on_input(Message::ChangeSpeed),
+                drafts.errors(errors, "speed")
+            ),
+            checked_row(
                 "Respawn time, s",
-                text_input("Respawn time, s", &format!("{}", self.data.respawn_time))
-                    .on_input(Message::ChangeRespawnTime),
+                text_input(
+                    "Respawn time, s",
+                    &drafts.text("respawn_time", format!("{}", data.respawn_time))
+                )
+                .on_input(Message::ChangeRespawnTime),
+                drafts.errors(errors, "respawn_time")
             ),
+            self.stats_view(data),
             text("Add attack:"),
             add_attack_row,
-            text("Attacks:"),
+            text("Attacks, drag to reorder:"),
             attack_list,
         ]
         .align_x(Alignment::Start)
         .spacing(10);
-        let scrollable_details = Scrollable::new(npc_details_column);
-        let npc_details = container(scrollable_details).width(500);
-        contents = contents.push(npc_details);
-
-        contents.into()
+        // a drag released outside of the attack list is cancelled
+        let details = mouse_area(npc_details_column).on_release(Message::CancelDrag);
+        let scrollable_details = Scrollable::new(details);
+        container(scrollable_details).width(500).into()
     }
 }
@@ -1,82 +1,32 @@
-use iced::Element;
+use shared::npc::NpcConstructor;
 
-use crate::utils::combine;
-use crate::{DATA_PATH, EditorState};
-
-const FILE_PREFIX: &str = "npc";
-const FOLDER_NAME: &str = "npc/";
-const FOLDER_PATH: &str = combine!(DATA_PATH, FOLDER_NAME);
+use crate::EditorState;
+use crate::entity::EditorEntity;
+use crate::page;
+use crate::play_test::Subject;
 
 mod item;
-pub mod list;
 
-fn get_item_file_path(id: u32) -> String {
-    format!("{FOLDER_PATH}{FILE_PREFIX}_{id}.json")
-}
-
-pub struct Page {
-    list: list::Page,
-    item: Option<item::Page>,
-    current_page: CurrentPage,
-}
+pub type Page = page::Page<item::Form>;
+pub type Message = page::Message<item::Message>;
 
 pub fn load_state() -> EditorState {
     EditorState::Npc(Box::new(Page::load()))
 }
 
-impl Page {
-    fn load() -> Self {
-        Page {
-            list: list::Page::load(),
-            item: None,
-            current_page: CurrentPage::List,
-        }
+impl EditorEntity for NpcConstructor {
+    const LABEL: &'static str = "npc";
+
+    fn create(name: String) -> Self {
+        Self::new(name)
     }
-    pub fn update(&mut self, message: Message) {
-        match message {
-            Message::Item(message) => {
-                if let Some(page) = &mut self.item {
-                    page.update(message);
-                }
-            }
-            Message::List(message) => {
-                if let Some(new_message) = self.list.update(message) {
-                    self.update(new_message);
-                }
-            }
-            Message::EditItem(id) => {
-                self.item = Some(item::Page::load_by_id(id));
-                self.current_page = CurrentPage::Item;
-            } // Message::OpenList => {
-              //     self.item = None;
-              //     self.current_page = CurrentPage::List;
-              // }
-        }
+    fn name(&self) -> &str {
+        &self.name
     }
-    pub fn view(&self) -> Element<'_, Message> {
-        match self.current_page {
-            CurrentPage::List => self.list.view().map(Message::List),
-            CurrentPage::Item => {
-                // should never be None here
-                if let Some(item) = &self.item {
-                    item.view().map(Message::Item)
-                } else {
-                    self.list.view().map(Message::List)
-                }
-            }
-        }
+    fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    fn play_test(&self, id: u32) -> Option<Subject> {
+        Some(Subject::Npc(id, self.clone()))
     }
-}
-
-#[derive(Debug, Clone)]
-pub enum Message {
-    Item(item::Message),
-    List(list::Message),
-    EditItem(u32),
-    // OpenList,
-}
-
-enum CurrentPage {
-    Item,
-    List,
 }
@@ -0,0 +1,87 @@
+use std::time::Instant;
+
+use iced::Element;
+
+use crate::history;
+use crate::item::{self, Form};
+use crate::list;
+use crate::play_test;
+
+// list of entities of one kind and the item page of the one being edited
+pub struct Page<F: Form> {
+    list: list::Page<F::Entity>,
+    item: Option<item::Page<F>>,
+    current_page: CurrentPage,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message<M> {
+    Item(item::Message<M>),
+    List(list::Message),
+    EditItem(u32),
+}
+
+enum CurrentPage {
+    Item,
+    List,
+}
+
+impl<F: Form> Page<F> {
+    pub fn load() -> Self {
+        Page {
+            list: list::Page::load(),
+            item: None,
+            current_page: CurrentPage::List,
+        }
+    }
+    pub fn update(&mut self, message: Message<F::Message>) {
+        match message {
+            Message::Item(message) => {
+                if let Some(page) = &mut self.item {
+                    page.update(message);
+                }
+            }
+            Message::List(message) => match self.list.update(message) {
+                Some(list::Action::Edit(id)) => self.update(Message::EditItem(id)),
+                None => (),
+            },
+            Message::EditItem(id) => {
+                let Some(item) = item::Page::load_by_id(id) else {
+                    return;
+                };
+                self.item = Some(item);
+                self.current_page = CurrentPage::Item;
+            }
+        }
+    }
+    pub fn is_dirty(&self) -> bool {
+        self.item.as_ref().is_some_and(|item| item.is_dirty())
+    }
+    pub fn update_history(&mut self, message: history::Message) {
+        if let CurrentPage::Item = self.current_page {
+            self.update(Message::Item(item::Message::History(message)));
+        }
+    }
+    pub fn update_play_test(&mut self, message: play_test::Message) {
+        if let CurrentPage::Item = self.current_page {
+            self.update(Message::Item(item::Message::PlayTest(message)));
+        }
+    }
+    pub fn is_playing(&self) -> bool {
+        match (&self.current_page, &self.item) {
+            (CurrentPage::Item, Some(item)) => item.is_playing(),
+            _ => false,
+        }
+    }
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(item) = &mut self.item {
+            item.tick(now);
+        }
+    }
+    pub fn view(&self) -> Element<'_, Message<F::Message>> {
+        match (&self.current_page, &self.item) {
+            (CurrentPage::Item, Some(item)) => item.view().map(Message::Item),
+            _ => self.list.view().map(Message::List),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::time::Instant;
+
+use iced::widget::canvas::{self, Event, Frame, Geometry};
+use iced::widget::{canvas as canvas_widget, column, text};
+use iced::{Alignment, Color, Element, Point, Rectangle, Renderer, Theme, event, keyboard, mouse};
+use iced::{Subscription, window};
+use nalgebra::Point2;
+
+use game_core::boss::Boss;
+use game_core::scene::{self, Scene};
+use game_view::hero::HeroView;
+use game_view::scene::SceneView;
+use network::client::{self, KeyActionKind, Move};
+use shared::character::AbilitySlot;
+use shared::level::{Level, LevelNpcInfo};
+use shared::npc::NpcConstructor;
+
+use crate::game_data;
+
+const HERO_ID: u128 = 1;
+const WIDTH: f32 = 640.0;
+const HEIGHT: f32 = 480.0;
+
+// what is being tested, edited data is used instead of the saved one
+pub enum Subject {
+    Npc(u32, NpcConstructor),
+    Level(Level),
+}
+
+// a fight in the editor, the scene runs in server mode
+// with the only hero controlled from the keyboard
+pub struct PlayTest {
+    scene: Scene,
+    character: String,
+    last_update: Instant,
+    aim: Point2<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Move(KeyActionKind, Move),
+    Dash,
+    Aim(Point2<f32>),
+    Attack,
+    UseAbility(AbilitySlot),
+}
+
+fn level_for(subject: &Subject) -> Level {
+    match subject {
+        Subject::Level(level) => level.clone(),
+        Subject::Npc(id, constructor) => {
+            let mut level = Level::new("Play-test".to_owned());
+            let position = Point2::new(level.bounds.width / 2.0, level.bounds.height / 2.0);
+            level.npc_list.push(LevelNpcInfo {
+                id: *id,
+                name: constructor.name.clone(),
+                position,
+                ..LevelNpcInfo::default()
+            });
+            level
+        }
+    }
+}
+
+// the edited npc, or the saved one
+fn npc_constructor(subject: &Subject, id: u32) -> game_data::Result<NpcConstructor> {
+    match subject {
+        Subject::Npc(edited_id, constructor) if *edited_id == id => Ok(constructor.clone()),
+        _ => game_data().load(id),
+    }
+}
+
+impl PlayTest {
+    pub fn start(subject: &Subject) -> game_data::Result<Self> {
+        // files could be changed outside of the editor
+        game_data().clear_cache();
+        let level = level_for(subject);
+        let mut npc = Vec::new();
+        for info in level.npc_list.iter() {
+            let constructor = npc_constructor(subject, info.id)?;
+            let attacks = game_data().npc_attacks(&constructor)?;
+            npc.push(Boss::spawn(info, constructor, attacks));
+        }
+        let definitions = game_data().character_definitions()?;
+        let Some(definition) = definitions.first() else {
+            return Err(game_data::Error::Invalid {
+                path: game_data().root().to_owned(),
+                message: "no active character to play with".to_owned(),
+            });
+        };
+        let mut scene = Scene::new(scene::Mode::Server);
+        scene.npc = npc;
+        scene.resource_drops = game_data().resource_drops()?;
+        scene.level = Some(level);
+        scene.add_character(HERO_ID, definition);
+        Ok(PlayTest {
+            scene,
+            character: definition.settings.name.clone(),
+            last_update: Instant::now(),
+            aim: Point2::new(0.0, 0.0),
+        })
+    }
+    // edited npc are changed in place, layout changes of a level
+    // apply to bounds and obstacles, npc are placed again on restart
+    pub fn apply(&mut self, subject: &Subject) {
+        match subject {
+            Subject::Npc(id, constructor) => {
+                let attacks = match game_data().npc_attacks(constructor) {
+                    Ok(attacks) => attacks,
+                    Err(e) => {
+                        println!("Error loading attacks for play-test: {e}");
+                        return;
+                    }
+                };
+                for boss in self.scene.npc.iter_mut().filter(|boss| boss.npc_id == *id) {
+                    boss.apply_constructor(constructor.clone(), attacks.clone());
+                }
+            }
+            Subject::Level(level) => self.scene.level = Some(level.clone()),
+        }
+    }
+    pub fn tick(&mut self, now: Instant) {
+        let dt = now.saturating_duration_since(self.last_update).as_millis();
+        self.last_update = now;
+        self.scene.update(dt);
+    }
+    pub fn update(&mut self, message: Message) {
+        let message = match message {
+            Message::Move(kind, movement) => client::Message::Move(kind, movement),
+            Message::Dash => client::Message::HeroDash,
+            Message::Aim(aim) => {
+                self.aim = aim;
+                return;
+            }
+            Message::Attack => client::Message::HeroAttack(self.aim),
+            Message::UseAbility(slot) => client::Message::UseAbility(slot, self.aim),
+        };
+        self.scene.handle_client_message(HERO_ID, message);
+    }
+    fn scale(&self) -> f32 {
+        let Some(level) = &self.scene.level else {
+            return 1.0;
+        };
+        (WIDTH / level.bounds.width.max(1.0)).min(HEIGHT / level.bounds.height.max(1.0))
+    }
+    pub fn view(&self) -> Element<'_, Message> {
+        column![
+            text(format!(
+                "Playing as {}: WASD to move, Shift to dash, click or Space to attack, 1-3 for abilities",
+                self.character
+            )),
+            canvas_widget(self).width(WIDTH).height(HEIGHT),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+}
+
+// same keys as in first-fight
+fn key_message(key: &keyboard::Key, kind: KeyActionKind) -> Option<Message> {
+    let pressed = matches!(kind, KeyActionKind::Pressed);
+    let movement = match key.as_ref() {
+        keyboard::Key::Character("w" | "W") => Move::Up,
+        keyboard::Key::Character("s" | "S") => Move::Down,
+        keyboard::Key::Character("a" | "A") => Move::Left,
+        keyboard::Key::Character("d" | "D") => Move::Right,
+        keyboard::Key::Named(keyboard::key::Named::Shift) if pressed => {
+            return Some(Message::Dash);
+        }
+        keyboard::Key::Named(keyboard::key::Named::Space) if pressed => {
+            return Some(Message::Attack);
+        }
+        keyboard::Key::Character("1") if pressed => {
+            return Some(Message::UseAbility(AbilitySlot::Ability1));
+        }
+        keyboard::Key::Character("2") if pressed => {
+            return Some(Message::UseAbility(AbilitySlot::Ability2));
+        }
+        keyboard::Key::Character("3") if pressed => {
+            return Some(Message::UseAbility(AbilitySlot::Ability3));
+        }
+        _ => return None,
+    };
+    Some(Message::Move(kind, movement))
+}
+
+// keys typed into inputs of the page are not sent to the hero
+fn key_event(event: iced::Event, status: event::Status, _window: window::Id) -> Option<Message> {
+    if let event::Status::Captured = status {
+        return None;
+    }
+    match event {
+        iced::Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
+            key_message(&key, KeyActionKind::Pressed)
+        }
+        iced::Event::Keyboard(keyboard::Event::KeyReleased { key, .. }) => {
+            key_message(&key, KeyActionKind::Released)
+        }
+        _ => None,
+    }
+}
+
+pub fn subscription() -> Subscription<Message> {
+    event::listen_with(key_event)
+}
+
+impl canvas::Program<Message> for PlayTest {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        let position = cursor.position_in(bounds)?;
+        let aim = Point2::new(position.x, position.y) / self.scale();
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                Some(canvas::Action::publish(Message::Aim(aim)))
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                Some(canvas::Action::publish(Message::Attack).and_capture())
+            }
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, frame.size(), Color::from_rgb8(200, 200, 200));
+        frame.scale(self.scale());
+        SceneView::new(&self.scene).draw(&mut frame, HERO_ID);
+        if let Some(hero) = self.scene.characters.get(&HERO_ID) {
+            let hero_view = HeroView::new(hero);
+            hero_view.draw(&mut frame);
+            hero_view.draw_hp_bar(&mut frame);
+            hero_view.draw_resource_bars(&mut frame);
+        }
+        vec![frame.into_geometry()]
+    }
+}
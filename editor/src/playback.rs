@@ -0,0 +1,178 @@
+use std::time::Instant;
+
+use iced::widget::canvas::{self, Frame, Path, Stroke, stroke};
+use iced::widget::{button, column, pick_list, row, slider, text};
+use iced::{Alignment, Color, Element, Point};
+use nalgebra::Point2;
+
+use crate::common::editor_row;
+
+// the server moves attacks forward every 10ms
+pub const STEP: u128 = 10;
+pub const WIDTH: f32 = 600.0;
+pub const HEIGHT: f32 = 400.0;
+// same as the size of the default hero
+pub const TARGET_SIZE: f32 = 20.0;
+const ATTACKER_SIZE: f32 = 30.0;
+const MAX_TARGET_DISTANCE: f32 = 500.0;
+const SPEEDS: [f32; 4] = [0.25, 0.5, 1.0, 2.0];
+
+// plays an attack in a loop, the attack itself is replayed
+// from the start for every frame, so scrubbing back is free
+pub struct Playback {
+    time: f32, // ms since the attack started
+    speed: f32,
+    last_tick: Option<Instant>, // Some while playing
+    target_distance: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Play,
+    Pause,
+    Tick(Instant),
+    Scrub(f32),
+    ChangeSpeed(f32),
+    ChangeTargetDistance(f32),
+}
+
+impl Playback {
+    pub fn new(target_distance: f32) -> Self {
+        Self {
+            time: 0.0,
+            speed: 1.0,
+            last_tick: None,
+            target_distance: target_distance.clamp(0.0, MAX_TARGET_DISTANCE),
+        }
+    }
+    pub fn is_playing(&self) -> bool {
+        self.last_tick.is_some()
+    }
+    // number of game loop steps to replay for the current frame
+    pub fn steps(&self, duration: u128) -> u128 {
+        (self.time as u128).min(duration) / STEP
+    }
+    pub fn attacker_position(&self) -> Point2<f32> {
+        Point2::new(ATTACKER_SIZE * 2.0, HEIGHT / 2.0)
+    }
+    pub fn target_position(&self) -> Point2<f32> {
+        let attacker = self.attacker_position();
+        Point2::new(attacker.x + self.target_distance, attacker.y)
+    }
+    pub fn update(&mut self, message: Message, duration: u128) {
+        match message {
+            Message::Play => self.last_tick = Some(Instant::now()),
+            Message::Pause => self.last_tick = None,
+            Message::Tick(now) => {
+                let Some(last_tick) = self.last_tick else {
+                    return;
+                };
+                let elapsed = now.saturating_duration_since(last_tick).as_secs_f32() * 1000.0;
+                self.time += elapsed * self.speed;
+                if self.time > duration as f32 {
+                    self.time = 0.0;
+                }
+                self.last_tick = Some(now);
+            }
+            Message::Scrub(time) => self.time = time,
+            Message::ChangeSpeed(speed) => self.speed = speed,
+            Message::ChangeTargetDistance(distance) => self.target_distance = distance,
+        }
+    }
+    pub fn view(&self, duration: u128) -> Element<'_, Message> {
+        let play_button = if self.is_playing() {
+            button("Pause").on_press(Message::Pause)
+        } else {
+            button("Play").on_press(Message::Play)
+        };
+        let time = self.time.min(duration as f32);
+        column![
+            row![
+                play_button,
+                slider(0.0..=duration as f32, time, Message::Scrub)
+                    .step(STEP as f32)
+                    .width(300),
+                text(format!("{} / {duration} ms", time as u128)),
+                pick_list(SPEEDS, Some(self.speed), Message::ChangeSpeed),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            editor_row(
+                "Target distance",
+                slider(
+                    0.0..=MAX_TARGET_DISTANCE,
+                    self.target_distance,
+                    Message::ChangeTargetDistance
+                )
+                .width(300),
+            ),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+}
+
+pub fn point(position: Point2<f32>) -> Point {
+    Point::new(position.x, position.y)
+}
+
+pub fn draw_background(frame: &mut Frame) {
+    let path = Path::rectangle(Point::ORIGIN, frame.size());
+    frame.fill(&path, Color::from_rgb8(30, 30, 40));
+}
+
+pub fn draw_attacker(frame: &mut Frame, position: Point2<f32>) {
+    let path = Path::circle(point(position), ATTACKER_SIZE);
+    frame.fill(&path, Color::from_rgb8(120, 120, 120));
+}
+
+// the target turns red while it is in the hitbox
+pub fn draw_target(frame: &mut Frame, position: Point2<f32>, in_hitbox: bool) {
+    let color = if in_hitbox {
+        Color::from_rgb8(255, 80, 80)
+    } else {
+        Color::from_rgb8(80, 200, 80)
+    };
+    let path = Path::circle(point(position), TARGET_SIZE);
+    frame.fill(&path, color);
+}
+
+// hitboxes are drawn for the center of the target
+pub fn draw_target_center(frame: &mut Frame, position: Point2<f32>) {
+    let path = Path::circle(point(position), 2.0);
+    frame.fill(&path, Color::WHITE);
+}
+
+pub fn stroke_hitbox(frame: &mut Frame, path: &Path) {
+    frame.stroke(
+        path,
+        Stroke {
+            style: stroke::Style::Solid(Color::from_rgb8(255, 220, 0)),
+            width: 2.0,
+            ..Stroke::default()
+        },
+    );
+}
+
+pub fn fill_attack(frame: &mut Frame, path: &Path) {
+    frame.fill(path, Color::from_rgba8(255, 0, 0, 0.6));
+}
+
+pub fn circle_segment(center: Point, radius: f32, start_angle: f32, end_angle: f32) -> Path {
+    let side = Point::new(
+        center.x + radius * start_angle.cos(),
+        center.y + radius * start_angle.sin(),
+    );
+    Path::new(|b| {
+        b.move_to(center);
+        b.line_to(side);
+        b.arc(canvas::path::arc::Arc {
+            center,
+            radius,
+            start_angle: start_angle.into(),
+            end_angle: end_angle.into(),
+        });
+        b.line_to(center);
+    })
+}
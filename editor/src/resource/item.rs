@@ -1,90 +1,107 @@
-use iced::widget::{button, column, text, text_input, row};
+use iced::widget::{column, text_input};
 use iced::{Alignment, Element};
 
 use shared::resource::ResourceConstructor;
+use shared::validation::FieldError;
 
-use super::get_item_file_path;
-use crate::common::editor_row;
+use crate::common::{checked_row, editor_row};
+use crate::drafts::Drafts;
+use crate::item;
 
-pub struct Page {
-    id: u32,
-    data: ResourceConstructor,
-}
-
-impl Page {
-    pub fn load_by_id(id: u32) -> Self {
-        Page {
-            id,
-            data: load_by_id(id),
-        }
-    }
-}
+pub struct Form;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum Message {
-    ReadFile,
-    WriteFile,
     ChangeName(String),
+    ChangeMax(String),
+    ChangeRegen(String),
+    ChangePickupAmount(String),
+    ChangeDropCount(String),
 }
 
-fn load_by_id(id: u32) -> ResourceConstructor {
-    let file_path = get_item_file_path(id);
-    let contents = std::fs::read(file_path).expect("Should read ResourceConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode ResourceConstructor")
-}
+impl item::Form for Form {
+    type Entity = ResourceConstructor;
+    type Message = Message;
 
-pub fn save_by_id(resource: &ResourceConstructor, id: u32) {
-    let file_path = get_item_file_path(id);
-    let contents = serde_json::to_vec_pretty(resource).expect("Should encode ResourceConstructor");
-    std::fs::write(file_path, contents).expect("Should write ResourceConstructor to a file");
-}
-
-pub(super) fn delete_file_by_id(id: u32) {
-    let file_path = get_item_file_path(id);
-    if let Err(e) = std::fs::remove_file(file_path) {
-        println!("Error removing file for resource {id}: {e}");
+    fn new(_data: &ResourceConstructor) -> Self {
+        Form
     }
-}
-
-impl Page {
-    pub fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message, data: &mut ResourceConstructor, drafts: &mut Drafts) {
         match message {
-            Message::ReadFile => {
-                self.data = load_by_id(self.id);
+            Message::ChangeName(value) => {
+                data.name = value;
             }
-            Message::WriteFile => {
-                save_by_id(&self.data, self.id);
-                super::list::update_name_for(self.id, self.data.name.clone());
+            Message::ChangeMax(value) => {
+                if let Some(parsed) = drafts.parse("max", value) {
+                    data.max = parsed;
+                }
             }
-            Message::ChangeName(value) => {
-                self.data.name = value;
+            Message::ChangeRegen(value) => {
+                if let Some(parsed) = drafts.parse("regen", value) {
+                    data.regen = parsed;
+                }
+            }
+            Message::ChangePickupAmount(value) => {
+                if let Some(parsed) = drafts.parse("pickup_amount", value) {
+                    data.pickup_amount = parsed;
+                }
+            }
+            Message::ChangeDropCount(value) => {
+                if let Some(parsed) = drafts.parse("drop_count", value) {
+                    data.drop_count = parsed;
+                }
             }
         }
     }
-
-    pub fn view(&self) -> Element<'_, Message> {
-        let mut contents = column![
-            row![
-                button("Reload from disk").on_press(Message::ReadFile),
-                button("Save").on_press(Message::WriteFile),
-            ]
-            .spacing(10)
-        ]
-        .align_x(Alignment::Center)
-        .spacing(10);
-
-        let resource_details_column = column![
-            text(format!("Id {}", self.id)),
+    fn view<'a>(
+        &'a self,
+        data: &'a ResourceConstructor,
+        drafts: &Drafts,
+        errors: &[FieldError],
+    ) -> Element<'a, Message> {
+        column![
             editor_row(
                 "Resource name",
-                text_input("Resource name", &self.data.name).on_input(Message::ChangeName)
+                text_input("Resource name", &data.name).on_input(Message::ChangeName)
+            ),
+            checked_row(
+                "Max",
+                text_input("Max", &drafts.text("max", format!("{}", data.max)))
+                    .on_input(Message::ChangeMax),
+                drafts.errors(errors, "max")
+            ),
+            checked_row(
+                "Regen per second",
+                text_input(
+                    "Regen per second",
+                    &drafts.text("regen", format!("{}", data.regen))
+                )
+                .on_input(Message::ChangeRegen),
+                drafts.errors(errors, "regen")
+            ),
+            checked_row(
+                "Pickup amount",
+                text_input(
+                    "Pickup amount",
+                    &drafts.text("pickup_amount", format!("{}", data.pickup_amount))
+                )
+                .on_input(Message::ChangePickupAmount),
+                drafts.errors(errors, "pickup_amount")
+            ),
+            checked_row(
+                "Pickups dropped by npc",
+                text_input(
+                    "Pickups dropped by npc",
+                    &drafts.text("drop_count", format!("{}", data.drop_count))
+                )
+                .on_input(Message::ChangeDropCount),
+                drafts.errors(errors, "drop_count")
             ),
         ]
         .align_x(Alignment::Start)
         .spacing(10)
-        .width(500);
-        contents = contents.push(resource_details_column);
-
-        contents.into()
+        .width(500)
+        .into()
     }
 }
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use shared::bundle::DataBundle;
+
+// last definitions received from the server, so they are not
+// downloaded again while the hash stays the same
+fn cache_path() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "first-fight-definitions.{}",
+        game_data::BUNDLE_EXTENSION
+    ))
+}
+
+pub fn load_cached() -> Option<DataBundle> {
+    let path = cache_path();
+    if !path.exists() {
+        return None;
+    }
+    game_data::read_bundle(&path)
+        .inspect_err(|e| tracing::error!("Can't read cached definitions: {e}"))
+        .ok()
+}
+
+pub fn save_cached(definitions: &DataBundle) {
+    if let Err(e) = game_data::write_bundle(&cache_path(), definitions) {
+        tracing::error!("Can't cache definitions: {e}");
+    }
+}
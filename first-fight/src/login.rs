@@ -0,0 +1,66 @@
+use std::thread;
+
+use iced_winit::winit::event_loop::EventLoopProxy;
+
+use network::auth::{self, LoginRequest, LoginResponse};
+
+use crate::UserEvent;
+use crate::servers::ServerAddress;
+use crate::ui_app::Message;
+
+#[derive(Debug, Clone, Copy)]
+pub enum LoginKind {
+    Login,
+    Register,
+}
+
+impl LoginKind {
+    fn path(&self) -> &'static str {
+        match self {
+            LoginKind::Login => auth::LOGIN_PATH,
+            LoginKind::Register => auth::REGISTER_PATH,
+        }
+    }
+}
+
+// posts the request in a separate thread, the result
+// comes back as LoggedIn or LoginFailed
+pub fn spawn(
+    proxy: EventLoopProxy<UserEvent>,
+    address: ServerAddress,
+    kind: LoginKind,
+    request: LoginRequest,
+) {
+    thread::spawn(move || {
+        let message = match post(&address, kind, &request) {
+            Ok(response) => Message::LoggedIn(address, response),
+            Err(e) => Message::LoginFailed(e),
+        };
+        if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(message))) {
+            tracing::error!("Error sending Message in login: {e}");
+        }
+    });
+}
+
+fn post(
+    address: &ServerAddress,
+    kind: LoginKind,
+    request: &LoginRequest,
+) -> Result<LoginResponse, String> {
+    let url = address.http_url(kind.path());
+    let mut response = ureq::post(&url)
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .send_json(request)
+        .map_err(|e| format!("Can't reach {address}: {e}"))?;
+    if !response.status().is_success() {
+        // the server explains what is wrong in plain text
+        let message = response.body_mut().read_to_string().unwrap_or_default();
+        return Err(message.trim().to_owned());
+    }
+    response
+        .body_mut()
+        .read_json()
+        .map_err(|e| format!("Unexpected response from {address}: {e}"))
+}
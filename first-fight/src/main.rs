@@ -8,24 +8,27 @@ use iced_winit::clipboard::Clipboard;
 use iced_winit::core::{Event, Font, Pixels, Size, Theme, mouse, renderer, window};
 use iced_winit::runtime::user_interface::{self, UserInterface};
 use iced_winit::{conversion, winit};
+use nalgebra::Point2;
 use wgpu::{Device, Instance, Queue, TextureFormat};
 use winit::application::ApplicationHandler;
-use winit::event::{ElementState, KeyEvent, StartCause, WindowEvent};
+use winit::event::{ElementState, KeyEvent, MouseButton, StartCause, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
 use winit::window::{Window, WindowId};
 
 use network::client;
+use shared::character::AbilitySlot;
 
-mod attack;
-mod boss;
-mod hero;
-mod scene;
+mod definitions;
+mod login;
+mod servers;
 mod ui_app;
 mod ws;
 
 use ui_app::UiApp;
 
+const OFFLINE_ARG: &str = "--offline";
+
 fn key_event_to_message(event: &KeyEvent) -> ui_app::Message {
     match event.physical_key {
         PhysicalKey::Code(code) => {
@@ -46,6 +49,17 @@ fn key_event_to_message(event: &KeyEvent) -> ui_app::Message {
                     }
                     return ui_app::Message::None;
                 }
+                KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 => {
+                    if let ElementState::Pressed = event.state {
+                        let slot = match code {
+                            KeyCode::Digit1 => AbilitySlot::Ability1,
+                            KeyCode::Digit2 => AbilitySlot::Ability2,
+                            _ => AbilitySlot::Ability3,
+                        };
+                        return ui_app::Message::UseAbility(slot);
+                    }
+                    return ui_app::Message::None;
+                }
                 _ => return ui_app::Message::None,
             };
             let kind = match event.state {
@@ -65,7 +79,6 @@ enum UserEvent {
 }
 
 struct App {
-    proxy: EventLoopProxy<UserEvent>,
     app_data: Option<AppData>,
     ui_app: UiApp,
     events: Vec<Event>,
@@ -88,9 +101,13 @@ struct AppData {
 
 impl App {
     fn new(proxy: EventLoopProxy<UserEvent>) -> Self {
-        let ui_app = UiApp::new(proxy.clone());
+        let mut ui_app = UiApp::new(proxy);
+        if std::env::args().any(|arg| arg == OFFLINE_ARG) {
+            ui_app.update(ui_app::Message::PlayOffline);
+        } else if let Some(address) = servers::server_override() {
+            ui_app.update(ui_app::Message::SelectServer(address));
+        }
         Self {
-            proxy,
             app_data: None,
             ui_app,
             events: vec![],
@@ -113,12 +130,6 @@ impl ApplicationHandler<UserEvent> for App {
         //     log::info!("Already initialized, skipping");
         //     return;
         // }
-        let proxy = self.proxy.clone();
-        std::thread::spawn(move || {
-            println!("Spawned a thread, creating a runtime");
-            let rt = tokio::runtime::Runtime::new().expect("Should build a runtime");
-            rt.block_on(ws::connect(proxy));
-        });
 
         let instance = Instance::new(&wgpu::InstanceDescriptor::from_env_or_default());
 
@@ -336,10 +347,20 @@ impl ApplicationHandler<UserEvent> for App {
                 }
             }
             WindowEvent::CursorMoved { position, .. } => {
-                self.cursor = mouse::Cursor::Available(conversion::cursor_position(
-                    position,
-                    app_data.viewport.scale_factor(),
-                ));
+                let cursor_position =
+                    conversion::cursor_position(position, app_data.viewport.scale_factor());
+                self.cursor = mouse::Cursor::Available(cursor_position);
+                // the canvas fills the whole window, so cursor position matches scene coordinates
+                let aim = Point2::new(cursor_position.x, cursor_position.y);
+                self.ui_app.update(ui_app::Message::Aim(aim));
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.ui_app.update(ui_app::Message::HeroAttack);
+                window.request_redraw();
             }
             WindowEvent::Touch(touch) => {
                 self.cursor = mouse::Cursor::Available(conversion::cursor_position(
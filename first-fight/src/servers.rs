@@ -0,0 +1,141 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const SERVER_ARG: &str = "--server";
+const SERVER_ENV: &str = "FIRST_FIGHT_SERVER";
+const RECENT_FILE_NAME: &str = ".first-fight-servers";
+const RECENT_LIMIT: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerAddress {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for ServerAddress {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_owned(),
+            port: 8080,
+        }
+    }
+}
+
+impl ServerAddress {
+    // ipv6 literals need brackets in urls
+    fn url_host(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        }
+    }
+    pub fn url(&self) -> String {
+        format!("ws://{}:{}/ws", self.url_host(), self.port)
+    }
+    pub fn http_url(&self, path: &str) -> String {
+        format!("http://{}:{}{path}", self.url_host(), self.port)
+    }
+}
+
+impl fmt::Display for ServerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.url_host(), self.port)
+    }
+}
+
+fn parse_port(port: &str, value: &str) -> Result<u16, String> {
+    port.parse()
+        .map_err(|_| format!("Invalid port in server address {value}"))
+}
+
+// `host:port`, or only `host` with the default port,
+// ipv6 hosts go in brackets: `[::1]:8080` or `[::1]`
+impl FromStr for ServerAddress {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let default_port = ServerAddress::default().port;
+        let (host, port) = if let Some(rest) = value.strip_prefix('[') {
+            let (host, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| format!("Missing ']' in server address {value}"))?;
+            let port = match rest {
+                "" => default_port,
+                rest => match rest.strip_prefix(':') {
+                    Some(port) => parse_port(port, value)?,
+                    None => {
+                        return Err(format!("Expected ':' after ']' in server address {value}"));
+                    }
+                },
+            };
+            (host, port)
+        } else {
+            match value.split_once(':') {
+                Some((host, port)) => {
+                    if port.contains(':') {
+                        return Err(format!(
+                            "IPv6 address should be in brackets, like [::1]:8080, got {value}"
+                        ));
+                    }
+                    (host, parse_port(port, value)?)
+                }
+                None => (value, default_port),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in server address {value}"));
+        }
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+        })
+    }
+}
+
+// `--server <host:port>` command line argument wins over FIRST_FIGHT_SERVER
+pub fn server_override() -> Option<ServerAddress> {
+    let mut args = std::env::args().skip_while(|arg| arg != SERVER_ARG);
+    let value = match args.nth(1) {
+        Some(value) => value,
+        None => std::env::var(SERVER_ENV).ok()?,
+    };
+    value.parse().inspect_err(|e| tracing::error!("{e}")).ok()
+}
+
+fn recent_path() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(RECENT_FILE_NAME))
+}
+
+// one `host:port` per line, the last used first
+pub fn load_recent() -> Vec<ServerAddress> {
+    let Some(path) = recent_path() else {
+        return Vec::new();
+    };
+    let Ok(data) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    data.lines().filter_map(|line| line.parse().ok()).collect()
+}
+
+fn save_recent(recent: &[ServerAddress]) {
+    let Some(path) = recent_path() else {
+        return;
+    };
+    let data: String = recent
+        .iter()
+        .map(|address| format!("{address}\n"))
+        .collect();
+    if let Err(e) = std::fs::write(&path, data) {
+        tracing::error!("Can't save recent servers to {}: {e}", path.display());
+    }
+}
+
+pub fn remember(recent: &mut Vec<ServerAddress>, address: &ServerAddress) {
+    recent.retain(|item| item != address);
+    recent.insert(0, address.clone());
+    recent.truncate(RECENT_LIMIT);
+    save_recent(recent);
+}
@@ -1,9 +1,9 @@
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use iced_wgpu::Renderer;
 use iced_widget::canvas::{self, Cache, Canvas, Geometry};
-use iced_widget::{Row, button, column, container, row, text};
+use iced_widget::{Row, button, column, container, row, text, text_input};
 use iced_winit::core::{Alignment, Element, Length, Rectangle, Theme, mouse};
 use iced_winit::winit;
 use nalgebra::Point2;
@@ -13,13 +13,19 @@ use winit::event_loop::EventLoopProxy;
 use game_core::boss::Boss;
 use game_core::hero::Hero;
 use game_core::scene::{self, Scene};
+use game_data::GameData;
+use game_view::hero::HeroView;
+use game_view::scene::{self as scene_view, SceneView};
+use network::auth::{LoginRequest, LoginResponse};
 use network::client::{KeyActionKind, Move};
 use network::server;
-use shared::level::{Level, LevelInfo, LevelList};
-use shared::npc::NpcConstructor;
+use shared::bundle::DataBundle;
+use shared::character::{AbilitySlot, CharacterDefinition};
+use shared::level::LevelInfo;
 
-use crate::hero::HeroView;
-use crate::scene::SceneView;
+use crate::definitions;
+use crate::login::{self, LoginKind};
+use crate::servers::{self, ServerAddress};
 use crate::{UserEvent, ws};
 
 #[derive(Debug, Clone)]
@@ -30,22 +36,38 @@ pub enum Message {
     WsChannel(mpsc::Sender<ws::LocalMessage>),
     WsConnected,
     WsDisconnected,
+    WsReconnecting(Duration), // delay before the next attempt
+    WsUnauthorized,
+    WsReplaced(String), // reason from the server
     WsMessage(String),
     UpdateScene(server::Scene),
     ServerAction(ServerAction),
     ServerMessage(Box<server::Message>),
     Tick,
-    SwitchToLevelSelect,
+    SelectCharacter(u32),
     Start(u32),
     SelectLevel(u32),
     Retry,
+    PlayOffline,
+    HostChanged(String),
+    PortChanged(String),
+    NameChanged(String),
+    PasswordChanged(String),
+    SelectServer(ServerAddress),
+    Login(LoginKind),
+    LoggedIn(ServerAddress, LoginResponse),
+    LoginFailed(String),
+    Disconnect,
     Move(KeyActionKind, Move),
     HeroDash,
+    Aim(Point2<f32>), // cursor position on the canvas
     HeroAttack,
+    UseAbility(AbilitySlot),
     None,
 }
 
 enum FightState {
+    Connect,
     Pending,
     LevelSelect,
     Action,
@@ -63,39 +85,48 @@ pub struct UiApp {
     npc_list: HashMap<u128, Boss>,
     state: FightState,
     ws_sender: Option<mpsc::Sender<ws::LocalMessage>>,
-    level_list: LevelList,
+    definitions: DataBundle, // received from the server
     selected_level: Option<LevelInfo>,
+    character_classes: Vec<CharacterDefinition>,
+    aim: Point2<f32>,
     last_frame_request: Instant,
     frames_passed_since_request: u128,
+    host: String,
+    port: String,
+    name: String,
+    password: String,
+    login_pending: bool,
+    connect_error: Option<String>,
+    recent_servers: Vec<ServerAddress>,
+    server: Option<ServerAddress>,
+    connection: Connection,
+    offline: bool, // the scene runs here instead of the server
 }
 
-fn load_npc_by_id(id: u32) -> NpcConstructor {
-    let file_path = format!("../data/npc/npc_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read NpcConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode NpcConstructor")
+enum Connection {
+    Idle,
+    Connecting,
+    Connected,
+    Lost,
+    Reconnecting(Duration),
 }
 
-fn load_level_list() -> LevelList {
-    let file_path = "../data/level/list.json";
-    let contents = std::fs::read(file_path).expect("Should read LevelList from a file");
-    serde_json::from_slice(&contents).expect("Should decode LevelList")
-}
-
-fn load_level_by_id(id: u32) -> Level {
-    let file_path = format!("../data/level/level_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read Level from a file");
-    serde_json::from_slice(&contents).expect("Should decode Level")
-}
+// the only hero of an offline game
+const OFFLINE_HERO_ID: u128 = 1;
 
 impl UiApp {
     pub fn new(proxy: EventLoopProxy<UserEvent>) -> Self {
-        let level_list = load_level_list();
-        let _boss_constructor = load_npc_by_id(1);
-        // let boss = Boss::from_constructor(Point2::new(512.0, 384.0), boss_constructor);
         let tmp_id = 0; // will receive a proper one from server when connected
-        let hero = Hero::new(tmp_id, Point2::new(250.0, 200.0));
+        // replaced when a character class is selected
+        let hero = Hero::new(
+            tmp_id,
+            Point2::new(250.0, 200.0),
+            &CharacterDefinition::default(),
+        );
         let scene = Scene::new(scene::Mode::Client);
         // let scene = Scene::new(hero.clone(), boss);
+        let recent_servers = servers::load_recent();
+        let last_server = recent_servers.first().cloned().unwrap_or_default();
         UiApp {
             last_update: Instant::now(),
             proxy,
@@ -104,24 +135,155 @@ impl UiApp {
             scene,
             characters: HashMap::new(),
             npc_list: HashMap::new(),
-            state: FightState::Pending,
+            state: FightState::Connect,
             ws_sender: None,
-            level_list,
+            definitions: DataBundle::default(),
             selected_level: None,
+            character_classes: Vec::new(),
+            aim: Point2::new(0.0, 0.0),
             last_frame_request: Instant::now(),
             frames_passed_since_request: 0,
+            host: last_server.host,
+            port: last_server.port.to_string(),
+            name: String::new(),
+            password: String::new(),
+            login_pending: false,
+            connect_error: None,
+            recent_servers,
+            server: None,
+            connection: Connection::Idle,
+            offline: false,
+        }
+    }
+    fn login(&mut self, kind: LoginKind) {
+        let host = self.host.trim();
+        let address = if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]:{}", self.port.trim())
+        } else {
+            format!("{host}:{}", self.port.trim())
+        };
+        let address = match address.parse() {
+            Ok(address) => address,
+            Err(e) => {
+                self.connect_error = Some(e);
+                return;
+            }
+        };
+        let request = LoginRequest {
+            name: self.name.trim().to_owned(),
+            password: self.password.clone(),
+        };
+        self.connect_error = None;
+        self.login_pending = true;
+        login::spawn(self.proxy.clone(), address, kind, request);
+    }
+    fn connect(&mut self, address: ServerAddress, token: String) {
+        // dropping the sender stops the previous connection
+        self.ws_sender = None;
+        self.connect_error = None;
+        ws::spawn(self.proxy.clone(), address.clone(), token);
+        self.server = Some(address);
+        self.connection = Connection::Connecting;
+        self.state = FightState::Pending;
+    }
+    fn disconnect(&mut self) {
+        self.ws_sender = None;
+        self.server = None;
+        self.connection = Connection::Idle;
+        self.offline = false;
+        self.scene = Scene::new(scene::Mode::Client);
+        self.definitions = DataBundle::default();
+        self.character_classes = Vec::new();
+        self.selected_level = None;
+        self.state = FightState::Connect;
+    }
+    fn on_connected(&mut self) {
+        self.connection = Connection::Connected;
+        if let Some(address) = &self.server {
+            servers::remember(&mut self.recent_servers, address);
+        }
+    }
+    // reads game data from the data folder or bundle, like the server does
+    fn start_offline(&mut self) -> game_data::Result<()> {
+        let game_data = GameData::resolve(game_data::data_path_arg())?;
+        let definitions = game_data::pack(&game_data)?;
+        let mut scene = Scene::new(scene::Mode::Server);
+        scene.resource_drops = game_data.resource_drops()?;
+        self.scene = scene;
+        self.hero.id = OFFLINE_HERO_ID;
+        self.ws_sender = None;
+        self.offline = true;
+        self.set_definitions(definitions);
+        Ok(())
+    }
+    // hero input goes to the server, or to the local scene when offline
+    fn send(&mut self, message: ws::LocalMessage) {
+        if self.offline {
+            if let Some(message) = message.to_client_message() {
+                self.scene.handle_client_message(self.hero.id, message);
+            }
+            return;
+        }
+        if let Some(sender) = &mut self.ws_sender {
+            let _ = sender.try_send(message);
         }
     }
     fn load_level(&mut self, id: u32) {
-        let level = load_level_by_id(id);
-        self.scene.npc = level
-            .npc_list
-            .iter()
-            .map(|npc| {
-                let constructor = load_npc_by_id(npc.id);
-                Boss::from_constructor(Point2::new(512.0, 384.0), constructor)
-            })
-            .collect();
+        let contents = &self.definitions.contents;
+        let Some(level) = contents.levels.items.get(&id) else {
+            tracing::error!("Level {id} is not in definitions");
+            return;
+        };
+        let mut npc_list = Vec::new();
+        for npc in level.npc_list.iter() {
+            let Some(constructor) = contents.npc.items.get(&npc.id).cloned() else {
+                tracing::error!("Npc {} is not in definitions", npc.id);
+                continue;
+            };
+            let attacks = contents.npc_attacks(&constructor);
+            npc_list.push(Boss::spawn(npc, constructor, attacks));
+        }
+        self.scene.npc = npc_list;
+        self.scene.level = Some(level.clone());
+        if self.offline {
+            self.scene.pickups.clear();
+            self.scene.characters.clear();
+            if let Some(definition) = contents.character_definition(self.hero.character_id) {
+                self.scene.add_character(self.hero.id, &definition);
+            }
+        }
+    }
+    // use cached definitions if they match, otherwise ask the server
+    fn check_definitions_hash(&mut self, hash: String) {
+        if self.definitions.hash == hash {
+            return;
+        }
+        if let Some(cached) = definitions::load_cached()
+            && cached.hash == hash
+        {
+            println!("Using cached definitions {hash}");
+            self.set_definitions(cached);
+            return;
+        }
+        self.send(ws::LocalMessage::RequestDefinitions);
+    }
+    fn set_definitions(&mut self, definitions: DataBundle) {
+        self.character_classes = definitions.contents.character_definitions();
+        if !matches!(self.state, FightState::Pending)
+            && let Some(definition) = definitions
+                .contents
+                .character_definition(self.hero.character_id)
+        {
+            self.hero.apply_definition(&definition);
+        }
+        // a level edited on the server brings new bounds and obstacles
+        if self.scene.level.is_some()
+            && let Some(selected_level) = &self.selected_level
+            && let Some(level) = definitions.contents.levels.items.get(&selected_level.id)
+        {
+            self.scene.level = Some(level.clone());
+        }
+        self.definitions = definitions;
     }
 }
 
@@ -135,10 +297,21 @@ impl UiApp {
                 self.ws_sender = Some(sender);
             }
             Message::WsConnected => {
-                // do something, send some messages, idk
+                self.on_connected();
             }
             Message::WsDisconnected => {
-                // do something here too
+                self.connection = Connection::Lost;
+            }
+            Message::WsReconnecting(delay) => {
+                self.connection = Connection::Reconnecting(delay);
+            }
+            Message::WsUnauthorized => {
+                self.disconnect();
+                self.connect_error = Some("Session expired, log in again".to_owned());
+            }
+            Message::WsReplaced(reason) => {
+                self.disconnect();
+                self.connect_error = Some(format!("Disconnected: {reason}"));
             }
             Message::UpdateScene(scene) => {
                 self.scene.update_from_network(scene);
@@ -146,6 +319,9 @@ impl UiApp {
             Message::ServerAction(_action) => {
                 // do nothing for now
             }
+            Message::ServerMessage(_) if self.offline => {
+                // the connection may finish after going offline
+            }
             Message::ServerMessage(m) => {
                 // println!("ServerMessage in UiApp update");
                 self.handle_server_message(*m);
@@ -157,26 +333,40 @@ impl UiApp {
             Message::Move(kind, movement) => {
                 // println!("Moving: {:?} {:?}", kind, movement);
                 self.hero.handle_move_action(kind.clone(), movement.clone());
-                if let Some(sender) = &mut self.ws_sender {
-                    let _ = sender.try_send(ws::LocalMessage::Move(kind, movement));
-                }
+                self.send(ws::LocalMessage::Move(kind, movement));
             }
             Message::HeroDash => {
                 self.hero.dash();
-                if let Some(sender) = &mut self.ws_sender {
-                    let _ = sender.try_send(ws::LocalMessage::HeroDash);
-                }
+                self.send(ws::LocalMessage::HeroDash);
+            }
+            Message::Aim(aim) => {
+                self.aim = aim;
             }
             Message::HeroAttack => {
-                self.hero.check_attack();
-                if let Some(sender) = &mut self.ws_sender {
-                    let _ = sender.try_send(ws::LocalMessage::HeroAttack);
+                if !matches!(self.state, FightState::Action) {
+                    return;
                 }
+                let aim = self.aim;
+                self.hero.check_attack(aim);
+                self.send(ws::LocalMessage::HeroAttack(aim));
+            }
+            Message::UseAbility(slot) => {
+                let aim = self.aim;
+                self.hero.use_ability(slot, aim);
+                self.send(ws::LocalMessage::UseAbility(slot, aim));
             }
             Message::Tick => {
                 let now = Instant::now();
                 let dt = now.saturating_duration_since(self.last_update).as_millis();
                 self.last_update = now;
+                if self.offline {
+                    // the hero is updated by the scene, same as on the server
+                    self.scene.update(dt);
+                    if let Some(hero) = self.scene.characters.get(&self.hero.id) {
+                        self.hero = hero.clone();
+                    }
+                    return;
+                }
                 self.hero.update_visuals(dt);
                 self.scene.update(dt);
 
@@ -186,9 +376,7 @@ impl UiApp {
                 if self.last_frame_request.elapsed().as_secs() > 0 {
                     self.last_frame_request = Instant::now();
                     self.frames_passed_since_request = 0;
-                    if let Some(sender) = &mut self.ws_sender {
-                        let _ = sender.try_send(ws::LocalMessage::RequestFrameNumber);
-                    }
+                    self.send(ws::LocalMessage::RequestFrameNumber);
                 }
                 // if self.scene.characters.values().all(|hero| hero.defeated()) {
                 //     self.state = FightState::Loss;
@@ -198,12 +386,25 @@ impl UiApp {
                 //     self.scene.stop();
                 // }
             }
-            Message::SwitchToLevelSelect => {
+            Message::SelectCharacter(character_id) => {
+                let Some(character_class) = self
+                    .character_classes
+                    .iter()
+                    .find(|item| item.id == character_id)
+                else {
+                    return;
+                };
+                let position = self.hero.position;
+                self.hero = Hero::new(self.hero.id, position, character_class);
+                self.send(ws::LocalMessage::Join(character_id));
                 self.state = FightState::LevelSelect;
             }
             Message::SelectLevel(id) => {
                 self.selected_level = self
-                    .level_list
+                    .definitions
+                    .contents
+                    .levels
+                    .list
                     .list
                     .iter()
                     .find(|item| item.id == id)
@@ -217,12 +418,58 @@ impl UiApp {
                 self.scene.reset();
                 self.state = FightState::Action;
             }
+            Message::PlayOffline => {
+                self.disconnect();
+                match self.start_offline() {
+                    Ok(()) => self.state = FightState::Pending,
+                    Err(e) => {
+                        tracing::error!("Can't load game data for offline mode: {e}");
+                        self.connect_error = Some(format!("Can't load game data: {e}"));
+                    }
+                }
+            }
+            Message::HostChanged(host) => {
+                self.host = host;
+            }
+            Message::PortChanged(port) => {
+                self.port = port;
+            }
+            Message::NameChanged(name) => {
+                self.name = name;
+            }
+            Message::PasswordChanged(password) => {
+                self.password = password;
+            }
+            Message::SelectServer(address) => {
+                self.host = address.host;
+                self.port = address.port.to_string();
+            }
+            Message::Login(kind) => {
+                if !self.login_pending {
+                    self.login(kind);
+                }
+            }
+            Message::LoggedIn(address, response) => {
+                self.login_pending = false;
+                self.password.clear();
+                // hero ids are account ids
+                self.hero.id = response.account_id as u128;
+                self.connect(address, response.token);
+            }
+            Message::LoginFailed(error) => {
+                self.login_pending = false;
+                self.connect_error = Some(error);
+            }
+            Message::Disconnect => {
+                self.disconnect();
+            }
             Message::None => (), // do nothing
         }
     }
 
     pub fn view(&self) -> Element<'_, Message, Theme, Renderer> {
         let el = match self.state {
+            FightState::Connect => self.draw_connect().into(),
             FightState::Pending => self.draw_pending().into(),
             FightState::LevelSelect => self.draw_level_selection().into(),
             FightState::Action => self.draw_action(),
@@ -244,12 +491,26 @@ impl UiApp {
             }
             server::Message::SetId(id) => {
                 println!("Got id from server: {id}");
+                // join again after reconnecting, the server keeps
+                // the hero as it is if it's still there
+                let joined = !matches!(self.state, FightState::Connect | FightState::Pending);
                 self.hero.id = id;
+                if joined {
+                    self.send(ws::LocalMessage::Join(self.hero.character_id));
+                }
             }
             server::Message::ResponseFrameNumber(number) => {
                 self.scene.frame_number = number + self.frames_passed_since_request / 2;
                 self.frames_passed_since_request = 0;
             }
+            server::Message::DefinitionsHash(hash) => {
+                self.check_definitions_hash(hash);
+            }
+            server::Message::Definitions(definitions) => {
+                println!("Got definitions {}", definitions.hash);
+                definitions::save_cached(&definitions);
+                self.set_definitions(*definitions);
+            }
             server::Message::Update(update) => {
                 // println!("Got Update message from server");
                 // self.scene.handle_server_update(update);
@@ -263,11 +524,18 @@ impl UiApp {
                             if character.id == self.hero.id {
                                 self.hero.position = character.position;
                                 self.hero.hp = character.hp;
+                                self.hero.resources = character.resources;
                             } else {
                                 self.scene.characters.insert(key, character);
                             }
                         }
-                        self.scene.npc = scene.npc.into_iter().map(Boss::from_network).collect();
+                        let definitions = &self.definitions.contents;
+                        self.scene.npc = scene
+                            .npc
+                            .into_iter()
+                            .map(|boss| Boss::from_network(boss, definitions))
+                            .collect();
+                        self.scene.pickups = scene.pickups;
                     }
                     other => {
                         println!("Got some other update: {other:?}");
@@ -293,8 +561,9 @@ impl<Message> canvas::Program<Message> for UiApp {
         let geometry = self.cache.draw(renderer, bounds.size(), |frame| {
             let hero_view = HeroView::new(&self.hero);
             hero_view.draw(frame);
-            if crate::scene::DRAW_LARGE_HP_BAR {
+            if scene_view::DRAW_LARGE_HP_BAR {
                 hero_view.draw_hp_bar(frame);
+                hero_view.draw_resource_bars(frame);
             } else {
                 hero_view.draw_small_hp_bar(frame);
             }
@@ -308,17 +577,99 @@ impl<Message> canvas::Program<Message> for UiApp {
 // methods for drawing
 impl UiApp {
     fn draw_pending(&self) -> Row<'_, Message> {
-        let column = column![
+        let mut column = column![text("Welcome to the game!").size(30)]
+            .align_x(Alignment::Center)
+            .spacing(10)
+            .width(Length::Fill);
+        if self.character_classes.is_empty() {
+            column = column.push(text(self.connection_status()));
+            let buttons = row![
+                button("Play offline").on_press(Message::PlayOffline),
+                button("Back").on_press(Message::Disconnect),
+            ]
+            .spacing(10);
+            column = column.push(buttons);
+        } else {
+            column = column.push(text("Choose your hero"));
+        }
+        for item in self.character_classes.iter() {
+            let label = format!("{} (hp {})", item.settings.name, item.settings.hp);
+            column = column.push(button(text(label)).on_press(Message::SelectCharacter(item.id)));
+        }
+        row![column].align_y(Alignment::Center).height(Length::Fill)
+    }
+    fn draw_connect(&self) -> Row<'_, Message> {
+        let login = Message::Login(LoginKind::Login);
+        let address = row![
+            text_input("Host", &self.host)
+                .on_input(Message::HostChanged)
+                .width(200),
+            text_input("Port", &self.port)
+                .on_input(Message::PortChanged)
+                .width(80),
+        ]
+        .spacing(10);
+        let account = row![
+            text_input("Name", &self.name)
+                .on_input(Message::NameChanged)
+                .on_submit(login.clone())
+                .width(140),
+            text_input("Password", &self.password)
+                .on_input(Message::PasswordChanged)
+                .on_submit(login.clone())
+                .secure(true)
+                .width(140),
+        ]
+        .spacing(10);
+        let buttons = if self.login_pending {
+            row![text("Logging in...")]
+        } else {
+            row![
+                button("Log in").on_press(login),
+                button("Register").on_press(Message::Login(LoginKind::Register)),
+            ]
+            .spacing(10)
+        };
+        let mut column = column![
             text("Welcome to the game!").size(30),
-            button("Start").on_press(Message::SwitchToLevelSelect),
+            address,
+            account,
+            buttons
         ]
         .align_x(Alignment::Center)
+        .spacing(10)
         .width(Length::Fill);
+        if let Some(error) = &self.connect_error {
+            column = column.push(text(error));
+        }
+        if !self.recent_servers.is_empty() {
+            column = column.push(text("Recent servers"));
+        }
+        for address in self.recent_servers.iter() {
+            let label = text(address.to_string());
+            column = column.push(button(label).on_press(Message::SelectServer(address.clone())));
+        }
+        column = column.push(button("Play offline").on_press(Message::PlayOffline));
         row![column].align_y(Alignment::Center).height(Length::Fill)
     }
+    fn connection_status(&self) -> String {
+        let server = match &self.server {
+            Some(address) => address.to_string(),
+            None => "the server".to_owned(),
+        };
+        match self.connection {
+            Connection::Idle => "Not connected".to_owned(),
+            Connection::Connecting => format!("Connecting to {server}..."),
+            Connection::Connected => format!("Waiting for {server}..."),
+            Connection::Lost => format!("Connection to {server} lost"),
+            Connection::Reconnecting(delay) => {
+                format!("Can't reach {server}, retrying in {}s...", delay.as_secs())
+            }
+        }
+    }
     fn draw_level_selection(&self) -> Row<'_, Message> {
         let mut level_list = column![].align_x(Alignment::Center).height(Length::Fill);
-        for item in self.level_list.list.iter() {
+        for item in self.definitions.contents.levels.list.list.iter() {
             let level = button(text(&item.name)).on_press(Message::SelectLevel(item.id));
             level_list = level_list.push(level);
         }
@@ -353,9 +704,12 @@ impl UiApp {
         row![column].align_y(Alignment::Center).height(Length::Fill)
     }
     fn draw_action(&self) -> Element<'_, Message, Theme, Renderer> {
-        Canvas::new(self)
-            .width(Length::Fill)
-            .height(Length::Fill)
+        let canvas = Canvas::new(self).width(Length::Fill).height(Length::Fill);
+        if self.offline || matches!(self.connection, Connection::Connected) {
+            return canvas.into();
+        }
+        column![text(self.connection_status()), canvas]
+            .align_x(Alignment::Center)
             .into()
     }
 }
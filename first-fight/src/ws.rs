@@ -1,19 +1,27 @@
 use std::fmt;
+use std::thread;
+use std::time::Duration;
 
 use bytes::Bytes;
 use futures::sink::{Sink, SinkExt};
 use futures::stream::StreamExt;
 use futures_util::stream::SplitSink;
 use iced_winit::winit::event_loop::EventLoopProxy;
+use nalgebra::Point2;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite};
+use tungstenite::http::StatusCode;
+use tungstenite::protocol::frame::coding::CloseCode;
 use tungstenite::{Error as WsError, Message as WsMessage};
 
+use network::auth;
 use network::client::{self, KeyActionKind, Move};
 use network::server;
+use shared::character::AbilitySlot;
 
 use crate::UserEvent;
+use crate::servers::ServerAddress;
 use crate::ui_app::Message;
 
 type Writer = SplitSink<WebSocket, WsMessage>;
@@ -23,10 +31,13 @@ pub enum LocalMessage {
     Connected,
     Disconnected,
     User(String),
+    Join(u32),
     Move(KeyActionKind, Move),
     HeroDash,
-    HeroAttack,
+    HeroAttack(Point2<f32>),
+    UseAbility(AbilitySlot, Point2<f32>),
     RequestFrameNumber,
+    RequestDefinitions,
 }
 
 impl LocalMessage {
@@ -45,6 +56,25 @@ impl LocalMessage {
     pub fn disconnected() -> Self {
         LocalMessage::Disconnected
     }
+
+    // what is sent to the server, None for messages about the connection
+    pub fn to_client_message(&self) -> Option<client::Message> {
+        let message = match self {
+            LocalMessage::Join(character_id) => client::Message::Join(*character_id),
+            LocalMessage::Move(kind, movement) => {
+                client::Message::Move(kind.clone(), movement.clone())
+            }
+            LocalMessage::HeroDash => client::Message::HeroDash,
+            LocalMessage::HeroAttack(aim) => client::Message::HeroAttack(*aim),
+            LocalMessage::UseAbility(slot, aim) => client::Message::UseAbility(*slot, *aim),
+            LocalMessage::RequestFrameNumber => client::Message::RequestFrameNumber,
+            LocalMessage::RequestDefinitions => client::Message::RequestDefinitions,
+            LocalMessage::Connected | LocalMessage::Disconnected | LocalMessage::User(_) => {
+                return None;
+            }
+        };
+        Some(message)
+    }
 }
 
 impl fmt::Display for LocalMessage {
@@ -60,62 +90,118 @@ impl fmt::Display for LocalMessage {
                 KeyActionKind::Pressed => write!(f, "Move key pressed {movement:?}"),
                 KeyActionKind::Released => write!(f, "Move key released {movement:?}"),
             },
+            LocalMessage::Join(character_id) => write!(f, "Join as {character_id}"),
             LocalMessage::HeroDash => write!(f, "HeroDash"),
-            LocalMessage::HeroAttack => write!(f, "HeroAttack"),
+            LocalMessage::HeroAttack(_) => write!(f, "HeroAttack"),
+            LocalMessage::UseAbility(slot, _) => write!(f, "UseAbility {slot}"),
             LocalMessage::RequestFrameNumber => write!(f, "RequestFrameNumber"),
+            LocalMessage::RequestDefinitions => write!(f, "RequestDefinitions"),
         }
     }
 }
 
-const SERVER: &str = "ws://127.0.0.1:8080/ws";
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
 
-pub async fn connect(proxy: EventLoopProxy<UserEvent>) {
-    let (sender, mut receiver) = mpsc::channel(100);
-    if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(Message::WsChannel(sender)))) {
-        println!("Error sending Message in ws: {e}");
+fn send_event(proxy: &EventLoopProxy<UserEvent>, message: Message) {
+    if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(message))) {
+        tracing::error!("Error sending Message in ws: {e}");
     }
+}
 
-    let mut websocket;
+// connects in a separate thread with its own runtime
+pub fn spawn(proxy: EventLoopProxy<UserEvent>, address: ServerAddress, token: String) {
+    thread::spawn(move || {
+        println!("Spawned a thread, creating a runtime");
+        let rt = tokio::runtime::Runtime::new().expect("Should build a runtime");
+        rt.block_on(connect(proxy, address, token));
+    });
+}
+
+// keeps reconnecting with a growing delay until the app
+// drops the sender of the channel or the token expires
+async fn connect(proxy: EventLoopProxy<UserEvent>, address: ServerAddress, token: String) {
+    let (sender, mut receiver) = mpsc::channel(100);
+    send_event(&proxy, Message::WsChannel(sender));
 
-    match tokio_tungstenite::connect_async(SERVER).await {
-        Ok((ws, _)) => {
-            websocket = ws;
-            println!("WebSocket connected");
-            if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(Message::WsConnected))) {
-                println!("Error sending Message in ws: {e}");
+    let url = format!("{}?{}={token}", address.url(), auth::TOKEN_PARAM);
+    let mut delay = FIRST_RETRY_DELAY;
+
+    loop {
+        match tokio_tungstenite::connect_async(&url).await {
+            Ok((websocket, _)) => {
+                println!("WebSocket connected to {address}");
+                delay = FIRST_RETRY_DELAY;
+                // input collected while disconnected is outdated
+                while receiver.try_recv().is_ok() {}
+                send_event(&proxy, Message::WsConnected);
+                if !run_session(&proxy, websocket, &mut receiver).await {
+                    return;
+                }
+                send_event(&proxy, Message::WsDisconnected);
             }
-        }
-        Err(e) => {
-            println!("Error connecting to WebSocket: {e}");
-            if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(Message::WsDisconnected)))
-            {
-                tracing::error!("Error sending Message in ws: {e}");
+            Err(WsError::Http(response)) if response.status() == StatusCode::UNAUTHORIZED => {
+                println!("Server at {address} refused the token");
+                send_event(&proxy, Message::WsUnauthorized);
+                return;
+            }
+            Err(e) => {
+                println!("Error connecting to WebSocket at {address}: {e}");
+                send_event(&proxy, Message::WsDisconnected);
             }
+        }
+        send_event(&proxy, Message::WsReconnecting(delay));
+        if !wait_before_retry(&mut receiver, delay).await {
             return;
         }
+        delay = (delay * 2).min(MAX_RETRY_DELAY);
     }
+}
 
-    // let (mut write, mut read) = socket.split();
-    // let mut maybe_ws_future = Option<Box::pin(websocket.next())>;
-    // let mut maybe_receiver_future = Option<Box::pin(receiver.recv())>;
+// false when the channel is closed and there is no need to reconnect
+async fn wait_before_retry(receiver: &mut mpsc::Receiver<LocalMessage>, delay: Duration) -> bool {
+    let sleep = tokio::time::sleep(delay);
+    tokio::pin!(sleep);
+    loop {
+        tokio::select! {
+            _ = &mut sleep => return true,
+            maybe_message = receiver.recv() => {
+                if maybe_message.is_none() {
+                    println!("Ws channel has been closed");
+                    return false;
+                }
+            }
+        }
+    }
+}
 
+// false when the channel is closed or the session is taken
+// by another login, true when the connection is lost
+async fn run_session(
+    proxy: &EventLoopProxy<UserEvent>,
+    mut websocket: WebSocket,
+    receiver: &mut mpsc::Receiver<LocalMessage>,
+) -> bool {
     loop {
         tokio::select! {
             maybe_message = websocket.next() => {
                 match maybe_message {
+                    Some(Ok(WsMessage::Close(Some(frame)))) if frame.code == CloseCode::Policy => {
+                        println!("WebSocket closed by the server: {}", frame.reason);
+                        send_event(proxy, Message::WsReplaced(frame.reason.to_string()));
+                        return false;
+                    }
                     Some(Ok(message)) => {
                         // println!("Got websocket message");
-                        handle_ws_message(&proxy, message).await;
+                        handle_ws_message(proxy, message).await;
                     }
                     Some(Err(e)) => {
                         tracing::error!("Error reading WebSocket message: {e}");
+                        return true;
                     }
                     None => {
                         println!("WebSocket closed by the server");
-                        if let Err(e) = proxy.send_event(UserEvent::Message(Box::new(Message::WsDisconnected))) {
-                            tracing::error!("Error sending Message in ws: {e}");
-                        }
-                        break;
+                        return true;
                     }
                 }
             }
@@ -125,7 +211,8 @@ pub async fn connect(proxy: EventLoopProxy<UserEvent>) {
                     handle_local_message(&mut websocket, message).await;
                 } else {
                     println!("Ws channel has been closed");
-                    break;
+                    let _ = websocket.close(None).await;
+                    return false;
                 }
             }
         }
@@ -151,6 +238,14 @@ async fn handle_local_message(websocket: &mut WebSocket, message: LocalMessage)
                 println!("Error sending WsMessage: {e:?}");
             }
         }
+        LocalMessage::Join(character_id) => {
+            let message = client::Message::Join(character_id);
+            let bytes = Bytes::from(message.to_vec());
+            let ws_message = WsMessage::Binary(bytes);
+            if let Err(e) = websocket.send(ws_message).await {
+                println!("Error sending WsMessage: {e:?}");
+            }
+        }
         LocalMessage::HeroDash => {
             // println!("Local message HeroDash");
             let message = client::Message::HeroDash;
@@ -160,9 +255,17 @@ async fn handle_local_message(websocket: &mut WebSocket, message: LocalMessage)
                 println!("Error sending WsMessage: {e:?}");
             }
         }
-        LocalMessage::HeroAttack => {
+        LocalMessage::HeroAttack(aim) => {
             // println!("Local message HeroAttack");
-            let message = client::Message::HeroAttack;
+            let message = client::Message::HeroAttack(aim);
+            let bytes = Bytes::from(message.to_vec());
+            let ws_message = WsMessage::Binary(bytes);
+            if let Err(e) = websocket.send(ws_message).await {
+                println!("Error sending WsMessage: {e:?}");
+            }
+        }
+        LocalMessage::UseAbility(slot, aim) => {
+            let message = client::Message::UseAbility(slot, aim);
             let bytes = Bytes::from(message.to_vec());
             let ws_message = WsMessage::Binary(bytes);
             if let Err(e) = websocket.send(ws_message).await {
@@ -177,6 +280,14 @@ async fn handle_local_message(websocket: &mut WebSocket, message: LocalMessage)
                 println!("Error sending WsMessage: {e:?}");
             }
         }
+        LocalMessage::RequestDefinitions => {
+            let message = client::Message::RequestDefinitions;
+            let bytes = Bytes::from(message.to_vec());
+            let ws_message = WsMessage::Binary(bytes);
+            if let Err(e) = websocket.send(ws_message).await {
+                println!("Error sending WsMessage: {e:?}");
+            }
+        }
         other => println!("Got some other message from WebSocket: {other:?}"),
     }
 }
@@ -220,20 +331,32 @@ where
                 let client_message = client::Message::Move(kind, movement);
                 send_client_message(&mut write_half, client_message).await;
             }
+            LocalMessage::Join(character_id) => {
+                let client_message = client::Message::Join(character_id);
+                send_client_message(&mut write_half, client_message).await;
+            }
             LocalMessage::HeroDash => {
                 println!("Local message HeroDash");
                 let client_message = client::Message::HeroDash;
                 send_client_message(&mut write_half, client_message).await;
             }
-            LocalMessage::HeroAttack => {
+            LocalMessage::HeroAttack(aim) => {
                 println!("Local message HeroAttack");
-                let client_message = client::Message::HeroAttack;
+                let client_message = client::Message::HeroAttack(aim);
+                send_client_message(&mut write_half, client_message).await;
+            }
+            LocalMessage::UseAbility(slot, aim) => {
+                let client_message = client::Message::UseAbility(slot, aim);
                 send_client_message(&mut write_half, client_message).await;
             }
             LocalMessage::RequestFrameNumber => {
                 let client_message = client::Message::RequestFrameNumber;
                 send_client_message(&mut write_half, client_message).await;
             }
+            LocalMessage::RequestDefinitions => {
+                let client_message = client::Message::RequestDefinitions;
+                send_client_message(&mut write_half, client_message).await;
+            }
             other => println!("Got some other message in ws subscription: {other:?}"),
         }
     }
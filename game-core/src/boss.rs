@@ -1,32 +1,65 @@
 use std::collections::HashMap;
 
-use nalgebra::{Point2, Vector2};
+use nalgebra::Point2;
 
 use network::server;
 use shared::action::Action;
 use shared::attack::{
-    AttackConstructor, AttackDamageConstructor, AttackInfo, AttackOrder, AttackPartConstructor,
-    AttackRange, AttackSequenceConstructor, AttackShapeConstructor, CircleConstructor,
-    ComplexAttack, ComplexAttackConstructor, RecoverInfo,
+    AttackConstructor, AttackDamageConstructor, AttackInfo, AttackPartConstructor, AttackRange,
+    AttackSequenceConstructor, AttackShapeConstructor, CircleConstructor, ComplexAttack,
+    ComplexAttackConstructor, RecoverInfo,
 };
-use shared::character::Character;
-use shared::npc::{NpcConstructor, load_attacks};
+use shared::bundle::BundleContents;
+use shared::character::{Character, HIT_DAMAGE};
+use shared::level::{ActivationTrigger, LevelNpcInfo};
+use shared::npc::NpcConstructor;
 // use shared::npc::load_complex_attacks;
-use shared::position::{direction_from, distance_between};
+use shared::position::distance_between;
 
 use crate::hero::Hero;
 use crate::scene;
 
 pub struct Boss {
+    pub npc_id: u32, // constructor id in game data
     pub position: Point2<f32>,
     pub size: f32,
-    attacks: Vec<AttackConstructor>,
+    attacks: Vec<NpcAttack>,
     attacks_complex: Vec<ComplexAttackConstructor>,
     pub action: Action,
     pub hp: i32,
     pub max_hp: i32,
     pub time_since_defeated: u128,
     pub respawn_time: u128,
+    pub speed: f32,
+    pub facing: f32, // radians
+    hp_override: Option<i32>,
+    trigger: ActivationTrigger,
+    pub active: bool, // doesn't attack until the trigger fires
+    time_since_spawn: u128,
+    target: Option<u128>, // id of the hero which is chased until the next attack starts
+}
+
+// an attack with its settings from NpcConstructor
+struct NpcAttack {
+    constructor: AttackConstructor,
+    weight: u32,
+    cooldown: u128,
+    cooldown_left: u128,
+}
+
+// attacks are loaded in the order of NpcConstructor::attacks
+fn npc_attacks(constructor: &NpcConstructor, attacks: Vec<AttackConstructor>) -> Vec<NpcAttack> {
+    constructor
+        .attacks
+        .iter()
+        .zip(attacks)
+        .map(|(info, constructor)| NpcAttack {
+            constructor,
+            weight: info.weight,
+            cooldown: info.cooldown,
+            cooldown_left: 0,
+        })
+        .collect()
 }
 
 impl Character for Boss {
@@ -34,7 +67,7 @@ impl Character for Boss {
         if self.defeated() {
             return;
         }
-        self.hp -= 35;
+        self.hp -= HIT_DAMAGE;
         if self.hp < 0 {
             self.hp = 0;
         }
@@ -85,6 +118,7 @@ fn get_complex_attack_constructor() -> ComplexAttackConstructor {
         parts: vec![attack_part_2],
     };
     ComplexAttackConstructor {
+        name: "Twin circles".to_owned(),
         range: AttackRange {
             from: 300.0,
             to: 500.0,
@@ -96,6 +130,7 @@ fn get_complex_attack_constructor() -> ComplexAttackConstructor {
 impl Boss {
     pub fn new(position: Point2<f32>) -> Self {
         Boss {
+            npc_id: 0,
             position,
             size: 30.0,
             attacks: Vec::new(),
@@ -105,48 +140,119 @@ impl Boss {
             max_hp: 300,
             time_since_defeated: 0,
             respawn_time: 10_000, // 10s
+            speed: 0.0,
+            facing: 0.0,
+            hp_override: None,
+            trigger: ActivationTrigger::OnStart,
+            active: true,
+            time_since_spawn: 0,
+            target: None,
         }
     }
-    pub fn from_constructor(position: Point2<f32>, constructor: NpcConstructor) -> Self {
+    pub fn from_constructor(
+        position: Point2<f32>,
+        npc_id: u32,
+        constructor: NpcConstructor,
+        attacks: Vec<AttackConstructor>,
+    ) -> Self {
+        let attacks = npc_attacks(&constructor, attacks);
         let NpcConstructor {
             respawn_time,
-            attacks,
+            hp,
+            size,
+            speed,
             ..
         } = constructor;
-        let attacks = load_attacks(attacks);
         // let attacks_complex = load_complex_attacks(Vec::new());
         let attacks_complex = vec![get_complex_attack_constructor()];
         Boss {
+            npc_id,
             position,
-            size: 30.0,
+            size,
             attacks,
             attacks_complex,
             action: Action::Empty,
-            hp: 300,
-            max_hp: 300,
+            hp,
+            max_hp: hp,
             time_since_defeated: 0,
             respawn_time: respawn_time * 1000, // change s to ms
+            speed,
+            facing: 0.0,
+            hp_override: None,
+            trigger: ActivationTrigger::OnStart,
+            active: true,
+            time_since_spawn: 0,
+            target: None,
         }
     }
+    // an npc placed by a level
+    pub fn spawn(
+        info: &LevelNpcInfo,
+        constructor: NpcConstructor,
+        attacks: Vec<AttackConstructor>,
+    ) -> Self {
+        let mut boss = Self::from_constructor(info.position, info.id, constructor, attacks);
+        if let Some(hp) = info.hp {
+            boss.hp = hp;
+            boss.max_hp = hp;
+        }
+        boss.facing = info.facing;
+        boss.hp_override = info.hp;
+        boss.active = info.trigger == ActivationTrigger::OnStart;
+        boss.trigger = info.trigger.clone();
+        boss
+    }
+    // new attacks are used starting from the next one,
+    // the current action is not interrupted
+    pub fn apply_constructor(
+        &mut self,
+        constructor: NpcConstructor,
+        attacks: Vec<AttackConstructor>,
+    ) {
+        self.attacks = npc_attacks(&constructor, attacks);
+        self.respawn_time = constructor.respawn_time * 1000;
+        self.size = constructor.size;
+        self.speed = constructor.speed;
+        self.max_hp = self.hp_override.unwrap_or(constructor.hp);
+        self.hp = self.hp.min(self.max_hp);
+    }
     pub fn to_network(&self) -> server::Boss {
         server::Boss {
+            npc_id: self.npc_id,
+            size: self.size,
             position: self.position,
             action: self.action.clone(),
             hp: self.hp,
             max_hp: self.max_hp,
         }
     }
-    pub fn from_network(boss: server::Boss) -> Self {
+    // attacks come from definitions sent by the server,
+    // they stay empty until definitions arrive
+    pub fn from_network(boss: server::Boss, definitions: &BundleContents) -> Self {
+        let attacks = definitions
+            .npc
+            .items
+            .get(&boss.npc_id)
+            .map(|constructor| npc_attacks(constructor, definitions.npc_attacks(constructor)))
+            .unwrap_or_default();
         Self {
+            npc_id: boss.npc_id,
             position: boss.position,
-            size: 30.0,
-            attacks: Vec::new(),
+            size: boss.size,
+            attacks,
             attacks_complex: Vec::new(),
             action: boss.action.clone(),
             hp: boss.hp,
             max_hp: boss.max_hp,
             time_since_defeated: 0,
             respawn_time: 0, // don't track respawn time on the client
+            speed: 0.0,      // npc are moved by the server
+            facing: 0.0,
+            hp_override: None,
+            trigger: ActivationTrigger::OnStart,
+            active: true, // triggers are checked by the server
+            time_since_spawn: 0,
+            target: None,
         }
     }
     pub fn reset(&mut self) {
@@ -168,20 +274,63 @@ impl Boss {
         }
 
         self.check_respawn_time(dt);
+        for attack in self.attacks.iter_mut() {
+            attack.cooldown_left = attack.cooldown_left.saturating_sub(dt);
+        }
 
-        if characters.is_empty() {
+        if !self.active {
+            // the first update after activation is sent to clients
+            return self.check_trigger(characters, dt);
+        }
+        let Some(target) = self.pick_target(characters) else {
             return false;
+        };
+        if self.check_new_attack(target) {
+            // the next attack may go to someone else
+            self.target = None;
+            return true;
+        }
+        self.move_towards(target, dt)
+    }
+    // keeps the target while it can be attacked, a new one is picked at random
+    fn pick_target(&mut self, characters: &HashMap<u128, Hero>) -> Option<Point2<f32>> {
+        let available = |hero: &&Hero| !hero.disconnected && !hero.defeated();
+        if let Some(hero) = self
+            .target
+            .and_then(|id| characters.get(&id))
+            .filter(available)
+        {
+            return Some(hero.position);
         }
-        let index = if characters.len() == 1 {
-            0
-        } else {
-            rand::random_range(0..characters.len())
+        let targets: Vec<&Hero> = characters.values().filter(available).collect();
+        let index = match targets.len() {
+            0 => {
+                self.target = None;
+                return None;
+            }
+            1 => 0,
+            len => rand::random_range(0..len),
         };
-        if let Some(character) = characters.values().nth(index) {
-            self.check_new_attack(character.position)
-        } else {
-            false
+        self.target = Some(targets[index].id);
+        Some(targets[index].position)
+    }
+    // walks to the target until one of the attacks can reach it,
+    // returns true while moving
+    fn move_towards(&mut self, target: Point2<f32>, dt: u128) -> bool {
+        if self.speed <= 0.0 || self.defeated() || self.action.is_some() {
+            return false;
+        }
+        let distance = distance_between(&self.position, &target);
+        let in_range = self
+            .attacks
+            .iter()
+            .any(|attack| attack.constructor.range.in_range(distance));
+        if in_range || distance == 0.0 {
+            return false;
         }
+        let step = (self.speed * dt as f32).min(distance);
+        self.position += (target - self.position).normalize() * step;
+        true
     }
     fn update_action(&mut self, characters: &mut HashMap<u128, Hero>, dt: u128) {
         match &mut self.action {
@@ -212,34 +361,35 @@ impl Boss {
             return false;
         }
         let distance = distance_between(&self.position, &character_position);
-        let attacks: Vec<_> = self
+        let ready: Vec<usize> = self
             .attacks
             .iter()
-            .filter(|attack| attack.range.in_range(distance))
+            .enumerate()
+            .filter(|(_, attack)| attack.cooldown_left == 0 && attack.weight > 0)
+            .filter(|(_, attack)| attack.constructor.range.in_range(distance))
+            .map(|(index, _)| index)
             .collect();
-        if attacks.is_empty() {
+        let total: u32 = ready.iter().map(|index| self.attacks[*index].weight).sum();
+        if total == 0 {
             return false;
         }
-        let index = rand::random_range(0..attacks.len());
-        let constructor = attacks[index].clone();
-
-        let mut direction = direction_from(&self.position, &character_position);
-        if direction.norm() > 0.000_001 {
-            direction.normalize_mut();
-        }
-        let info = match &constructor.order {
-            AttackOrder::ExpandingCircle => {
-                AttackInfo::from_constructor(constructor, character_position, direction, 70.0)
-            }
-            AttackOrder::ProjectileFromCaster => {
-                let direction = Vector2::new(-direction.x, -direction.y);
-                AttackInfo::from_constructor(constructor, self.position, direction, 20.0)
-            }
-            _ => {
-                let range = constructor.range.to;
-                AttackInfo::from_constructor(constructor, self.position, direction, range)
+        let mut roll = rand::random_range(0..total);
+        let Some(index) = ready.into_iter().find(|index| {
+            let weight = self.attacks[*index].weight;
+            if roll < weight {
+                return true;
             }
+            roll -= weight;
+            false
+        }) else {
+            return false;
         };
+        let attack = &mut self.attacks[index];
+        attack.cooldown_left = attack.cooldown;
+        let constructor = attack.constructor.clone();
+
+        let range = constructor.range.to;
+        let info = AttackInfo::aimed(constructor, self.position, character_position, range);
         self.action = Action::Attack(info);
         true // send updates to client if it's a server
     }
@@ -262,24 +412,25 @@ impl Boss {
         let index = rand::random_range(0..attacks.len());
         let constructor = attacks[index].clone();
 
-        let dx = self.position.x - character_position.x;
-        let dy = self.position.y - character_position.y;
-        let direction_angle = dy.atan2(dx) + std::f32::consts::PI;
-
-        let mut direction = direction_from(&self.position, &character_position);
-        if direction.norm() > 0.000_001 {
-            direction.normalize_mut();
-        }
-        let direction = Vector2::new(-direction.x, -direction.y);
-        let attack = ComplexAttack::from_constructor(
-            constructor,
-            self.position,
-            character_position,
-            direction,
-            direction_angle,
-        );
+        let attack = ComplexAttack::aimed(constructor, self.position, character_position);
         self.action = Action::ComplexAttack(attack);
     }
+    fn check_trigger(&mut self, characters: &HashMap<u128, Hero>, dt: u128) -> bool {
+        self.time_since_spawn += dt;
+        self.active = match self.trigger {
+            ActivationTrigger::OnStart => true,
+            ActivationTrigger::PlayerInRange { distance } => characters
+                .values()
+                .filter(|hero| !hero.disconnected)
+                .any(|hero| distance_between(&self.position, &hero.position) <= distance),
+            ActivationTrigger::AfterDelay { delay } => self.time_since_spawn >= delay,
+        };
+        self.active
+    }
+    // hurt or attacking, such an npc is not replaced by a data reload
+    pub fn in_fight(&self) -> bool {
+        !self.defeated() && (self.hp < self.max_hp || self.action.is_some())
+    }
     pub fn hp_left_percent(&self) -> f32 {
         self.hp as f32 / self.max_hp as f32
     }
@@ -3,15 +3,19 @@ use nalgebra::{Point2, Vector2};
 use network::client::{KeyActionKind, Move};
 use network::server;
 use shared::action::Action;
-use shared::attack::{AttackInfo, AttackKind, AttackOrder, AttackState, RecoverInfo};
-use shared::character::{Character, CharacterSettings};
+use shared::attack::{AttackConstructor, AttackInfo, RecoverInfo};
+use shared::character::{
+    AbilitySlot, Character, CharacterDefinition, CharacterSettings, HIT_DAMAGE, HeroAbility,
+};
 use shared::hero::{DashCooldown, DashInfo, Moving};
+use shared::resource::{Pickup, ResourcePool};
 
 use crate::boss::Boss;
 
 #[derive(Debug, Clone)]
 pub struct Hero {
     pub id: u128,
+    pub character_id: u32,
     pub hp: i32,
     max_hp: i32,
     pub position: Point2<f32>,
@@ -23,6 +27,38 @@ pub struct Hero {
     pub ranged_attack_distance: f32,
     pub action: Action,
     pub character_settings: CharacterSettings,
+    pub abilities: Vec<Ability>,
+    pub resources: Vec<ResourcePool>,
+    pub disconnected: bool, // the client is gone, npc ignore the hero until it's back
+}
+
+#[derive(Debug, Clone)]
+pub struct Ability {
+    pub slot: AbilitySlot,
+    attack: Option<AttackConstructor>,
+    cooldown: u128,
+    pub cooldown_left: u128,
+    resource_id: Option<u32>,
+    cost: f32,
+}
+
+impl Ability {
+    fn new(info: &HeroAbility, attack: Option<AttackConstructor>) -> Self {
+        Self {
+            slot: info.slot,
+            attack,
+            cooldown: info.cooldown,
+            cooldown_left: 0,
+            resource_id: info.resource.as_ref().map(|resource| resource.id),
+            cost: info.cost as f32,
+        }
+    }
+    fn ready(&self) -> bool {
+        self.cooldown_left == 0
+    }
+    fn update(&mut self, dt: u128) {
+        self.cooldown_left = self.cooldown_left.saturating_sub(dt);
+    }
 }
 
 impl Character for Hero {
@@ -31,10 +67,10 @@ impl Character for Hero {
             // invulnerability frame
             return;
         }
-        if self.hp == 0 {
+        if self.hp == 0 || self.disconnected {
             return;
         }
-        self.hp -= 35;
+        self.hp -= HIT_DAMAGE;
         if self.hp < 0 {
             self.hp = 0;
         }
@@ -47,26 +83,48 @@ impl Character for Hero {
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn load_character_settings_by_id(id: u32) -> CharacterSettings {
-    let file_path = format!("../data/character/character_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read CharacterSettings from a file");
-    serde_json::from_slice(&contents).expect("Should decode CharacterSettings")
+fn load_abilities(definition: &CharacterDefinition) -> Vec<Ability> {
+    definition
+        .settings
+        .abilities
+        .iter()
+        .map(|item| {
+            let attack = definition.attacks.get(&item.attack.id).cloned();
+            Ability::new(item, attack)
+        })
+        .collect()
 }
 
-#[cfg(target_arch = "wasm32")]
-fn load_character_settings_by_id(_id: u32) -> CharacterSettings {
-    CharacterSettings::default()
+// a pool for every resource used by the ability kit
+fn load_resources(definition: &CharacterDefinition) -> Vec<ResourcePool> {
+    let mut resources: Vec<ResourcePool> = Vec::new();
+    for ability in definition.settings.abilities.iter() {
+        let Some(resource) = &ability.resource else {
+            continue;
+        };
+        if resources.iter().any(|item| item.id == resource.id) {
+            continue;
+        }
+        let Some(constructor) = definition.resources.get(&resource.id) else {
+            continue;
+        };
+        resources.push(ResourcePool::new(resource.id, constructor));
+    }
+    resources
 }
 
 impl Hero {
-    pub fn new(id: u128, position: Point2<f32>) -> Self {
+    pub fn new(id: u128, position: Point2<f32>, definition: &CharacterDefinition) -> Self {
+        let abilities = load_abilities(definition);
+        let resources = load_resources(definition);
+        let character_settings = definition.settings.clone();
         Hero {
             id,
-            hp: 1000,
-            max_hp: 1000,
+            character_id: definition.id,
+            hp: character_settings.hp,
+            max_hp: character_settings.hp,
             position,
-            size: 20.0,
+            size: character_settings.size,
             direction: Vector2::new(0.0, 0.0),
             moving: Moving {
                 left: false,
@@ -75,15 +133,43 @@ impl Hero {
                 down: false,
             },
             last_key_up: None,
-            melee_attack_distance: 100.0,
-            ranged_attack_distance: 300.0,
+            melee_attack_distance: character_settings.melee_attack_distance,
+            ranged_attack_distance: character_settings.ranged_attack_distance,
             action: Action::Empty,
-            character_settings: load_character_settings_by_id(1),
+            character_settings,
+            abilities,
+            resources,
+            disconnected: false,
+        }
+    }
+    // switches to updated class data, cooldowns and resources are kept
+    pub fn apply_definition(&mut self, definition: &CharacterDefinition) {
+        let mut abilities = load_abilities(definition);
+        for ability in abilities.iter_mut() {
+            if let Some(old) = self.abilities.iter().find(|item| item.slot == ability.slot) {
+                ability.cooldown_left = old.cooldown_left.min(ability.cooldown);
+            }
         }
+        let mut resources = load_resources(definition);
+        for pool in resources.iter_mut() {
+            if let Some(old) = self.resources.iter().find(|item| item.id == pool.id) {
+                pool.current = old.current.min(pool.max);
+            }
+        }
+        let settings = definition.settings.clone();
+        self.max_hp = settings.hp;
+        self.hp = self.hp.min(self.max_hp);
+        self.size = settings.size;
+        self.melee_attack_distance = settings.melee_attack_distance;
+        self.ranged_attack_distance = settings.ranged_attack_distance;
+        self.character_settings = settings;
+        self.abilities = abilities;
+        self.resources = resources;
     }
     pub fn to_network(&self) -> server::Hero {
         server::Hero {
             id: self.id,
+            character_id: self.character_id,
             hp: self.hp,
             max_hp: self.max_hp,
             position: self.position,
@@ -93,15 +179,17 @@ impl Hero {
             ranged_attack_distance: self.ranged_attack_distance,
             action: self.action.clone(),
             character_settings: self.character_settings.clone(),
+            resources: self.resources.clone(),
         }
     }
     pub fn from_network(hero: server::Hero) -> Self {
         Self {
             id: hero.id,
+            character_id: hero.character_id,
             hp: hero.hp,
             max_hp: hero.max_hp,
             position: hero.position,
-            size: 20.0,
+            size: hero.character_settings.size,
             direction: hero.direction,
             moving: hero.moving.clone(),
             last_key_up: None,
@@ -109,6 +197,9 @@ impl Hero {
             ranged_attack_distance: hero.ranged_attack_distance,
             action: hero.action.clone(),
             character_settings: hero.character_settings.clone(),
+            abilities: Vec::new(),
+            resources: hero.resources.clone(),
+            disconnected: false,
         }
     }
     pub fn update_from_network(&mut self, hero: server::Hero) {
@@ -121,6 +212,7 @@ impl Hero {
         self.ranged_attack_distance = hero.ranged_attack_distance;
         self.action = hero.action;
         self.character_settings = hero.character_settings;
+        self.resources = hero.resources;
     }
     pub fn reset(&mut self) {
         self.hp = self.max_hp;
@@ -167,39 +259,69 @@ impl Hero {
             return;
         }
         direction.normalize_mut();
-        let dash = DashInfo::new(direction, self.character_settings.dash_duration);
+        let dash = DashInfo::new(
+            direction,
+            self.character_settings.dash_distance as f32,
+            self.character_settings.dash_duration,
+        );
         self.action = Action::Dash(dash);
     }
-    pub fn check_attack(&mut self) {
+    pub fn check_attack(&mut self, aim: Point2<f32>) {
+        self.use_ability(AbilitySlot::Primary, aim);
+    }
+    pub fn use_ability(&mut self, slot: AbilitySlot, aim: Point2<f32>) {
         if self.action.is_some() {
             return;
         }
-        let mut direction = self.direction;
-        direction.x = -direction.x;
-        direction.y = -direction.y;
-        if direction.x.abs() < 0.000_001 && direction.y.abs() < 0.000_001 {
-            // no direction, x & y are 0
+        let Some(ability) = self.abilities.iter_mut().find(|item| item.slot == slot) else {
+            return;
+        };
+        if !ability.ready() {
             return;
         }
-        direction.normalize_mut();
-
-        let attack = AttackInfo {
-            position: self.position,
-            direction,
-            delay: 50,
-            time_passed: 0,
-            time_to_complete: 100,
-            aftercast: 0,
-            percent_completed: 0.0,
-            kind: AttackKind::Pizza,
-            order: AttackOrder::LeftToRight,
-            distance: self.melee_attack_distance,
-            width_angle: 1.0,
-            state: AttackState::Selected,
-            damage_done: false,
+        let Some(constructor) = ability.attack.clone() else {
+            return;
         };
+        if let Some(resource_id) = ability.resource_id {
+            let Some(pool) = self
+                .resources
+                .iter_mut()
+                .find(|item| item.id == resource_id)
+            else {
+                return;
+            };
+            if !pool.try_spend(ability.cost) {
+                return;
+            }
+        }
+        ability.cooldown_left = ability.cooldown;
+        let reach = constructor.range.to;
+        let attack = AttackInfo::aimed(constructor, self.position, aim, reach);
         self.action = Action::Attack(attack);
     }
+    fn update_abilities(&mut self, dt: u128) {
+        for ability in self.abilities.iter_mut() {
+            ability.update(dt);
+        }
+        for pool in self.resources.iter_mut() {
+            pool.update(dt);
+        }
+    }
+    // returns true if the pickup was used
+    pub fn collect(&mut self, pickup: &Pickup) -> bool {
+        if self.defeated() || !pickup.touches(self.position, self.size) {
+            return false;
+        }
+        let Some(pool) = self
+            .resources
+            .iter_mut()
+            .find(|item| item.id == pickup.resource_id)
+        else {
+            return false;
+        };
+        pool.restore(pickup.amount);
+        true
+    }
     pub fn handle_move_action(&mut self, kind: KeyActionKind, movement: Move) {
         let moving = match movement {
             Move::Left => &mut self.moving.left,
@@ -221,6 +343,7 @@ impl Hero {
     pub fn update_visuals(&mut self, dt: u128) {
         self.update_position(dt);
         self.update_action_visuals(dt);
+        self.update_abilities(dt);
         if let Some(time_passed) = &mut self.last_key_up {
             *time_passed += dt;
             if *time_passed > 100 {
@@ -236,7 +359,7 @@ impl Hero {
             Action::Attack(attack) => {
                 attack.update(dt);
                 if !attack.damage_done {
-                    attack.check_damage_for_hero(self.melee_attack_distance, npc);
+                    attack.check_damage_for_hero(npc);
                 }
                 if attack.completed() {
                     self.action = Action::Recovery(RecoverInfo::new(0));
@@ -244,12 +367,10 @@ impl Hero {
             }
             Action::ComplexAttack(_attack) => {}
             Action::Dash(dash) => {
-                dash.update(dt);
-                let speed =
-                    self.character_settings.dash_distance as f32 / dash.time_to_complete as f32;
-                self.position += dash.direction * speed * 10.0;
+                self.position += dash.step(dt);
                 if dash.completed() {
-                    self.action = Action::DashCooldown(DashCooldown::new(200));
+                    let cooldown = DashCooldown::new(self.character_settings.dash_cooldown);
+                    self.action = Action::DashCooldown(cooldown);
                 }
             }
             other => other.update(dt),
@@ -265,12 +386,10 @@ impl Hero {
             }
             Action::ComplexAttack(_attack) => {}
             Action::Dash(dash) => {
-                dash.update(dt);
-                let speed =
-                    self.character_settings.dash_distance as f32 / dash.time_to_complete as f32;
-                self.position += dash.direction * speed * 10.0;
+                self.position += dash.step(dt);
                 if dash.completed() {
-                    self.action = Action::DashCooldown(DashCooldown::new(200));
+                    let cooldown = DashCooldown::new(self.character_settings.dash_cooldown);
+                    self.action = Action::DashCooldown(cooldown);
                 }
             }
             other => other.update(dt),
@@ -279,6 +398,7 @@ impl Hero {
     pub fn update(&mut self, npc: &mut [Boss], dt: u128) {
         self.update_position(dt);
         self.update_action(npc, dt);
+        self.update_abilities(dt);
         if let Some(time_passed) = &mut self.last_key_up {
             *time_passed += dt;
             if *time_passed > 100 {
@@ -294,7 +414,7 @@ impl Hero {
             return;
         }
 
-        let speed = 0.1;
+        let speed = self.character_settings.speed;
         if self.moving.left && self.moving.right {
             // do nothing
         } else if self.moving.left {
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
-use nalgebra::Point2;
+use nalgebra::{Point2, Vector2};
 
 use network::client;
 use network::server;
+use shared::character::CharacterDefinition;
 use shared::effect::area;
+use shared::level::Level;
 use shared::projectile::Projectile;
+use shared::resource::{PICKUP_SIZE, Pickup, ResourceDrop};
 
 use crate::boss::Boss;
 use crate::hero::Hero;
@@ -23,6 +26,9 @@ pub struct Scene {
     pub npc: Vec<Boss>,
     pub effects: Vec<area::Effect>,
     pub projectiles: Vec<Projectile>,
+    pub pickups: Vec<Pickup>,
+    pub resource_drops: Vec<ResourceDrop>,
+    pub level: Option<Level>, // bounds, obstacles and spawns
 }
 
 impl Scene {
@@ -34,15 +40,28 @@ impl Scene {
             npc: Vec::new(),
             effects: Vec::new(),
             projectiles: Vec::new(),
+            pickups: Vec::new(),
+            resource_drops: Vec::new(),
+            level: None,
         }
     }
-    pub fn add_character(&mut self, id: u128) {
-        let hero = Hero::new(id, Point2::new(250.0, 200.0));
+    pub fn add_character(&mut self, id: u128, definition: &CharacterDefinition) {
+        let position = match &self.level {
+            Some(level) => level.player_spawn(self.characters.len()),
+            None => Point2::new(250.0, 200.0),
+        };
+        let hero = Hero::new(id, position, definition);
         self.characters.insert(id, hero);
     }
     pub fn remove_character(&mut self, id: u128) {
         self.characters.remove(&id);
     }
+    // a disconnected hero stays on the scene, but npc don't see it
+    pub fn set_character_connected(&mut self, id: u128, connected: bool) {
+        if let Some(hero) = self.characters.get_mut(&id) {
+            hero.disconnected = !connected;
+        }
+    }
     pub fn to_network(&self) -> server::Scene {
         let frame_number = self.frame_number;
         let mut characters = HashMap::new();
@@ -50,10 +69,12 @@ impl Scene {
             characters.insert(*key, value.to_network());
         }
         let npc = self.npc.iter().map(|item| item.to_network()).collect();
+        let pickups = self.pickups.clone();
         server::Scene {
             frame_number,
             characters,
             npc,
+            pickups,
         }
     }
     pub fn update_from_network(&mut self, scene: server::Scene) {
@@ -73,14 +94,43 @@ impl Scene {
     }
     pub fn update(&mut self, dt: u128) -> bool {
         self.frame_number += 1;
+        let defeated_before: Vec<bool> = self.npc.iter().map(|boss| boss.defeated()).collect();
         for hero in self.characters.values_mut() {
             hero.update(&mut self.npc, dt);
+            if let Some(level) = &self.level {
+                hero.position = level.keep_inside(hero.position, hero.size);
+            }
         }
         let mut update_event = false;
+        if let Mode::Server = self.mode {
+            for (boss, defeated_before) in self.npc.iter().zip(defeated_before) {
+                if !defeated_before && boss.defeated() {
+                    let level = self.level.as_ref();
+                    spawn_pickups(
+                        &mut self.pickups,
+                        &self.resource_drops,
+                        boss.position,
+                        level,
+                    );
+                    update_event = true;
+                }
+            }
+            if self.collect_pickups() {
+                update_event = true;
+            }
+            if self.expire_pickups(dt) {
+                update_event = true;
+            }
+        }
         for boss in self.npc.iter_mut() {
             if boss.update(&mut self.characters, dt, self.mode) {
                 update_event = true;
             }
+            if boss.speed > 0.0
+                && let Some(level) = &self.level
+            {
+                boss.position = level.keep_inside(boss.position, boss.size);
+            }
         }
         for effect in self.effects.iter_mut() {
             effect.update(dt);
@@ -90,6 +140,25 @@ impl Scene {
         }
         update_event
     }
+    fn collect_pickups(&mut self) -> bool {
+        let count = self.pickups.len();
+        self.pickups.retain(|pickup| {
+            !self
+                .characters
+                .values_mut()
+                .any(|hero| hero.collect(pickup))
+        });
+        count != self.pickups.len()
+    }
+    // pickups nobody can collect would pile up otherwise
+    fn expire_pickups(&mut self, dt: u128) -> bool {
+        let count = self.pickups.len();
+        for pickup in self.pickups.iter_mut() {
+            pickup.update(dt);
+        }
+        self.pickups.retain(|pickup| !pickup.expired());
+        count != self.pickups.len()
+    }
     pub fn handle_server_message(&mut self, message: server::Message) {
         use server::Message;
         match message {
@@ -102,6 +171,12 @@ impl Scene {
             Message::ResponseFrameNumber(_number) => {
                 // do nothing yet
             }
+            Message::DefinitionsHash(_hash) => {
+                // clients compare it with their cached definitions
+            }
+            Message::Definitions(_definitions) => {
+                // stored by clients
+            }
             Message::Update(update) => {
                 self.handle_server_update(update);
             }
@@ -134,8 +209,8 @@ impl Scene {
         use client::Message;
 
         match message {
-            Message::Join => {
-                println!("Message::Join in game-core scene");
+            Message::Join(_character_id) => {
+                // handled by the server, which has access to character data
             }
             Message::Move(kind, movement) => {
                 // println!("Message::Move in game-core scene: {kind:?} {movement:?}");
@@ -145,13 +220,19 @@ impl Scene {
                 // println!("Message::HeroDash in game-core scene");
                 hero.dash();
             }
-            Message::HeroAttack => {
+            Message::HeroAttack(aim) => {
                 // println!("Message::HeroAttack in game-core scene");
-                hero.check_attack();
+                hero.check_attack(aim);
+            }
+            Message::UseAbility(slot, aim) => {
+                hero.use_ability(slot, aim);
             }
             Message::RequestFrameNumber => {
                 // do nothing
             }
+            Message::RequestDefinitions => {
+                // handled by the server
+            }
         }
     }
     pub fn stop(&mut self) {
@@ -171,5 +252,31 @@ impl Scene {
         }
         self.effects = Vec::new();
         self.projectiles = Vec::new();
+        self.pickups = Vec::new();
+    }
+}
+
+// pickups are placed in a circle around the defeated npc,
+// moved out of obstacles and inside the level bounds
+fn spawn_pickups(
+    pickups: &mut Vec<Pickup>,
+    drops: &[ResourceDrop],
+    position: Point2<f32>,
+    level: Option<&Level>,
+) {
+    let total: u32 = drops.iter().map(|drop| drop.count).sum();
+    let radius = 50.0;
+    let mut index = 0;
+    for drop in drops.iter() {
+        for _ in 0..drop.count {
+            let angle = index as f32 / total as f32 * 2.0 * std::f32::consts::PI;
+            let offset = Vector2::new(angle.cos(), angle.sin()) * radius;
+            let mut pickup_position = position + offset;
+            if let Some(level) = level {
+                pickup_position = level.keep_inside(pickup_position, PICKUP_SIZE);
+            }
+            pickups.push(Pickup::new(drop.resource_id, drop.amount, pickup_position));
+            index += 1;
+        }
     }
 }
@@ -0,0 +1,30 @@
+use game_data::GameData;
+use shared::bundle::SCHEMA_VERSION;
+
+// Rewrites every file in the data folder in the latest schema version.
+// Usage: migrate [--data path/to/data]
+fn main() {
+    let game_data = match GameData::resolve(game_data::data_path_arg()) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            println!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Migrating {} to schema version {SCHEMA_VERSION}",
+        game_data.root().display()
+    );
+    match game_data::migrate(&game_data) {
+        Ok(upgraded) => {
+            for path in upgraded.iter() {
+                println!("upgraded {}", path.display());
+            }
+            println!("{} files upgraded", upgraded.len());
+        }
+        Err(e) => {
+            println!("error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
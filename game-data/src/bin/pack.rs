@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use game_data::GameData;
+
+const OUT_PATH_ARG: &str = "--out";
+
+fn out_path_arg() -> Option<PathBuf> {
+    let mut args = std::env::args().skip_while(|arg| arg != OUT_PATH_ARG);
+    args.next()?;
+    args.next().map(PathBuf::from)
+}
+
+// Packs the data folder into a single bundle file, refuses to pack invalid data.
+// Usage: pack [--data path/to/data] [--out path/to/data.bundle]
+fn main() {
+    let game_data = match GameData::resolve(game_data::data_path_arg()) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            println!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    if game_data.bundle().is_some() {
+        println!(
+            "error: {} is already a bundle, pack needs a data folder",
+            game_data.root().display()
+        );
+        std::process::exit(1);
+    }
+    let out_path = out_path_arg().unwrap_or(PathBuf::from("../data.bundle"));
+    println!("Packing {}", game_data.root().display());
+
+    let report = game_data::validate(&game_data);
+    for issue in report.issues.iter() {
+        println!("{issue}");
+    }
+    if report.has_errors() {
+        println!("{} errors, bundle is not written", report.error_count());
+        std::process::exit(1);
+    }
+
+    let result = game_data::pack(&game_data)
+        .and_then(|bundle| game_data::write_bundle(&out_path, &bundle).map(|_| bundle));
+    match result {
+        Ok(bundle) => {
+            println!("Written {}", out_path.display());
+            println!(
+                "schema version {}, hash {}",
+                bundle.schema_version, bundle.hash
+            );
+        }
+        Err(e) => {
+            println!("error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
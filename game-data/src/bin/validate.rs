@@ -0,0 +1,26 @@
+use game_data::GameData;
+
+// Checks every file in the data folder, exits with 1 if there are errors.
+// Usage: validate [--data path/to/data or path/to/data.bundle]
+fn main() {
+    let game_data = match GameData::resolve(game_data::data_path_arg()) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            println!("error: {e}");
+            std::process::exit(1);
+        }
+    };
+    println!("Validating {}", game_data.root().display());
+    let report = game_data::validate(&game_data);
+    for issue in report.issues.iter() {
+        println!("{issue}");
+    }
+    println!(
+        "{} errors, {} warnings",
+        report.error_count(),
+        report.warning_count()
+    );
+    if report.has_errors() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+
+use shared::attack::{AttackConstructor, ComplexAttackConstructor};
+use shared::bundle::{BundleContents, DataBundle, SCHEMA_VERSION};
+use shared::character::CharacterSettings;
+use shared::level::Level;
+use shared::npc::NpcConstructor;
+use shared::resource::ResourceConstructor;
+
+use crate::{Entity, Error, GameData, Result};
+
+pub const BUNDLE_EXTENSION: &str = "bundle";
+
+fn pack_section<T: Entity>(data: &GameData, contents: &mut BundleContents) -> Result<()> {
+    let Some(section) = T::section_mut(contents) else {
+        return Ok(());
+    };
+    section.list = data.list::<T>()?;
+    // hidden entries are packed too, they may still be referenced
+    for entry in section.list.list.iter() {
+        section.items.insert(entry.id, data.load::<T>(entry.id)?);
+    }
+    Ok(())
+}
+
+fn hash_contents(path: &Path, contents: &BundleContents) -> Result<String> {
+    let encoded = rmp_serde::to_vec_named(contents).map_err(|source| Error::EncodeBundle {
+        path: path.to_owned(),
+        source,
+    })?;
+    let digest = Sha1::digest(&encoded);
+    Ok(digest.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+// collects every list and item into a bundle
+pub fn pack(data: &GameData) -> Result<DataBundle> {
+    let mut contents = BundleContents::default();
+    pack_section::<AttackConstructor>(data, &mut contents)?;
+    pack_section::<ComplexAttackConstructor>(data, &mut contents)?;
+    pack_section::<NpcConstructor>(data, &mut contents)?;
+    pack_section::<Level>(data, &mut contents)?;
+    pack_section::<CharacterSettings>(data, &mut contents)?;
+    pack_section::<ResourceConstructor>(data, &mut contents)?;
+    Ok(DataBundle {
+        schema_version: SCHEMA_VERSION,
+        hash: hash_contents(data.root(), &contents)?,
+        contents,
+    })
+}
+
+pub fn write_bundle(path: &Path, bundle: &DataBundle) -> Result<()> {
+    let encoded = rmp_serde::to_vec_named(bundle).map_err(|source| Error::EncodeBundle {
+        path: path.to_owned(),
+        source,
+    })?;
+    std::fs::write(path, encoded).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+pub fn read_bundle(path: &Path) -> Result<DataBundle> {
+    let encoded = std::fs::read(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let bundle: DataBundle = rmp_serde::from_slice(&encoded).map_err(|source| Error::Decode {
+        path: path.to_owned(),
+        source,
+    })?;
+    if bundle.schema_version != SCHEMA_VERSION {
+        return Err(Error::SchemaVersion {
+            path: path.to_owned(),
+            found: bundle.schema_version,
+            expected: SCHEMA_VERSION,
+        });
+    }
+    if hash_contents(path, &bundle.contents)? != bundle.hash {
+        return Err(Error::HashMismatch {
+            path: path.to_owned(),
+        });
+    }
+    Ok(bundle)
+}
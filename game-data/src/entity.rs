@@ -0,0 +1,146 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use shared::attack::{AttackConstructor, ComplexAttackConstructor};
+use shared::bundle::{BundleContents, BundleSection};
+use shared::character::CharacterSettings;
+use shared::level::Level;
+use shared::npc::NpcConstructor;
+use shared::resource::ResourceConstructor;
+
+// Something stored as `{FOLDER}/{FILE_PREFIX}_{id}.json`,
+// with the list of entries in `{FOLDER}/list.json`
+pub trait Entity: Clone + DeserializeOwned + Serialize + Send + Sync + 'static {
+    const FOLDER: &'static str;
+    const FILE_PREFIX: &'static str;
+
+    // where the entity is stored in a data bundle, None if it isn't packed
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>>;
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>>;
+
+    // other entities this one points to, as (FOLDER, id)
+    fn references(&self) -> Vec<(&'static str, u32)> {
+        Vec::new()
+    }
+    // drops whatever points to the entity, used when it's deleted
+    fn remove_references(&mut self, _folder: &str, _id: u32) {}
+}
+
+impl Entity for AttackConstructor {
+    const FOLDER: &'static str = "attack";
+    const FILE_PREFIX: &'static str = "attack";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.attacks)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.attacks)
+    }
+}
+
+impl Entity for ComplexAttackConstructor {
+    const FOLDER: &'static str = "complex_attack";
+    const FILE_PREFIX: &'static str = "complex_attack";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.complex_attacks)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.complex_attacks)
+    }
+}
+
+impl Entity for NpcConstructor {
+    const FOLDER: &'static str = "npc";
+    const FILE_PREFIX: &'static str = "npc";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.npc)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.npc)
+    }
+    fn references(&self) -> Vec<(&'static str, u32)> {
+        let attacks = self.attacks.iter();
+        attacks
+            .map(|item| (AttackConstructor::FOLDER, item.id))
+            .collect()
+    }
+    fn remove_references(&mut self, folder: &str, id: u32) {
+        if folder == AttackConstructor::FOLDER {
+            self.attacks.retain(|item| item.id != id);
+        }
+    }
+}
+
+impl Entity for Level {
+    const FOLDER: &'static str = "level";
+    const FILE_PREFIX: &'static str = "level";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.levels)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.levels)
+    }
+    fn references(&self) -> Vec<(&'static str, u32)> {
+        let npc_list = self.npc_list.iter();
+        npc_list
+            .map(|item| (NpcConstructor::FOLDER, item.id))
+            .collect()
+    }
+    fn remove_references(&mut self, folder: &str, id: u32) {
+        if folder == NpcConstructor::FOLDER {
+            self.npc_list.retain(|item| item.id != id);
+        }
+    }
+}
+
+impl Entity for CharacterSettings {
+    const FOLDER: &'static str = "character";
+    const FILE_PREFIX: &'static str = "character";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.characters)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.characters)
+    }
+    fn references(&self) -> Vec<(&'static str, u32)> {
+        let mut references = Vec::new();
+        for ability in self.abilities.iter() {
+            references.push((AttackConstructor::FOLDER, ability.attack.id));
+            if let Some(resource) = &ability.resource {
+                references.push((ResourceConstructor::FOLDER, resource.id));
+            }
+        }
+        references
+    }
+    // an ability without the attack is removed,
+    // without the resource it becomes free
+    fn remove_references(&mut self, folder: &str, id: u32) {
+        if folder == AttackConstructor::FOLDER {
+            self.abilities.retain(|ability| ability.attack.id != id);
+        }
+        if folder == ResourceConstructor::FOLDER {
+            for ability in self.abilities.iter_mut() {
+                if ability.resource.as_ref().is_some_and(|item| item.id == id) {
+                    ability.resource = None;
+                    ability.cost = 0;
+                }
+            }
+        }
+    }
+}
+
+impl Entity for ResourceConstructor {
+    const FOLDER: &'static str = "resource";
+    const FILE_PREFIX: &'static str = "resource";
+
+    fn section(contents: &BundleContents) -> Option<&BundleSection<Self>> {
+        Some(&contents.resources)
+    }
+    fn section_mut(contents: &mut BundleContents) -> Option<&mut BundleSection<Self>> {
+        Some(&mut contents.resources)
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{}: at `{json_path}`: {source}", path.display())]
+    Json {
+        path: PathBuf,
+        json_path: String,
+        source: serde_json::Error,
+    },
+    #[error("{}: failed to encode: {source}", path.display())]
+    Encode {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("{}: failed to encode bundle: {source}", path.display())]
+    EncodeBundle {
+        path: PathBuf,
+        source: rmp_serde::encode::Error,
+    },
+    #[error("{}: failed to decode bundle: {source}", path.display())]
+    Decode {
+        path: PathBuf,
+        source: rmp_serde::decode::Error,
+    },
+    #[error("{}: bundle has schema version {found}, expected {expected}", path.display())]
+    SchemaVersion {
+        path: PathBuf,
+        found: u32,
+        expected: u32,
+    },
+    #[error("{}: schema version {found} is newer than {latest}, update the game", path.display())]
+    NewerSchema {
+        path: PathBuf,
+        found: u32,
+        latest: u32,
+    },
+    #[error("{}: expected an object with a numeric `schema_version`", path.display())]
+    InvalidSchema { path: PathBuf },
+    #[error("{}: bundle contents don't match its hash", path.display())]
+    HashMismatch { path: PathBuf },
+    #[error("{}: not found in the bundle", path.display())]
+    Missing { path: PathBuf },
+    #[error("{}: game data is loaded from a bundle and can't be changed", path.display())]
+    ReadOnly { path: PathBuf },
+    #[error("{}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+}
+
+impl Error {
+    pub fn path(&self) -> &Path {
+        match self {
+            Error::Io { path, .. }
+            | Error::Json { path, .. }
+            | Error::Encode { path, .. }
+            | Error::EncodeBundle { path, .. }
+            | Error::Decode { path, .. }
+            | Error::SchemaVersion { path, .. }
+            | Error::NewerSchema { path, .. }
+            | Error::InvalidSchema { path }
+            | Error::HashMismatch { path }
+            | Error::Missing { path }
+            | Error::ReadOnly { path }
+            | Error::Invalid { path, .. } => path,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use shared::attack::AttackConstructor;
+use shared::bundle::DataBundle;
+use shared::character::{CharacterDefinition, CharacterSettings};
+use shared::list::EntryList;
+use shared::npc::NpcConstructor;
+use shared::resource::{ResourceConstructor, ResourceDrop};
+
+mod bundle;
+mod entity;
+mod error;
+mod migrate;
+mod usage;
+mod validate;
+
+pub use bundle::{BUNDLE_EXTENSION, pack, read_bundle, write_bundle};
+pub use entity::Entity;
+pub use error::{Error, Result};
+pub use migrate::{VERSION_FIELD, migrate};
+pub use usage::Usage;
+pub use validate::{Issue, Report, Severity, validate};
+
+pub const DATA_PATH_ENV: &str = "GAME_DATA_PATH";
+const DATA_PATH_ARG: &str = "--data";
+const LIST_FILE_NAME: &str = "list.json";
+
+type Cache = HashMap<PathBuf, Arc<dyn Any + Send + Sync>>;
+
+enum Source {
+    Folder,
+    Bundle(Box<DataBundle>),
+}
+
+// Game data from a data folder or a packed bundle file,
+// a bundle is read-only
+pub struct GameData {
+    root: PathBuf,
+    source: Source,
+    cache: Mutex<Cache>,
+}
+
+// value of `--data <path>` command line argument
+pub fn data_path_arg() -> Option<PathBuf> {
+    let mut args = std::env::args().skip_while(|arg| arg != DATA_PATH_ARG);
+    args.next()?;
+    args.next().map(PathBuf::from)
+}
+
+impl GameData {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            source: Source::Folder,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    pub fn from_bundle(path: impl Into<PathBuf>) -> Result<Self> {
+        let root = path.into();
+        let bundle = read_bundle(&root)?;
+        Ok(Self {
+            root,
+            source: Source::Bundle(Box::new(bundle)),
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+    // a file is treated as a bundle, anything else as a data folder
+    pub fn open(path: PathBuf) -> Result<Self> {
+        if path.is_file() {
+            Self::from_bundle(path)
+        } else {
+            Ok(Self::new(path))
+        }
+    }
+    // explicit path (command line or config) wins over GAME_DATA_PATH,
+    // otherwise look for `data` folder or `data.bundle` file
+    // in the current and parent folders
+    pub fn resolve(explicit: Option<PathBuf>) -> Result<Self> {
+        if let Some(path) = explicit {
+            return Self::open(path);
+        }
+        if let Some(path) = std::env::var_os(DATA_PATH_ENV) {
+            return Self::open(PathBuf::from(path));
+        }
+        let bundle_name = format!("data.{BUNDLE_EXTENSION}");
+        let candidates = [
+            PathBuf::from("data"),
+            PathBuf::from("../data"),
+            PathBuf::from(&bundle_name),
+            PathBuf::from("..").join(&bundle_name),
+        ];
+        match candidates.into_iter().find(|path| path.exists()) {
+            Some(path) => Self::open(path),
+            None => Ok(Self::new("../data")),
+        }
+    }
+    pub fn bundle(&self) -> Option<&DataBundle> {
+        match &self.source {
+            Source::Folder => None,
+            Source::Bundle(bundle) => Some(bundle),
+        }
+    }
+    // drops cached files, or reads the bundle again
+    pub fn refresh(&mut self) -> Result<()> {
+        self.clear_cache();
+        if let Source::Bundle(bundle) = &mut self.source {
+            **bundle = read_bundle(&self.root)?;
+        }
+        Ok(())
+    }
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    pub fn item_path<T: Entity>(&self, id: u32) -> PathBuf {
+        let file_name = format!("{}_{id}.json", T::FILE_PREFIX);
+        self.root.join(T::FOLDER).join(file_name)
+    }
+    pub fn list_path<T: Entity>(&self) -> PathBuf {
+        self.root.join(T::FOLDER).join(LIST_FILE_NAME)
+    }
+    pub fn load<T: Entity>(&self, id: u32) -> Result<T> {
+        let path = self.item_path::<T>(id);
+        match &self.source {
+            Source::Folder => self.read_cached(path, T::FILE_PREFIX),
+            Source::Bundle(bundle) => T::section(&bundle.contents)
+                .and_then(|section| section.items.get(&id))
+                .cloned()
+                .ok_or(Error::Missing { path }),
+        }
+    }
+    pub fn save<T: Entity>(&self, id: u32, value: &T) -> Result<()> {
+        let path = self.writable_path(self.item_path::<T>(id))?;
+        self.write_cached(path, value)
+    }
+    pub fn delete<T: Entity>(&self, id: u32) -> Result<()> {
+        let path = self.writable_path(self.item_path::<T>(id))?;
+        self.cache.lock().unwrap().remove(&path);
+        std::fs::remove_file(&path).map_err(|source| Error::Io { path, source })
+    }
+    pub fn list<T: Entity>(&self) -> Result<EntryList> {
+        let path = self.list_path::<T>();
+        match &self.source {
+            Source::Folder => self.read_cached(path, migrate::LIST_TARGET),
+            Source::Bundle(bundle) => T::section(&bundle.contents)
+                .map(|section| section.list.clone())
+                .ok_or(Error::Missing { path }),
+        }
+    }
+    pub fn save_list<T: Entity>(&self, list: &EntryList) -> Result<()> {
+        let path = self.writable_path(self.list_path::<T>())?;
+        self.write_cached(path, list)
+    }
+    // skip the cache and read the file again
+    pub fn reload<T: Entity>(&self, id: u32) -> Result<T> {
+        self.cache.lock().unwrap().remove(&self.item_path::<T>(id));
+        self.load(id)
+    }
+    pub fn reload_list<T: Entity>(&self) -> Result<EntryList> {
+        self.cache.lock().unwrap().remove(&self.list_path::<T>());
+        self.list::<T>()
+    }
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    pub fn npc_attacks(&self, npc: &NpcConstructor) -> Result<Vec<AttackConstructor>> {
+        npc.attacks.iter().map(|item| self.load(item.id)).collect()
+    }
+    pub fn character_definition(&self, id: u32) -> Result<CharacterDefinition> {
+        let settings: CharacterSettings = self.load(id)?;
+        let mut attacks = HashMap::new();
+        let mut resources = HashMap::new();
+        for ability in settings.abilities.iter() {
+            let attack_id = ability.attack.id;
+            attacks.insert(attack_id, self.load::<AttackConstructor>(attack_id)?);
+            if let Some(resource) = &ability.resource {
+                let constructor = self.load::<ResourceConstructor>(resource.id)?;
+                resources.insert(resource.id, constructor);
+            }
+        }
+        Ok(CharacterDefinition {
+            id,
+            settings,
+            attacks,
+            resources,
+        })
+    }
+    // all characters which are not hidden in the list
+    pub fn character_definitions(&self) -> Result<Vec<CharacterDefinition>> {
+        self.list::<CharacterSettings>()?
+            .list
+            .into_iter()
+            .filter(|item| item.status.is_active())
+            .map(|item| self.character_definition(item.id))
+            .collect()
+    }
+    pub fn resource_drops(&self) -> Result<Vec<ResourceDrop>> {
+        let mut drops = Vec::new();
+        for item in self.list::<ResourceConstructor>()?.list {
+            if !item.status.is_active() {
+                continue;
+            }
+            let constructor: ResourceConstructor = self.load(item.id)?;
+            let drop = ResourceDrop::new(item.id, &constructor);
+            if drop.count > 0 {
+                drops.push(drop);
+            }
+        }
+        Ok(drops)
+    }
+
+    fn writable_path(&self, path: PathBuf) -> Result<PathBuf> {
+        match self.source {
+            Source::Folder => Ok(path),
+            Source::Bundle(_) => Err(Error::ReadOnly { path }),
+        }
+    }
+    // old files are upgraded in memory, `migrate` writes them back
+    fn read_cached<V>(&self, path: PathBuf, target: &str) -> Result<V>
+    where
+        V: Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        if let Some(value) = self.cache.lock().unwrap().get(&path)
+            && let Some(value) = value.downcast_ref::<V>()
+        {
+            return Ok(value.clone());
+        }
+        let (value, _upgraded): (V, bool) = migrate::read_data_file(&path, target)?;
+        let cached = Arc::new(value.clone());
+        self.cache.lock().unwrap().insert(path, cached);
+        Ok(value)
+    }
+    fn write_cached<V>(&self, path: PathBuf, value: &V) -> Result<()>
+    where
+        V: Clone + Serialize + Send + Sync + 'static,
+    {
+        migrate::write_data_file(&path, value)?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(path, Arc::new(value.clone()));
+        Ok(())
+    }
+}
+
+pub fn read_json<V: DeserializeOwned>(path: &Path) -> Result<V> {
+    let contents = std::fs::read(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    let deserializer = &mut serde_json::Deserializer::from_slice(&contents);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| Error::Json {
+        path: path.to_owned(),
+        json_path: e.path().to_string(),
+        source: e.into_inner(),
+    })
+}
+
+pub fn write_json<V: Serialize>(path: &Path, value: &V) -> Result<()> {
+    let contents = serde_json::to_vec_pretty(value).map_err(|source| Error::Encode {
+        path: path.to_owned(),
+        source,
+    })?;
+    std::fs::write(path, contents).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
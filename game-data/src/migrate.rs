@@ -0,0 +1,361 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use shared::attack::{AttackConstructor, ComplexAttackConstructor};
+use shared::bundle::SCHEMA_VERSION;
+use shared::character::CharacterSettings;
+use shared::level::Level;
+use shared::list::EntryList;
+use shared::npc::NpcConstructor;
+use shared::resource::ResourceConstructor;
+
+use crate::{Entity, Error, GameData, Result, read_json, write_json};
+
+pub const VERSION_FIELD: &str = "schema_version";
+// files written before versioning have the layout of the first bundle
+const UNVERSIONED: u32 = 1;
+pub(crate) const LIST_TARGET: &str = "list";
+
+// upgrades one kind of data file from version `from` to `from + 1`
+struct Migration {
+    target: &'static str, // FILE_PREFIX of an entity, or LIST_TARGET
+    from: u32,
+    apply: fn(&mut Map<String, Value>),
+}
+
+// every change of a stored struct needs a SCHEMA_VERSION bump
+// and a migration here, old files are upgraded on load
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        target: <AttackConstructor as Entity>::FILE_PREFIX,
+        from: 1,
+        apply: drop_legacy_attack_fields,
+    },
+    Migration {
+        target: LIST_TARGET,
+        from: 3,
+        apply: add_entry_tags,
+    },
+    Migration {
+        target: <Level as Entity>::FILE_PREFIX,
+        from: 4,
+        apply: add_level_layout,
+    },
+    Migration {
+        target: <NpcConstructor as Entity>::FILE_PREFIX,
+        from: 5,
+        apply: add_npc_movement_and_weights,
+    },
+];
+
+// position, direction and state are set when an attack starts
+fn drop_legacy_attack_fields(attack: &mut Map<String, Value>) {
+    attack.remove("position");
+    attack.remove("direction");
+    attack.remove("state");
+}
+
+// entries of a list have no tags until they are set in the editor
+fn add_entry_tags(list: &mut Map<String, Value>) {
+    let Some(Value::Array(entries)) = list.get_mut("list") else {
+        return;
+    };
+    for entry in entries.iter_mut().filter_map(|entry| entry.as_object_mut()) {
+        entry.insert("tags".to_owned(), Value::Array(Vec::new()));
+    }
+}
+
+// levels had no layout, npc stood in the middle of the default arena
+// and players appeared at the same place
+fn add_level_layout(level: &mut Map<String, Value>) {
+    level.insert(
+        "bounds".to_owned(),
+        json!({"width": 1024.0, "height": 768.0}),
+    );
+    level.insert("player_spawns".to_owned(), json!([[250.0, 200.0]]));
+    level.insert("obstacles".to_owned(), json!([]));
+    let Some(Value::Array(npc_list)) = level.get_mut("npc_list") else {
+        return;
+    };
+    for npc in npc_list.iter_mut().filter_map(|npc| npc.as_object_mut()) {
+        npc.insert("position".to_owned(), json!([512.0, 384.0]));
+        npc.insert("facing".to_owned(), json!(0.0));
+        npc.insert("hp".to_owned(), Value::Null);
+        npc.insert("trigger".to_owned(), json!("OnStart"));
+    }
+}
+
+// npc didn't move and picked every attack equally often
+fn add_npc_movement_and_weights(npc: &mut Map<String, Value>) {
+    npc.insert("speed".to_owned(), json!(0.0));
+    let Some(Value::Array(attacks)) = npc.get_mut("attacks") else {
+        return;
+    };
+    for attack in attacks
+        .iter_mut()
+        .filter_map(|attack| attack.as_object_mut())
+    {
+        attack.insert("weight".to_owned(), json!(1));
+        attack.insert("cooldown".to_owned(), json!(0));
+    }
+}
+
+#[derive(Serialize)]
+struct Versioned<'a, V> {
+    schema_version: u32,
+    #[serde(flatten)]
+    value: &'a V,
+}
+
+// brings a file to SCHEMA_VERSION, returns true if anything was upgraded
+fn upgrade(path: &Path, target: &str, value: &mut Value) -> Result<bool> {
+    let Some(object) = value.as_object_mut() else {
+        return Err(Error::InvalidSchema {
+            path: path.to_owned(),
+        });
+    };
+    let found = match object.get(VERSION_FIELD) {
+        None => UNVERSIONED,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| Error::InvalidSchema {
+                path: path.to_owned(),
+            })?,
+    };
+    if found > SCHEMA_VERSION {
+        return Err(Error::NewerSchema {
+            path: path.to_owned(),
+            found,
+            latest: SCHEMA_VERSION,
+        });
+    }
+    for version in found..SCHEMA_VERSION {
+        MIGRATIONS
+            .iter()
+            .filter(|migration| migration.target == target && migration.from == version)
+            .for_each(|migration| (migration.apply)(object));
+    }
+    object.insert(VERSION_FIELD.to_owned(), SCHEMA_VERSION.into());
+    Ok(found < SCHEMA_VERSION)
+}
+
+// reads a data file of any version, the second value is true
+// if the file is older than SCHEMA_VERSION
+pub(crate) fn read_data_file<V: DeserializeOwned>(path: &Path, target: &str) -> Result<(V, bool)> {
+    let mut value: Value = read_json(path)?;
+    let upgraded = upgrade(path, target, &mut value)?;
+    let value = serde_path_to_error::deserialize(value).map_err(|e| Error::Json {
+        path: path.to_owned(),
+        json_path: e.path().to_string(),
+        source: e.into_inner(),
+    })?;
+    Ok((value, upgraded))
+}
+
+pub(crate) fn write_data_file<V: Serialize>(path: &Path, value: &V) -> Result<()> {
+    let versioned = Versioned {
+        schema_version: SCHEMA_VERSION,
+        value,
+    };
+    write_json(path, &versioned)
+}
+
+fn rewrite<V>(path: &Path, target: &str, upgraded: &mut Vec<PathBuf>) -> Result<V>
+where
+    V: DeserializeOwned + Serialize,
+{
+    let (value, was_upgraded) = read_data_file(path, target)?;
+    write_data_file(path, &value)?;
+    if was_upgraded {
+        upgraded.push(path.to_owned());
+    }
+    Ok(value)
+}
+
+fn migrate_entity<T: Entity>(data: &GameData, upgraded: &mut Vec<PathBuf>) -> Result<()> {
+    let list: EntryList = rewrite(&data.list_path::<T>(), LIST_TARGET, upgraded)?;
+    for entry in list.list.iter() {
+        rewrite::<T>(&data.item_path::<T>(entry.id), T::FILE_PREFIX, upgraded)?;
+    }
+    Ok(())
+}
+
+// rewrites every list and listed item in SCHEMA_VERSION,
+// returns files which were upgraded
+pub fn migrate(data: &GameData) -> Result<Vec<PathBuf>> {
+    if data.bundle().is_some() {
+        return Err(Error::ReadOnly {
+            path: data.root().to_owned(),
+        });
+    }
+    let mut upgraded = Vec::new();
+    migrate_entity::<AttackConstructor>(data, &mut upgraded)?;
+    migrate_entity::<ComplexAttackConstructor>(data, &mut upgraded)?;
+    migrate_entity::<NpcConstructor>(data, &mut upgraded)?;
+    migrate_entity::<Level>(data, &mut upgraded)?;
+    migrate_entity::<CharacterSettings>(data, &mut upgraded)?;
+    migrate_entity::<ResourceConstructor>(data, &mut upgraded)?;
+    data.clear_cache();
+    Ok(upgraded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Case {
+        name: &'static str,
+        target: &'static str,
+        old: Value,
+        // json pointers with their expected values after the upgrade
+        expected: Vec<(&'static str, Value)>,
+        removed: Vec<&'static str>,
+    }
+
+    fn cases() -> Vec<Case> {
+        vec![
+            Case {
+                name: "attack v1 drops legacy fields",
+                target: <AttackConstructor as Entity>::FILE_PREFIX,
+                old: json!({
+                    "name": "Narrow arc",
+                    "delay": 0,
+                    "time_to_complete": 500,
+                    "aftercast": 100,
+                    "kind": "Pizza",
+                    "order": "CloseToFar",
+                    "range": {"from": 0.0, "to": 100.0},
+                    "width_angle": 0.5,
+                    "position": [0.0, 0.0],
+                    "direction": [1.0, 0.0],
+                    "state": "Selected",
+                }),
+                expected: vec![("/name", json!("Narrow arc"))],
+                removed: vec!["/position", "/direction", "/state"],
+            },
+            Case {
+                name: "list v3 gets tags",
+                target: LIST_TARGET,
+                old: json!({
+                    "schema_version": 3,
+                    "last_id": 2,
+                    "list": [
+                        {"id": 1, "name": "First", "status": "Active"},
+                        {"id": 2, "name": "Second", "status": "Hidden"},
+                    ],
+                }),
+                expected: vec![
+                    ("/list/0/tags", json!([])),
+                    ("/list/1/tags", json!([])),
+                    ("/list/1/status", json!("Hidden")),
+                ],
+                removed: vec![],
+            },
+            Case {
+                name: "level v4 gets a layout",
+                target: <Level as Entity>::FILE_PREFIX,
+                old: json!({
+                    "schema_version": 4,
+                    "name": "First level",
+                    "npc_list": [{"id": 1, "name": "First boss"}],
+                }),
+                expected: vec![
+                    ("/bounds", json!({"width": 1024.0, "height": 768.0})),
+                    ("/player_spawns", json!([[250.0, 200.0]])),
+                    ("/obstacles", json!([])),
+                    ("/npc_list/0/position", json!([512.0, 384.0])),
+                    ("/npc_list/0/facing", json!(0.0)),
+                    ("/npc_list/0/hp", Value::Null),
+                    ("/npc_list/0/trigger", json!("OnStart")),
+                ],
+                removed: vec![],
+            },
+            Case {
+                name: "npc v5 gets speed and attack weights",
+                target: <NpcConstructor as Entity>::FILE_PREFIX,
+                old: json!({
+                    "schema_version": 5,
+                    "name": "First boss",
+                    "respawn_time": 10,
+                    "attacks": [{"id": 1, "name": "Narrow arc"}],
+                    "hp": 300,
+                    "size": 30.0,
+                }),
+                expected: vec![
+                    ("/speed", json!(0.0)),
+                    ("/attacks/0/weight", json!(1)),
+                    ("/attacks/0/cooldown", json!(0)),
+                ],
+                removed: vec![],
+            },
+        ]
+    }
+
+    // the upgraded value should load as the current struct
+    fn check_loads(target: &str, value: Value) -> std::result::Result<(), serde_json::Error> {
+        match target {
+            LIST_TARGET => serde_json::from_value::<EntryList>(value).map(|_| ()),
+            "attack" => serde_json::from_value::<AttackConstructor>(value).map(|_| ()),
+            "level" => serde_json::from_value::<Level>(value).map(|_| ()),
+            "npc" => serde_json::from_value::<NpcConstructor>(value).map(|_| ()),
+            other => panic!("no struct for {other}"),
+        }
+    }
+
+    #[test]
+    fn upgrade_old_files() {
+        let path = Path::new("test.json");
+        for case in cases() {
+            let mut value = case.old;
+            let upgraded = upgrade(path, case.target, &mut value).unwrap();
+            assert!(upgraded, "{}: not upgraded", case.name);
+            assert_eq!(
+                value[VERSION_FIELD],
+                json!(SCHEMA_VERSION),
+                "{}: schema version",
+                case.name
+            );
+            for (pointer, expected) in case.expected {
+                assert_eq!(
+                    value.pointer(pointer),
+                    Some(&expected),
+                    "{}: {pointer}",
+                    case.name
+                );
+            }
+            for pointer in case.removed {
+                assert_eq!(value.pointer(pointer), None, "{}: {pointer}", case.name);
+            }
+            if let Err(e) = check_loads(case.target, value) {
+                panic!("{}: {e}", case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn keep_latest_files() {
+        let mut value = json!({VERSION_FIELD: SCHEMA_VERSION, "last_id": 0, "list": []});
+        let before = value.clone();
+        let upgraded = upgrade(Path::new("list.json"), LIST_TARGET, &mut value).unwrap();
+        assert!(!upgraded);
+        assert_eq!(value, before);
+    }
+
+    #[test]
+    fn reject_newer_and_invalid_versions() {
+        let path = Path::new("npc_1.json");
+        let mut value = json!({VERSION_FIELD: SCHEMA_VERSION + 1});
+        let result = upgrade(path, "npc", &mut value);
+        assert!(matches!(result, Err(Error::NewerSchema { .. })));
+        let mut value = json!({VERSION_FIELD: "two"});
+        let result = upgrade(path, "npc", &mut value);
+        assert!(matches!(result, Err(Error::InvalidSchema { .. })));
+        let mut value = json!([]);
+        let result = upgrade(path, "npc", &mut value);
+        assert!(matches!(result, Err(Error::InvalidSchema { .. })));
+    }
+}
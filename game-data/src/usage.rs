@@ -0,0 +1,71 @@
+use std::fmt::{self, Display};
+
+use shared::attack::{AttackConstructor, ComplexAttackConstructor};
+use shared::character::CharacterSettings;
+use shared::level::Level;
+use shared::npc::NpcConstructor;
+use shared::resource::ResourceConstructor;
+
+use crate::{Entity, GameData, Result};
+
+// an entity which references another one by id
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub folder: &'static str,
+    pub id: u32,
+    pub name: String,
+}
+
+impl Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.folder, self.id, self.name)
+    }
+}
+
+impl GameData {
+    // every listed entity which references `T` with this id
+    pub fn used_by<T: Entity>(&self, id: u32) -> Result<Vec<Usage>> {
+        let mut usages = Vec::new();
+        self.find_usages::<AttackConstructor>(T::FOLDER, id, &mut usages)?;
+        self.find_usages::<ComplexAttackConstructor>(T::FOLDER, id, &mut usages)?;
+        self.find_usages::<NpcConstructor>(T::FOLDER, id, &mut usages)?;
+        self.find_usages::<Level>(T::FOLDER, id, &mut usages)?;
+        self.find_usages::<CharacterSettings>(T::FOLDER, id, &mut usages)?;
+        self.find_usages::<ResourceConstructor>(T::FOLDER, id, &mut usages)?;
+        Ok(usages)
+    }
+    // removes references to `T` with this id from every entity using it,
+    // call before deleting the entity
+    pub fn remove_references<T: Entity>(&self, id: u32) -> Result<()> {
+        self.remove_references_from::<AttackConstructor>(T::FOLDER, id)?;
+        self.remove_references_from::<ComplexAttackConstructor>(T::FOLDER, id)?;
+        self.remove_references_from::<NpcConstructor>(T::FOLDER, id)?;
+        self.remove_references_from::<Level>(T::FOLDER, id)?;
+        self.remove_references_from::<CharacterSettings>(T::FOLDER, id)?;
+        self.remove_references_from::<ResourceConstructor>(T::FOLDER, id)
+    }
+
+    fn find_usages<S: Entity>(&self, folder: &str, id: u32, usages: &mut Vec<Usage>) -> Result<()> {
+        for entry in self.list::<S>()?.list {
+            let item: S = self.load(entry.id)?;
+            if item.references().contains(&(folder, id)) {
+                usages.push(Usage {
+                    folder: S::FOLDER,
+                    id: entry.id,
+                    name: entry.name,
+                });
+            }
+        }
+        Ok(())
+    }
+    fn remove_references_from<S: Entity>(&self, folder: &str, id: u32) -> Result<()> {
+        for entry in self.list::<S>()?.list {
+            let mut item: S = self.load(entry.id)?;
+            if item.references().contains(&(folder, id)) {
+                item.remove_references(folder, id);
+                self.save(entry.id, &item)?;
+            }
+        }
+        Ok(())
+    }
+}
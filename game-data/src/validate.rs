@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
+
+use shared::attack::{AttackConstructor, AttackShapeConstructor, ComplexAttackConstructor};
+use shared::character::{AbilitySlot, CharacterSettings};
+use shared::level::Level;
+use shared::list::EntryList;
+use shared::npc::NpcConstructor;
+use shared::resource::ResourceConstructor;
+use shared::validation::Validate;
+
+use crate::{Entity, Error, GameData};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.file.display(), self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+    fn count(&self, severity: Severity) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .count()
+    }
+    fn error(&mut self, file: &Path, message: impl Into<String>) {
+        self.push(Severity::Error, file, message.into());
+    }
+    fn warning(&mut self, file: &Path, message: impl Into<String>) {
+        self.push(Severity::Warning, file, message.into());
+    }
+    fn load_error(&mut self, error: Error) {
+        match error {
+            Error::Io { path, source } => self.error(&path, source.to_string()),
+            Error::Json {
+                path,
+                json_path,
+                source,
+            } => self.error(&path, format!("at `{json_path}`: {source}")),
+            Error::Missing { path } => self.error(&path, "not found in the bundle"),
+            Error::NewerSchema {
+                path,
+                found,
+                latest,
+            } => {
+                let message = format!("schema version {found} is newer than {latest}");
+                self.error(&path, message);
+            }
+            other => {
+                let path = other.path().to_owned();
+                self.error(&path, other.to_string());
+            }
+        }
+    }
+    fn push(&mut self, severity: Severity, file: &Path, message: String) {
+        self.issues.push(Issue {
+            severity,
+            file: file.to_owned(),
+            message,
+        });
+    }
+}
+
+// every entity of one kind, loaded from the list and item files
+struct Entries<T> {
+    list: EntryList,
+    items: BTreeMap<u32, T>,
+}
+
+impl<T: Entity> Entries<T> {
+    fn load(data: &GameData, report: &mut Report) -> Self {
+        let list_path = data.list_path::<T>();
+        let list = match data.reload_list::<T>() {
+            Ok(list) => list,
+            Err(e) => {
+                report.load_error(e);
+                EntryList::default()
+            }
+        };
+        let mut ids = HashSet::new();
+        let mut items = BTreeMap::new();
+        for entry in list.list.iter() {
+            if !ids.insert(entry.id) {
+                report.error(&list_path, format!("id {} is listed twice", entry.id));
+                continue;
+            }
+            if entry.id > list.last_id {
+                let message = format!("id {} is greater than last_id {}", entry.id, list.last_id);
+                report.error(&list_path, message);
+            }
+            match data.reload::<T>(entry.id) {
+                Ok(item) => {
+                    items.insert(entry.id, item);
+                }
+                Err(e) => report.load_error(e),
+            }
+        }
+        for id in ids_on_disk::<T>(data) {
+            if !ids.contains(&id) {
+                let message = format!("file is not in {}", list_path.display());
+                report.warning(&data.item_path::<T>(id), message);
+            }
+        }
+        Self { list, items }
+    }
+    // reports broken rules of every item
+    fn check_rules(&self, data: &GameData, report: &mut Report)
+    where
+        T: Validate,
+    {
+        for (id, item) in self.items.iter() {
+            let file = data.item_path::<T>(*id);
+            for error in item.validate() {
+                report.error(&file, error.to_string());
+            }
+        }
+    }
+    fn is_hidden(&self, id: u32) -> bool {
+        self.list
+            .list
+            .iter()
+            .any(|entry| entry.id == id && !entry.status.is_active())
+    }
+    // reports missing and hidden entities referenced from another file
+    fn check_reference(&self, id: u32, file: &Path, report: &mut Report) {
+        if !self.items.contains_key(&id) {
+            report.error(file, format!("references missing {} {id}", T::FOLDER));
+        } else if self.is_hidden(id) {
+            report.warning(file, format!("references hidden {} {id}", T::FOLDER));
+        }
+    }
+}
+
+fn ids_on_disk<T: Entity>(data: &GameData) -> Vec<u32> {
+    let folder = data.root().join(T::FOLDER);
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}_", T::FILE_PREFIX);
+    read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let id = file_name.strip_prefix(&prefix)?.strip_suffix(".json")?;
+            id.parse().ok()
+        })
+        .collect()
+}
+
+pub fn validate(data: &GameData) -> Report {
+    let mut report = Report::default();
+    let attacks = Entries::<AttackConstructor>::load(data, &mut report);
+    let complex_attacks = Entries::<ComplexAttackConstructor>::load(data, &mut report);
+    let npc = Entries::<NpcConstructor>::load(data, &mut report);
+    let levels = Entries::<Level>::load(data, &mut report);
+    let characters = Entries::<CharacterSettings>::load(data, &mut report);
+    let resources = Entries::<ResourceConstructor>::load(data, &mut report);
+
+    attacks.check_rules(data, &mut report);
+    complex_attacks.check_rules(data, &mut report);
+    npc.check_rules(data, &mut report);
+    levels.check_rules(data, &mut report);
+    characters.check_rules(data, &mut report);
+    resources.check_rules(data, &mut report);
+
+    for (id, attack) in complex_attacks.items.iter() {
+        let file = data.item_path::<ComplexAttackConstructor>(*id);
+        check_complex_attack(attack, &file, &mut report);
+    }
+    for (id, item) in npc.items.iter() {
+        let file = data.item_path::<NpcConstructor>(*id);
+        for attack in item.attacks.iter() {
+            attacks.check_reference(attack.id, &file, &mut report);
+        }
+    }
+    for (id, level) in levels.items.iter() {
+        let file = data.item_path::<Level>(*id);
+        for item in level.npc_list.iter() {
+            npc.check_reference(item.id, &file, &mut report);
+        }
+    }
+    for (id, character) in characters.items.iter() {
+        let file = data.item_path::<CharacterSettings>(*id);
+        check_character(character, &file, &mut report);
+        for ability in character.abilities.iter() {
+            attacks.check_reference(ability.attack.id, &file, &mut report);
+            if let Some(resource) = &ability.resource {
+                resources.check_reference(resource.id, &file, &mut report);
+            }
+        }
+    }
+    report
+}
+
+// errors are covered by Validate, these only warn
+fn check_complex_attack(attack: &ComplexAttackConstructor, file: &Path, report: &mut Report) {
+    for (sequence_index, sequence) in attack.sequences.iter().enumerate() {
+        for (part_index, part) in sequence.parts.iter().enumerate() {
+            match &part.shape {
+                AttackShapeConstructor::Circle(_) | AttackShapeConstructor::Pizza(_) => (),
+                other => {
+                    let message = format!(
+                        "sequences.{sequence_index}.parts.{part_index}: \
+                        {other:?} shape is not implemented, it never hits"
+                    );
+                    report.warning(file, message);
+                }
+            }
+        }
+    }
+}
+
+fn check_character(character: &CharacterSettings, file: &Path, report: &mut Report) {
+    for ability in character.abilities.iter() {
+        if ability.resource.is_none() && ability.cost > 0 {
+            report.warning(file, format!("{} has a cost but no resource", ability.slot));
+        }
+    }
+    if character.ability(AbilitySlot::Primary).is_none() {
+        report.warning(file, "no ability in Primary slot, hero can't attack");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::character::{AbilityAttackInfo, HeroAbility};
+    use shared::level::LevelNpcInfo;
+    use shared::list::{EntryInfo, EntryStatus};
+    use shared::npc::NpcAttackInfo;
+
+    use super::*;
+
+    // a data folder in the temp folder, removed on drop
+    struct TempData(GameData);
+
+    impl Drop for TempData {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(self.0.root());
+        }
+    }
+
+    fn save<T: Entity>(data: &GameData, id: u32, value: &T) {
+        let list = EntryList {
+            last_id: id,
+            list: vec![EntryInfo {
+                id,
+                name: format!("{} {id}", T::FOLDER),
+                status: EntryStatus::Active,
+                tags: Vec::new(),
+            }],
+        };
+        data.save_list::<T>(&list).unwrap();
+        data.save(id, value).unwrap();
+    }
+
+    fn save_empty_list<T: Entity>(data: &GameData) {
+        std::fs::create_dir_all(data.root().join(T::FOLDER)).unwrap();
+        data.save_list::<T>(&EntryList::default()).unwrap();
+    }
+
+    fn character(abilities: Vec<HeroAbility>) -> CharacterSettings {
+        CharacterSettings {
+            abilities,
+            ..CharacterSettings::new("hero".to_owned())
+        }
+    }
+
+    fn primary_ability(attack_id: u32) -> HeroAbility {
+        HeroAbility {
+            slot: AbilitySlot::Primary,
+            attack: AbilityAttackInfo {
+                id: attack_id,
+                name: String::new(),
+            },
+            cooldown: 0,
+            resource: None,
+            cost: 0,
+        }
+    }
+
+    fn npc(attack_id: u32) -> NpcConstructor {
+        NpcConstructor {
+            name: "npc".to_owned(),
+            hp: 100,
+            size: 20.0,
+            attacks: vec![NpcAttackInfo {
+                id: attack_id,
+                ..NpcAttackInfo::default()
+            }],
+            ..NpcConstructor::default()
+        }
+    }
+
+    fn level(npc_list: Vec<LevelNpcInfo>) -> Level {
+        Level {
+            npc_list,
+            ..Level::new("level".to_owned())
+        }
+    }
+
+    fn level_npc(id: u32) -> LevelNpcInfo {
+        LevelNpcInfo {
+            id,
+            ..LevelNpcInfo::default()
+        }
+    }
+
+    // attack 1, npc 1 with it, level 1 with the npc
+    // and character 1 using the attack as Primary
+    fn valid_data(name: &str) -> TempData {
+        let folder = format!("game-data-validate-{name}-{}", std::process::id());
+        let root = std::env::temp_dir().join(folder);
+        let _ = std::fs::remove_dir_all(&root);
+        let data = TempData(GameData::new(root));
+        save_empty_list::<AttackConstructor>(&data.0);
+        save_empty_list::<ComplexAttackConstructor>(&data.0);
+        save_empty_list::<NpcConstructor>(&data.0);
+        save_empty_list::<Level>(&data.0);
+        save_empty_list::<CharacterSettings>(&data.0);
+        save_empty_list::<ResourceConstructor>(&data.0);
+        let attack = AttackConstructor {
+            time_to_complete: 500,
+            ..AttackConstructor::new("attack".to_owned())
+        };
+        save(&data.0, 1, &attack);
+        save(&data.0, 1, &npc(1));
+        save(&data.0, 1, &level(vec![level_npc(1)]));
+        save(&data.0, 1, &character(vec![primary_ability(1)]));
+        data
+    }
+
+    fn find<'a>(report: &'a Report, file: &Path, text: &str) -> &'a Issue {
+        report
+            .issues
+            .iter()
+            .find(|issue| issue.file == file && issue.message.contains(text))
+            .unwrap_or_else(|| panic!("no issue with `{text}` in {report:?}"))
+    }
+
+    #[test]
+    fn valid_data_has_no_issues() {
+        let data = valid_data("valid");
+        let report = validate(&data.0);
+        assert!(report.issues.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn missing_attack_of_npc() {
+        let data = valid_data("missing-attack");
+        data.0.save(1, &npc(7)).unwrap();
+        let report = validate(&data.0);
+        let file = data.0.item_path::<NpcConstructor>(1);
+        let issue = find(&report, &file, "references missing attack 7");
+        assert_eq!(issue.severity, Severity::Error);
+    }
+
+    #[test]
+    fn missing_attack_of_character() {
+        let data = valid_data("missing-ability");
+        data.0
+            .save(1, &character(vec![primary_ability(7)]))
+            .unwrap();
+        let report = validate(&data.0);
+        let file = data.0.item_path::<CharacterSettings>(1);
+        let issue = find(&report, &file, "references missing attack 7");
+        assert_eq!(issue.severity, Severity::Error);
+    }
+
+    #[test]
+    fn empty_npc_list_of_level() {
+        let data = valid_data("empty-level");
+        data.0.save(1, &level(Vec::new())).unwrap();
+        let report = validate(&data.0);
+        let file = data.0.item_path::<Level>(1);
+        let issue = find(&report, &file, "npc_list should have at least one npc");
+        assert_eq!(issue.severity, Severity::Error);
+    }
+
+    #[test]
+    fn spawn_outside_bounds() {
+        let data = valid_data("spawn-outside");
+        let mut outside = level(vec![level_npc(1)]);
+        let mut spawn = outside.player_spawns[0];
+        spawn.x = -10.0;
+        outside.player_spawns.push(spawn);
+        outside.npc_list[0].position.y = -10.0;
+        data.0.save(1, &outside).unwrap();
+        let report = validate(&data.0);
+        let file = data.0.item_path::<Level>(1);
+        let issue = find(
+            &report,
+            &file,
+            "player_spawns.1 should be inside the bounds",
+        );
+        assert_eq!(issue.severity, Severity::Error);
+        let issue = find(
+            &report,
+            &file,
+            "npc_list.0.position should be inside the bounds",
+        );
+        assert_eq!(issue.severity, Severity::Error);
+    }
+
+    #[test]
+    fn no_primary_ability() {
+        let data = valid_data("no-primary");
+        data.0.save(1, &character(Vec::new())).unwrap();
+        let report = validate(&data.0);
+        let file = data.0.item_path::<CharacterSettings>(1);
+        let issue = find(&report, &file, "no ability in Primary slot");
+        assert_eq!(issue.severity, Severity::Warning);
+        assert!(!report.has_errors(), "{report:?}");
+    }
+}
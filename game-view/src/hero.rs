@@ -101,6 +101,27 @@ impl<'a> HeroView<'a> {
 
         frame.fill(&path, Color::from_rgb8(0, 255, 0));
     }
+    // placed under the large hp bar
+    pub fn draw_resource_bars(&self, frame: &mut Frame) {
+        let bar_width = 200.0;
+        let bar_height = 10.0;
+        for (index, pool) in self.hero_info.resources.iter().enumerate() {
+            let start = iced_core::Point::new(10.0, 35.0 + index as f32 * 15.0);
+
+            let path = Path::new(|b| {
+                let size = Size::new(bar_width, bar_height);
+                b.rectangle(start, size);
+            });
+            frame.fill(&path, Color::from_rgb8(128, 128, 128));
+
+            let path = Path::new(|b| {
+                let width = bar_width * pool.percent_left();
+                let size = Size::new(width, bar_height);
+                b.rectangle(start, size);
+            });
+            frame.fill(&path, Color::from_rgb8(0, 0, 255));
+        }
+    }
     fn draw_attack(&self, frame: &mut Frame) {
         if let Action::Attack(attack_info) = &self.hero_info.action {
             let attack_view = AttackView::new(attack_info);
@@ -0,0 +1,6 @@
+// canvas views of game-core objects, shared by first-fight
+// and the play-test of the editor
+pub mod attack;
+pub mod boss;
+pub mod hero;
+pub mod scene;
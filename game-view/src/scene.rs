@@ -0,0 +1,73 @@
+use iced_core::{Color, Point, Size};
+use iced_widget::canvas::{Frame, Path, Stroke, stroke};
+
+use game_core::scene::Scene;
+
+use shared::resource::PICKUP_SIZE;
+
+use crate::boss::BossView;
+use crate::hero::HeroView;
+
+pub const DRAW_LARGE_HP_BAR: bool = true;
+
+pub struct SceneView<'a> {
+    pub scene_info: &'a Scene,
+}
+
+impl<'a> SceneView<'a> {
+    pub fn new(scene_info: &'a Scene) -> Self {
+        Self { scene_info }
+    }
+    pub fn draw(&self, frame: &mut Frame, self_id: u128) {
+        let scene = &self.scene_info;
+        self.draw_level(frame);
+        for pickup in scene.pickups.iter() {
+            let path = Path::new(|b| {
+                let center = iced_core::Point::new(pickup.position.x, pickup.position.y);
+                b.circle(center, PICKUP_SIZE);
+            });
+            frame.fill(&path, Color::from_rgb8(0, 0, 255));
+        }
+        for boss in scene.npc.iter() {
+            let view = BossView::new(boss);
+            view.draw(frame);
+            if DRAW_LARGE_HP_BAR {
+                view.draw_hp_bar(frame);
+            } else {
+                view.draw_small_hp_bar(frame);
+            }
+        }
+        for hero in scene.characters.values() {
+            if hero.id == self_id {
+                continue;
+            }
+            let view = HeroView::new(hero);
+            view.draw(frame);
+            view.draw_small_hp_bar(frame);
+        }
+    }
+    fn draw_level(&self, frame: &mut Frame) {
+        let Some(level) = &self.scene_info.level else {
+            return;
+        };
+        let bounds = Path::rectangle(
+            Point::ORIGIN,
+            Size::new(level.bounds.width, level.bounds.height),
+        );
+        frame.stroke(
+            &bounds,
+            Stroke {
+                style: stroke::Style::Solid(Color::from_rgb8(120, 120, 120)),
+                width: 2.0,
+                ..Stroke::default()
+            },
+        );
+        for obstacle in level.obstacles.iter() {
+            let path = Path::rectangle(
+                Point::new(obstacle.position.x, obstacle.position.y),
+                Size::new(obstacle.width, obstacle.height),
+            );
+            frame.fill(&path, Color::from_rgb8(90, 90, 90));
+        }
+    }
+}
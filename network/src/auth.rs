@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+pub const LOGIN_PATH: &str = "/login";
+pub const REGISTER_PATH: &str = "/register";
+// query parameter of the /ws url with the token from LoginResponse
+pub const TOKEN_PARAM: &str = "token";
+
+// json body of /login and /register
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoginRequest {
+    pub name: String,
+    pub password: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoginResponse {
+    pub account_id: u32, // also the id of the hero
+    pub token: String,
+}
@@ -1,4 +1,6 @@
+use nalgebra::Point2;
 use serde::{Deserialize, Serialize};
+use shared::character::AbilitySlot;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Move {
@@ -16,11 +18,13 @@ pub enum KeyActionKind {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Message {
-    Join,
+    Join(u32), // character id
     Move(KeyActionKind, Move),
     HeroDash,
-    HeroAttack,
+    HeroAttack(Point2<f32>), // aim point
+    UseAbility(AbilitySlot, Point2<f32>),
     RequestFrameNumber,
+    RequestDefinitions,
 }
 
 impl Message {
@@ -4,9 +4,12 @@ use serde::{Deserialize, Serialize};
 use shared::action::Action;
 
 // Updates about Boss entities we send from
-// the server to clients. Do not include attacks
+// the server to clients. Do not include attacks,
+// clients find them in definitions by npc_id
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Boss {
+    pub npc_id: u32,
+    pub size: f32,
     pub position: Point2<f32>,
     pub action: Action,
     pub hp: i32,
@@ -4,12 +4,14 @@ use serde::{Deserialize, Serialize};
 use shared::action::Action;
 use shared::character::CharacterSettings;
 use shared::hero::Moving;
+use shared::resource::ResourcePool;
 
 // Updates about Hero we send from the server
 // to clients
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Hero {
     pub id: u128,
+    pub character_id: u32,
     pub hp: i32,
     pub max_hp: i32,
     pub position: Point2<f32>,
@@ -19,4 +21,5 @@ pub struct Hero {
     pub ranged_attack_distance: f32,
     pub action: Action,
     pub character_settings: CharacterSettings,
+    pub resources: Vec<ResourcePool>,
 }
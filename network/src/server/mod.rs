@@ -2,6 +2,9 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use shared::bundle::DataBundle;
+use shared::resource::Pickup;
+
 pub mod boss;
 pub mod hero;
 
@@ -25,6 +28,7 @@ pub struct Scene {
     pub frame_number: u128,
     pub characters: HashMap<u128, Hero>,
     pub npc: Vec<Boss>,
+    pub pickups: Vec<Pickup>,
 }
 
 // It's used to send server updates, no need for extra Box
@@ -44,6 +48,8 @@ pub enum Message {
     Test,
     SetId(u128),
     ResponseFrameNumber(u128),
+    DefinitionsHash(String), // clients request definitions if their copy is older
+    Definitions(Box<DataBundle>), // characters, npc, attacks and levels
     Update(Update),
 }
 
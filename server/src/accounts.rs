@@ -0,0 +1,249 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+const MIN_PASSWORD_LENGTH: usize = 8;
+const MAX_NAME_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Account {
+    pub id: u32,
+    pub name: String,
+    pub password_hash: String, // argon2 with a random salt, PHC string
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct AccountsFile {
+    next_id: u32,
+    accounts: Vec<Account>,
+}
+
+// Accounts stored in a json file next to the server,
+// the file is written again after every registration
+pub struct Accounts {
+    path: PathBuf,
+    file: Mutex<AccountsFile>,
+    // checked for unknown names, so they take as long as known ones
+    dummy_hash: String,
+}
+
+#[derive(Debug)]
+pub enum AccountError {
+    InvalidName,
+    ShortPassword,
+    NameTaken,
+    WrongCredentials,
+    Storage(String),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::InvalidName => write!(
+                f,
+                "name should be 1 to {MAX_NAME_LENGTH} letters, digits, '-' or '_'"
+            ),
+            AccountError::ShortPassword => write!(
+                f,
+                "password should be at least {MIN_PASSWORD_LENGTH} characters long"
+            ),
+            AccountError::NameTaken => write!(f, "name is already taken"),
+            AccountError::WrongCredentials => write!(f, "wrong name or password"),
+            AccountError::Storage(e) => write!(f, "can't store accounts: {e}"),
+        }
+    }
+}
+
+impl AccountError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AccountError::InvalidName | AccountError::ShortPassword => StatusCode::BAD_REQUEST,
+            AccountError::NameTaken => StatusCode::CONFLICT,
+            AccountError::WrongCredentials => StatusCode::UNAUTHORIZED,
+            AccountError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+fn check_name(name: &str) -> Result<(), AccountError> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AccountError::InvalidName)
+    }
+}
+
+fn hash_password(password: &str) -> Result<String, AccountError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AccountError::Storage(e.to_string()))
+}
+
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    let Ok(hash) = PasswordHash::new(password_hash) else {
+        return false;
+    };
+    Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_ok()
+}
+
+fn read_file(path: &Path) -> Result<AccountsFile, String> {
+    if !path.exists() {
+        return Ok(AccountsFile {
+            next_id: 1,
+            accounts: Vec::new(),
+        });
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    serde_json::from_str(&data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// written to a temporary file first, a failed write
+// leaves the old file as it was
+fn write_file(path: &Path, file: &AccountsFile) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    std::fs::write(&temp_path, data).map_err(|e| format!("{}: {e}", temp_path.display()))?;
+    std::fs::rename(&temp_path, path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+impl Accounts {
+    // an empty list if the file doesn't exist yet
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let file = read_file(&path)?;
+        let dummy_hash = hash_password("dummy password").map_err(|e| e.to_string())?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+            dummy_hash,
+        })
+    }
+    pub fn register(&self, name: &str, password: &str) -> Result<u32, AccountError> {
+        check_name(name)?;
+        if password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(AccountError::ShortPassword);
+        }
+        // hashing is slow, the lock is taken after it
+        let password_hash = hash_password(password)?;
+        let mut file = self.file.lock().expect("Accounts lock is poisoned");
+        if file.accounts.iter().any(|account| account.name == name) {
+            return Err(AccountError::NameTaken);
+        }
+        let id = file.next_id;
+        file.next_id += 1;
+        file.accounts.push(Account {
+            id,
+            name: name.to_owned(),
+            password_hash,
+        });
+        if let Err(e) = write_file(&self.path, &file) {
+            file.accounts.pop();
+            file.next_id -= 1;
+            return Err(AccountError::Storage(e));
+        }
+        Ok(id)
+    }
+    pub fn login(&self, name: &str, password: &str) -> Result<u32, AccountError> {
+        let account = {
+            let file = self.file.lock().expect("Accounts lock is poisoned");
+            file.accounts
+                .iter()
+                .find(|account| account.name == name)
+                .cloned()
+        };
+        match account {
+            Some(account) if verify_password(password, &account.password_hash) => Ok(account.id),
+            Some(_) => Err(AccountError::WrongCredentials),
+            None => {
+                verify_password(password, &self.dummy_hash);
+                Err(AccountError::WrongCredentials)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a fresh file in the temp folder, removed on drop
+    struct TempPath(PathBuf);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let file_name = format!("accounts-{name}-{}.json", std::process::id());
+            let path = std::env::temp_dir().join(file_name);
+            let _ = std::fs::remove_file(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn register_and_login() {
+        let path = TempPath::new("login");
+        let accounts = Accounts::open(path.0.clone()).unwrap();
+        let id = accounts.register("hero", "password123").unwrap();
+        assert_eq!(accounts.login("hero", "password123").unwrap(), id);
+    }
+
+    #[test]
+    fn reject_wrong_password_and_unknown_name() {
+        let path = TempPath::new("wrong");
+        let accounts = Accounts::open(path.0.clone()).unwrap();
+        accounts.register("hero", "password123").unwrap();
+        let result = accounts.login("hero", "password124");
+        assert!(matches!(result, Err(AccountError::WrongCredentials)));
+        let result = accounts.login("villain", "password123");
+        assert!(matches!(result, Err(AccountError::WrongCredentials)));
+    }
+
+    #[test]
+    fn reject_invalid_registration() {
+        let path = TempPath::new("invalid");
+        let accounts = Accounts::open(path.0.clone()).unwrap();
+        accounts.register("hero", "password123").unwrap();
+        let result = accounts.register("hero", "password456");
+        assert!(matches!(result, Err(AccountError::NameTaken)));
+        let result = accounts.register("another", "short");
+        assert!(matches!(result, Err(AccountError::ShortPassword)));
+        let result = accounts.register("with space", "password123");
+        assert!(matches!(result, Err(AccountError::InvalidName)));
+    }
+
+    #[test]
+    fn keep_ids_after_reopen() {
+        let path = TempPath::new("reopen");
+        let accounts = Accounts::open(path.0.clone()).unwrap();
+        let first = accounts.register("first", "password123").unwrap();
+        let second = accounts.register("second", "password123").unwrap();
+        drop(accounts);
+
+        let accounts = Accounts::open(path.0.clone()).unwrap();
+        assert_eq!(accounts.login("first", "password123").unwrap(), first);
+        assert_eq!(accounts.login("second", "password123").unwrap(), second);
+        let third = accounts.register("third", "password123").unwrap();
+        assert!(third > second);
+    }
+}
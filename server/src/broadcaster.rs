@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use axum::extract::ws::{Message as WsMessage, WebSocket};
+use axum::extract::ws::{CloseFrame, Message as WsMessage, WebSocket};
 use futures::sink::SinkExt;
 use futures_util::stream::SplitSink;
 use tokio::sync::mpsc::Receiver;
@@ -10,7 +10,7 @@ type Writer = SplitSink<WebSocket, WsMessage>;
 pub enum Message {
     AddWriter(u128, Writer),
     RemoveWriter(u128),
-    CloseConnection(u128),
+    CloseConnection(u128, Option<CloseFrame>),
     SendMessage(u128, WsMessage),
     SendMessageList(Vec<u128>, WsMessage),
     SendMessageToAll(WsMessage),
@@ -39,7 +39,7 @@ async fn handle_message(writers: &mut HashMap<u128, Writer>, message: Message) {
                 tracing::warn!("Writer not found, can't remove in broadcaster!");
             }
         }
-        CloseConnection(id) => close_writer(id, writers).await,
+        CloseConnection(id, frame) => close_writer(id, frame, writers).await,
         SendMessage(id, message) => {
             send_message(id, writers, message).await;
         }
@@ -69,12 +69,12 @@ async fn send_message(id: u128, writers: &mut HashMap<u128, Writer>, message: Ws
     }
 }
 
-async fn close_writer(id: u128, writers: &mut HashMap<u128, Writer>) {
+async fn close_writer(id: u128, frame: Option<CloseFrame>, writers: &mut HashMap<u128, Writer>) {
     let Some(mut writer) = writers.remove(&id) else {
         tracing::warn!("Writer not found, can't close in broadcaster!");
         return;
     };
-    let message = WsMessage::Close(None);
+    let message = WsMessage::Close(frame);
     if let Err(e) = writer.send(message).await {
         tracing::error!("Failed to send WebSocket message to id {id}: {e}");
     }
@@ -1,13 +1,66 @@
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
+const CONFIG_PATH_ARG: &str = "--config";
+const DEFAULT_CONFIG_PATH: &str = "./config.toml";
+
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     pub port: u32,
+    pub data_path: Option<PathBuf>,
+    pub web_client_path: PathBuf,
+    pub watch_data: bool,            // reload game data when files change
+    pub admin_token: Option<String>, // enables POST /admin/reload
+    pub resume_timeout: u64,         // seconds a disconnected hero waits for its client
+    pub accounts_path: PathBuf,
+    pub session_secret: Option<String>, // signs login tokens, random if not set
+    pub token_lifetime: u64,            // seconds a login token is valid
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: 8080,
+            data_path: None,
+            web_client_path: PathBuf::from("../client-web/dist"),
+            watch_data: true,
+            admin_token: None,
+            resume_timeout: 30,
+            accounts_path: PathBuf::from("./accounts.json"),
+            session_secret: None,
+            token_lifetime: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+fn config_path_arg() -> Option<PathBuf> {
+    let mut args = std::env::args().skip_while(|arg| arg != CONFIG_PATH_ARG);
+    args.next()?;
+    args.next().map(PathBuf::from)
 }
 
 impl Config {
-    pub fn read_from_file() -> Self {
-        let data = std::fs::read_to_string("./config.toml").expect("Should read server Config");
-        toml::from_str(&data).expect("Should decode server Config")
+    // `--config <path>`, or `./config.toml` if it exists, otherwise defaults.
+    // Relative paths inside the file are relative to the file itself
+    pub fn read_from_file() -> Result<Self, String> {
+        let (file_path, required) = match config_path_arg() {
+            Some(file_path) => (file_path, true),
+            None => (PathBuf::from(DEFAULT_CONFIG_PATH), false),
+        };
+        if !required && !file_path.exists() {
+            println!("No config.toml found, using default config");
+            return Ok(Self::default());
+        }
+        let data = std::fs::read_to_string(&file_path)
+            .map_err(|e| format!("{}: {e}", file_path.display()))?;
+        let mut config: Config =
+            toml::from_str(&data).map_err(|e| format!("{}: {e}", file_path.display()))?;
+        let base = file_path.parent().unwrap_or(Path::new("."));
+        config.data_path = config.data_path.map(|path| base.join(path));
+        config.web_client_path = base.join(&config.web_client_path);
+        config.accounts_path = base.join(&config.accounts_path);
+        Ok(config)
     }
 }
@@ -1,20 +1,19 @@
 use std::time::Duration;
 
 use axum::body::Bytes;
-use axum::extract::ws::Message as WsMessage;
+use axum::extract::ws::{CloseFrame, Message as WsMessage, close_code};
 use tokio::sync::mpsc;
 use tokio::time;
 
-use network::client;
+use network::client::{self, KeyActionKind, Move};
 use network::server;
 
 use crate::broadcaster;
+use crate::sessions::Sessions;
 use crate::stage::Stage;
-use crate::types::{GameLoopReceiver, LoopMessage};
-
-pub async fn game_loop(mut receiver: GameLoopReceiver) {
-    let mut stage = Stage::new();
+use crate::types::{GameLoopReceiver, LoopMessage, ReloadResult};
 
+pub async fn game_loop(mut stage: Stage, mut sessions: Sessions, mut receiver: GameLoopReceiver) {
     let (broadcaster_sender, broadcaster_receiver) = mpsc::channel(100);
 
     tokio::spawn(broadcaster::start(broadcaster_receiver));
@@ -31,18 +30,36 @@ pub async fn game_loop(mut receiver: GameLoopReceiver) {
                                 tracing::error!("Failed to send message to broadcaster in game loop: {e}");
                             }
                         }
-                        LoopMessage::Client(id, message) => {
-                            handle_client_message(&mut stage, id, *message, &broadcaster_sender).await;
+                        LoopMessage::Client(id, connection, message) => {
+                            // a replaced socket may still be sending
+                            if sessions.is_current(id, connection) {
+                                handle_client_message(&mut stage, id, *message, &broadcaster_sender).await;
+                            }
                         }
-                        LoopMessage::Join(id) => {
-                            handle_character_join(&mut stage, id, &broadcaster_sender).await;
+                        LoopMessage::Open(id, reply) => {
+                            if sessions.is_connected(id) {
+                                replace_connection(&mut stage, id, &broadcaster_sender).await;
+                            }
+                            let _ = reply.send(sessions.open(id));
                         }
-                        LoopMessage::Leave(id) => {
-                            let message = broadcaster::Message::CloseConnection(id);
-                            if let Err(e) = broadcaster_sender.send(message).await {
-                                tracing::error!("Failed to send message to broadcaster in game loop: {e}");
+                        LoopMessage::Connect(id) => {
+                            stage.scene.set_character_connected(id, true);
+                            send_definitions_hash(&stage, id, &broadcaster_sender).await;
+                        }
+                        LoopMessage::Leave(id, connection) => {
+                            if sessions.disconnect(id, connection) {
+                                let message = broadcaster::Message::CloseConnection(id, None);
+                                if let Err(e) = broadcaster_sender.send(message).await {
+                                    tracing::error!("Failed to send message to broadcaster in game loop: {e}");
+                                }
+                                handle_character_disconnect(&mut stage, id);
+                            }
+                        }
+                        LoopMessage::ReloadData(reply) => {
+                            let result = reload_data(&mut stage, &broadcaster_sender).await;
+                            if let Some(reply) = reply {
+                                let _ = reply.send(result);
                             }
-                            handle_character_leave(&mut stage, id, &broadcaster_sender).await;
                         }
                     }
                 } else {
@@ -51,6 +68,9 @@ pub async fn game_loop(mut receiver: GameLoopReceiver) {
             }
             _ = &mut tick => {
                 tick = Box::pin(timer());
+                for id in sessions.expired() {
+                    handle_character_leave(&mut stage, id, &broadcaster_sender).await;
+                }
                 if stage.update() {
                     send_scene_to_clients(&stage, &broadcaster_sender).await;
                 }
@@ -81,27 +101,101 @@ async fn handle_client_message(
     broadcaster: &mpsc::Sender<broadcaster::Message>,
 ) {
     // println!("Handle message in game loop for {id}");
-    if let client::Message::RequestFrameNumber = message {
-        let server_message = server::Message::ResponseFrameNumber(stage.scene.frame_number);
-        let data = server_message.to_vec();
-        let ws_message = WsMessage::Binary(Bytes::from(data));
-        let new_message = broadcaster::Message::SendMessage(id, ws_message);
-        if let Err(e) = broadcaster.send(new_message).await {
-            println!("Failed to send Scene to broadcaster in game loop: {e}");
+    match message {
+        client::Message::RequestFrameNumber => {
+            let server_message = server::Message::ResponseFrameNumber(stage.scene.frame_number);
+            let data = server_message.to_vec();
+            let ws_message = WsMessage::Binary(Bytes::from(data));
+            let new_message = broadcaster::Message::SendMessage(id, ws_message);
+            if let Err(e) = broadcaster.send(new_message).await {
+                println!("Failed to send Scene to broadcaster in game loop: {e}");
+            }
         }
-    } else {
-        stage.scene.handle_client_message(id, message);
-        send_scene_to_clients(stage, broadcaster).await;
+        client::Message::Join(character_id) => {
+            handle_character_join(stage, id, character_id, broadcaster).await;
+        }
+        client::Message::RequestDefinitions => {
+            let definitions = Box::new(stage.definitions.clone());
+            let data = server::Message::Definitions(definitions).to_vec();
+            let ws_message = WsMessage::Binary(Bytes::from(data));
+            let new_message = broadcaster::Message::SendMessage(id, ws_message);
+            if let Err(e) = broadcaster.send(new_message).await {
+                println!("Failed to send Definitions to broadcaster in game loop: {e}");
+            }
+        }
+        message => {
+            stage.scene.handle_client_message(id, message);
+            send_scene_to_clients(stage, broadcaster).await;
+        }
+    }
+}
+
+// clients with the same hash use their cached definitions,
+// others send RequestDefinitions
+async fn send_definitions_hash(
+    stage: &Stage,
+    id: u128,
+    broadcaster: &mpsc::Sender<broadcaster::Message>,
+) {
+    println!("Got LoopMessage::Connect");
+    let server_message = server::Message::DefinitionsHash(stage.definitions.hash.clone());
+    let data = server_message.to_vec();
+    let ws_message = WsMessage::Binary(Bytes::from(data));
+    let new_message = broadcaster::Message::SendMessage(id, ws_message);
+    if let Err(e) = broadcaster.send(new_message).await {
+        println!("Failed to send DefinitionsHash to broadcaster in game loop: {e}");
     }
 }
 
+async fn reload_data(
+    stage: &mut Stage,
+    broadcaster: &mpsc::Sender<broadcaster::Message>,
+) -> ReloadResult {
+    println!("Reloading game data");
+    if let Err(errors) = stage.reload_data() {
+        tracing::error!("Game data has errors, keeping the old version");
+        for error in errors.iter() {
+            tracing::error!("{error}");
+        }
+        return Err(errors);
+    }
+    // clients request the new definitions after they see the new hash
+    let server_message = server::Message::DefinitionsHash(stage.definitions.hash.clone());
+    let data = server_message.to_vec();
+    let ws_message = WsMessage::Binary(Bytes::from(data));
+    let new_message = broadcaster::Message::SendMessageToAll(ws_message);
+    if let Err(e) = broadcaster.send(new_message).await {
+        println!("Failed to send DefinitionsHash to broadcaster in game loop: {e}");
+    }
+    send_scene_to_clients(stage, broadcaster).await;
+    println!("Game data reloaded");
+    Ok(())
+}
+
 async fn handle_character_join(
     stage: &mut Stage,
     id: u128,
+    character_id: u32,
     broadcaster: &mpsc::Sender<broadcaster::Message>,
 ) {
-    println!("Got LoopMessage::Join");
-    stage.scene.add_character(id);
+    println!("Got client::Message::Join");
+    // a resumed hero is kept as it is, and a living hero can't
+    // switch class, it would come back with full hp and resources
+    if let Some(hero) = stage.scene.characters.get(&id)
+        && (hero.character_id == character_id || !hero.defeated())
+    {
+        if hero.character_id != character_id {
+            tracing::warn!("Client {id} tried to switch class to {character_id} while alive");
+        }
+        send_scene_to_clients(stage, broadcaster).await;
+        return;
+    }
+    let Some(character_class) = stage.find_character_class(character_id) else {
+        tracing::error!("Client {id} tried to join with unknown character {character_id}");
+        return;
+    };
+    let definition = character_class.clone();
+    stage.scene.add_character(id, &definition);
     let scene = stage.scene.to_network();
     let server_message = server::Message::Update(server::Update::Scene(scene));
     let data = server_message.to_vec();
@@ -112,12 +206,43 @@ async fn handle_character_join(
     }
 }
 
+// the hero stays on the scene until the client comes back
+// or the session expires, with movement keys released
+// and out of reach of npc
+fn handle_character_disconnect(stage: &mut Stage, id: u128) {
+    println!("Client {id} disconnected, keeping the hero for the grace period");
+    stage.scene.set_character_connected(id, false);
+    for movement in [Move::Up, Move::Down, Move::Left, Move::Right] {
+        let message = client::Message::Move(KeyActionKind::Released, movement);
+        stage.scene.handle_client_message(id, message);
+    }
+}
+
+// the newest login wins, the old socket may be half-open
+// and would keep the player out for a long time otherwise
+async fn replace_connection(
+    stage: &mut Stage,
+    id: u128,
+    broadcaster: &mpsc::Sender<broadcaster::Message>,
+) {
+    println!("Account {id} connected again, closing the old socket");
+    let frame = CloseFrame {
+        code: close_code::POLICY,
+        reason: "logged in from another place".into(),
+    };
+    let message = broadcaster::Message::CloseConnection(id, Some(frame));
+    if let Err(e) = broadcaster.send(message).await {
+        tracing::error!("Failed to send message to broadcaster in game loop: {e}");
+    }
+    handle_character_disconnect(stage, id);
+}
+
 async fn handle_character_leave(
     stage: &mut Stage,
     id: u128,
     broadcaster: &mpsc::Sender<broadcaster::Message>,
 ) {
-    println!("Got LoopMessage::Leave");
+    println!("Session of {id} expired");
     stage.scene.remove_character(id);
     let scene = stage.scene.to_network();
     let server_message = server::Message::Update(server::Update::Scene(scene));
@@ -1,30 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::Duration;
 
 use axum::body::Bytes;
-use axum::extract::State;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
-use axum::response::IntoResponse;
+use axum::extract::{Json, Query, State};
+use axum::response::{IntoResponse, Response};
 use axum::{Router, routing};
 use futures::sink::SinkExt;
 use futures::stream::StreamExt;
-use http::HeaderValue;
-use tokio::sync::mpsc;
+use http::{HeaderMap, HeaderValue, StatusCode, header};
+use tokio::sync::{mpsc, oneshot};
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
-// use tower_http::validate_request::ValidateRequestHeaderLayer;
-use uuid::Uuid;
 
+use game_data::{GameData, Severity};
+use network::auth::{self, LoginRequest, LoginResponse};
 use network::client;
 use network::server;
 
+mod accounts;
 mod broadcaster;
 mod config;
 mod game_loop;
 mod npc;
+mod sessions;
 mod stage;
+mod tokens;
 mod types;
+mod watcher;
 
+use accounts::{AccountError, Accounts};
+use sessions::Sessions;
+use stage::Stage;
+use tokens::Tokens;
 use types::{GameLoopSender, LoopMessage};
 
 const MAX_CLIENTS: u16 = 30;
@@ -32,28 +42,99 @@ const MAX_CLIENTS: u16 = 30;
 struct AppState {
     game_loop_sender: GameLoopSender,
     client_counter: Arc<AtomicU16>,
+    admin_token: Option<String>,
+    accounts: Accounts,
+    tokens: Tokens,
 }
 
 #[tokio::main]
 async fn main() {
     tracing_subscriber::fmt::init();
 
-    let config = config::Config::read_from_file();
+    let config = match config::Config::read_from_file() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("Failed to read server config: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let data_path = game_data::data_path_arg().or(config.data_path.clone());
+    let game_data = match GameData::resolve(data_path) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            tracing::error!("Failed to open game data: {e}");
+            std::process::exit(1);
+        }
+    };
+    println!("loading game data from {}", game_data.root().display());
+    let report = game_data::validate(&game_data);
+    for issue in report.issues.iter() {
+        match issue.severity {
+            Severity::Error => tracing::error!("{issue}"),
+            Severity::Warning => tracing::warn!("{issue}"),
+        }
+    }
+    if report.has_errors() {
+        tracing::error!(
+            "Game data has {} errors, fix them before starting the server",
+            report.error_count()
+        );
+        std::process::exit(1);
+    }
+    let data_root = game_data.root().to_owned();
+    let stage = match Stage::new(game_data) {
+        Ok(stage) => stage,
+        Err(e) => {
+            tracing::error!("Failed to load game data: {e}");
+            std::process::exit(1);
+        }
+    };
 
     let (game_loop_sender, receiver) = mpsc::channel(1000);
 
-    tokio::spawn(game_loop::game_loop(receiver));
+    let sessions = Sessions::new(Duration::from_secs(config.resume_timeout));
+    tokio::spawn(game_loop::game_loop(stage, sessions, receiver));
+    if config.watch_data {
+        tokio::spawn(watcher::watch_data(data_root, game_loop_sender.clone()));
+    }
+
+    let accounts = match Accounts::open(config.accounts_path.clone()) {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            tracing::error!("Failed to read accounts: {e}");
+            std::process::exit(1);
+        }
+    };
+    if let Some(secret) = &config.session_secret
+        && secret.len() < tokens::MIN_SECRET_LENGTH
+    {
+        tracing::error!(
+            "session_secret should be at least {} bytes long",
+            tokens::MIN_SECRET_LENGTH
+        );
+        std::process::exit(1);
+    }
+    let tokens = Tokens::new(
+        config.session_secret.as_deref(),
+        Duration::from_secs(config.token_lifetime),
+    );
 
     let client_counter = Arc::new(AtomicU16::new(0));
 
     let state = AppState {
         game_loop_sender,
         client_counter,
+        admin_token: config.admin_token.clone(),
+        accounts,
+        tokens,
     };
 
     let app = Router::new()
-        .nest_service("/game", serve_web_client())
-        .route("/login", routing::post(login_user))
+        .nest_service("/game", ServeDir::new(&config.web_client_path))
+        .route(auth::REGISTER_PATH, routing::post(register_user))
+        .route(auth::LOGIN_PATH, routing::post(login_user))
+        .route("/admin/reload", routing::post(reload_data))
         .route("/ws", routing::get(ws_handler))
         .route("/", routing::get(|| async { "hello from axum\n" }))
         .layer(CorsLayer::new().allow_origin("*".parse::<HeaderValue>().unwrap()))
@@ -64,22 +145,106 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
-fn serve_web_client() -> ServeDir {
-    ServeDir::new("../client-web/dist")
+fn login_response(state: &AppState, result: Result<u32, AccountError>) -> Response {
+    match result {
+        Ok(account_id) => {
+            let token = state.tokens.issue(account_id);
+            Json(LoginResponse { account_id, token }).into_response()
+        }
+        Err(e) => (e.status(), format!("{e}\n")).into_response(),
+    }
+}
+
+async fn register_user(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Response {
+    // password hashing takes a while
+    let task_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        task_state
+            .accounts
+            .register(&request.name, &request.password)
+    })
+    .await;
+    match result {
+        Ok(result) => login_response(&state, result),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}\n")).into_response(),
+    }
+}
+
+async fn login_user(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Response {
+    let task_state = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        task_state.accounts.login(&request.name, &request.password)
+    })
+    .await;
+    match result {
+        Ok(result) => login_response(&state, result),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{e}\n")).into_response(),
+    }
 }
 
-async fn login_user() -> String {
-    "logged in".to_owned()
+async fn reload_data(headers: HeaderMap, State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let Some(admin_token) = &state.admin_token else {
+        return (StatusCode::NOT_FOUND, "admin_token is not set\n".to_owned());
+    };
+    let expected = format!("Bearer {admin_token}");
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .is_some_and(|value| value.as_bytes() == expected.as_bytes());
+    if !authorized {
+        return (StatusCode::UNAUTHORIZED, "unauthorized\n".to_owned());
+    }
+    let (reply, result) = oneshot::channel();
+    let message = LoopMessage::ReloadData(Some(reply));
+    if let Err(e) = state.game_loop_sender.send(message).await {
+        tracing::error!("Failed to send ReloadData to game loop: {e}");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "game loop is down\n".to_owned(),
+        );
+    }
+    match result.await {
+        Ok(Ok(())) => (StatusCode::OK, "reloaded\n".to_owned()),
+        Ok(Err(errors)) => {
+            let mut body = "game data has errors, keeping the old version\n".to_owned();
+            for error in errors {
+                body.push_str(&error);
+                body.push('\n');
+            }
+            (StatusCode::UNPROCESSABLE_ENTITY, body)
+        }
+        Err(_) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "no reply from game loop\n".to_owned(),
+        ),
+    }
 }
 
-async fn ws_handler(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let id = Uuid::new_v4().as_u128();
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    // browsers can't set headers for WebSocket, the token is in the url
+    let account_id = params
+        .get(auth::TOKEN_PARAM)
+        .and_then(|token| state.tokens.verify(token));
+    let Some(account_id) = account_id else {
+        return (StatusCode::UNAUTHORIZED, "unauthorized\n").into_response();
+    };
+    let id = account_id as u128;
     println!("Got socket {id}");
     // share some data between sockets here
     // by passing it to the closure
     let sender = state.game_loop_sender.clone();
     let client_counter = state.client_counter.clone();
     ws.on_upgrade(move |socket| handle_socket(id, socket, sender, client_counter))
+        .into_response()
 }
 
 async fn handle_socket(
@@ -93,6 +258,21 @@ async fn handle_socket(
         let _ = client_counter.fetch_sub(1, Ordering::SeqCst);
         return;
     }
+    let (reply, result) = oneshot::channel();
+    if let Err(e) = sender.send(LoopMessage::Open(id, reply)).await {
+        tracing::error!("Failed to send LoopMessage::Open: {e}");
+        let _ = client_counter.fetch_sub(1, Ordering::SeqCst);
+        return;
+    }
+    let connection = match result.await {
+        Ok(connection) => connection,
+        Err(_) => {
+            tracing::error!("No reply from game loop to LoopMessage::Open");
+            let _ = client_counter.fetch_sub(1, Ordering::SeqCst);
+            return;
+        }
+    };
+
     let (mut write, mut read) = socket.split();
 
     let data = server::Message::SetId(id).to_vec();
@@ -109,7 +289,7 @@ async fn handle_socket(
         return;
     }
 
-    if let Err(e) = sender.send(LoopMessage::Join(id)).await {
+    if let Err(e) = sender.send(LoopMessage::Connect(id)).await {
         tracing::error!("Failed to send LoopMessage::LocalMessage: {e}");
     }
 
@@ -122,7 +302,7 @@ async fn handle_socket(
                     let message = client::Message::from_slice(&data);
                     // println!("Got binary message: {message:?}");
                     let result = sender
-                        .send(LoopMessage::Client(id, Box::new(message)))
+                        .send(LoopMessage::Client(id, connection, Box::new(message)))
                         .await;
                     if let Err(e) = result {
                         println!("Error sending message: {e:?}");
@@ -135,7 +315,7 @@ async fn handle_socket(
         }
     }
 
-    if let Err(e) = sender.send(LoopMessage::Leave(id)).await {
+    if let Err(e) = sender.send(LoopMessage::Leave(id, connection)).await {
         tracing::error!("Failed to send WebSocket message to broadcaster: {e}");
     }
 
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// Connected accounts, ids are account ids. After a disconnect the hero
+// stays on the scene for the grace period, a client logged in with the
+// same account coming back in that time gets the same hero back.
+// Every socket gets a connection number, a newer socket of the account
+// replaces the older one, and messages of the older one are ignored
+pub struct Sessions {
+    grace_period: Duration,
+    next_connection: u64,
+    connected: HashMap<u128, u64>, // id and its current connection
+    disconnected: HashMap<u128, Instant>, // id and time of the disconnect
+}
+
+impl Sessions {
+    pub fn new(grace_period: Duration) -> Self {
+        Self {
+            grace_period,
+            next_connection: 1,
+            connected: HashMap::new(),
+            disconnected: HashMap::new(),
+        }
+    }
+    pub fn is_connected(&self, id: u128) -> bool {
+        self.connected.contains_key(&id)
+    }
+    pub fn is_current(&self, id: u128, connection: u64) -> bool {
+        self.connected.get(&id) == Some(&connection)
+    }
+    // number of the new connection, it replaces the old one if any
+    pub fn open(&mut self, id: u128) -> u64 {
+        if self.disconnected.remove(&id).is_some() {
+            println!("Resuming session for {id}");
+        }
+        let connection = self.next_connection;
+        self.next_connection += 1;
+        self.connected.insert(id, connection);
+        connection
+    }
+    // false for a connection which was already replaced
+    pub fn disconnect(&mut self, id: u128, connection: u64) -> bool {
+        if !self.is_current(id, connection) {
+            return false;
+        }
+        self.connected.remove(&id);
+        self.disconnected.insert(id, Instant::now());
+        true
+    }
+    // ids of clients which didn't come back in time
+    pub fn expired(&mut self) -> Vec<u128> {
+        let grace_period = self.grace_period;
+        let expired: Vec<u128> = self
+            .disconnected
+            .iter()
+            .filter(|(_, since)| since.elapsed() >= grace_period)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in expired.iter() {
+            self.disconnected.remove(id);
+        }
+        expired
+    }
+}
@@ -1,70 +1,167 @@
 use std::collections::HashMap;
 use std::time::Instant;
 
-use nalgebra::Point2;
-
 use game_core::boss::Boss;
 use game_core::hero::Hero;
 use game_core::scene::{self, Scene};
-use shared::level::{Level, LevelList};
+use game_data::{GameData, Severity};
+use shared::bundle::DataBundle;
+use shared::character::CharacterDefinition;
+use shared::level::Level;
 use shared::npc::NpcConstructor;
 
+use crate::types::ReloadResult;
+
 pub struct Stage {
     last_update: Instant,
     pub scene: Scene,
-}
-
-fn load_npc_by_id(id: u32) -> NpcConstructor {
-    let file_path = format!("../data/npc/npc_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read NpcConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode NpcConstructor")
-}
-
-fn load_level_list() -> LevelList {
-    let file_path = "../data/level/list.json";
-    let contents = std::fs::read(file_path).expect("Should read LevelList from a file");
-    serde_json::from_slice(&contents).expect("Should decode LevelList")
-}
-
-fn load_level_by_id(id: u32) -> Level {
-    let file_path = format!("../data/level/level_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read Level from a file");
-    serde_json::from_slice(&contents).expect("Should decode Level")
+    pub character_classes: Vec<CharacterDefinition>,
+    pub definitions: DataBundle, // clients request it when their copy is older
+    game_data: GameData,
+    // npc from the reloaded level, waiting for the npc
+    // at the same index to finish its fight
+    pending_npc: Vec<Option<Boss>>,
 }
 
 impl Stage {
-    pub fn new() -> Self {
-        let _level_list = load_level_list();
-        let boss_constructor = load_npc_by_id(1);
-        let boss = Boss::from_constructor(Point2::new(512.0, 384.0), boss_constructor);
+    pub fn new(game_data: GameData) -> game_data::Result<Self> {
+        let (level, npc) = load_level(&game_data)?;
         let scene = Scene {
             frame_number: 0,
             mode: scene::Mode::Server,
             characters: HashMap::new(),
-            npc: vec![boss],
+            npc,
             effects: Vec::new(),
             projectiles: Vec::new(),
+            pickups: Vec::new(),
+            resource_drops: game_data.resource_drops()?,
+            level: Some(level),
         };
-        Stage {
+        Ok(Stage {
             last_update: Instant::now(),
             scene,
+            character_classes: game_data.character_definitions()?,
+            definitions: game_data::pack(&game_data)?,
+            game_data,
+            pending_npc: Vec::new(),
+        })
+    }
+    // reads definitions from disk again, if the new data has errors
+    // the old definitions stay in use
+    pub fn reload_data(&mut self) -> ReloadResult {
+        self.game_data.refresh().map_err(|e| vec![e.to_string()])?;
+        let report = game_data::validate(&self.game_data);
+        if report.has_errors() {
+            let errors = report
+                .issues
+                .iter()
+                .filter(|issue| issue.severity == Severity::Error)
+                .map(|issue| issue.to_string())
+                .collect();
+            return Err(errors);
         }
+        self.apply_data().map_err(|e| vec![e.to_string()])
     }
-    fn load_level(&mut self, id: u32) {
-        let level = load_level_by_id(id);
-        let npc = &level.npc_list[0];
-        let constructor = load_npc_by_id(npc.id);
-        let boss = Boss::from_constructor(Point2::new(512.0, 384.0), constructor);
-        self.scene.npc = vec![boss];
+    fn apply_data(&mut self) -> game_data::Result<()> {
+        let character_classes = self.game_data.character_definitions()?;
+        let resource_drops = self.game_data.resource_drops()?;
+        let definitions = game_data::pack(&self.game_data)?;
+        let (level, spawned) = load_level(&self.game_data)?;
+        // npc which are not in a fight are placed again with the new spawn
+        // settings, the others keep fighting with the new constructor
+        // and are replaced after it
+        let mut kept = Vec::new();
+        for (boss, spawned) in self.scene.npc.iter().zip(spawned.iter()) {
+            if boss.npc_id != spawned.npc_id || !boss.in_fight() {
+                kept.push(None);
+                continue;
+            }
+            let constructor: NpcConstructor = self.game_data.load(boss.npc_id)?;
+            let attacks = self.game_data.npc_attacks(&constructor)?;
+            kept.push(Some((constructor, attacks)));
+        }
+        // everything is loaded, nothing can fail from here
+        let old_npc = std::mem::take(&mut self.scene.npc);
+        let mut old_npc = old_npc.into_iter().zip(kept);
+        self.pending_npc.clear();
+        for boss in spawned {
+            match old_npc.next() {
+                Some((mut old, Some((constructor, attacks)))) => {
+                    old.apply_constructor(constructor, attacks);
+                    self.scene.npc.push(old);
+                    self.pending_npc.push(Some(boss));
+                }
+                _ => {
+                    self.scene.npc.push(boss);
+                    self.pending_npc.push(None);
+                }
+            }
+        }
+        self.scene.level = Some(level);
+        for hero in self.scene.characters.values_mut() {
+            let definition = character_classes
+                .iter()
+                .find(|item| item.id == hero.character_id);
+            if let Some(definition) = definition {
+                hero.apply_definition(definition);
+            }
+        }
+        self.scene.resource_drops = resource_drops;
+        self.character_classes = character_classes;
+        self.definitions = definitions;
+        Ok(())
+    }
+    // a pending npc takes the place of the old one once it's out of the fight
+    fn replace_pending_npc(&mut self) -> bool {
+        let mut replaced = false;
+        for (boss, pending) in self.scene.npc.iter_mut().zip(self.pending_npc.iter_mut()) {
+            if boss.in_fight() {
+                continue;
+            }
+            let Some(mut spawned) = pending.take() else {
+                continue;
+            };
+            // a defeated npc waits for its respawn as before
+            if boss.defeated() {
+                spawned.hp = 0;
+                spawned.time_since_defeated = boss.time_since_defeated;
+            }
+            *boss = spawned;
+            replaced = true;
+        }
+        replaced
     }
     pub fn add_character(&mut self, id: u128, hero: Hero) {
         self.scene.characters.insert(id, hero);
     }
+    pub fn find_character_class(&self, id: u32) -> Option<&CharacterDefinition> {
+        self.character_classes.iter().find(|item| item.id == id)
+    }
 
     pub fn update(&mut self) -> bool {
         let now = Instant::now();
         let dt = now.saturating_duration_since(self.last_update).as_millis();
         self.last_update = now;
-        self.scene.update(dt)
+        let update_event = self.scene.update(dt);
+        self.replace_pending_npc() || update_event
+    }
+}
+
+// the first active level with its npc placed on spawns
+fn load_level(game_data: &GameData) -> game_data::Result<(Level, Vec<Boss>)> {
+    let level_list = game_data.list::<Level>()?;
+    let Some(entry) = level_list.list.iter().find(|item| item.status.is_active()) else {
+        return Err(game_data::Error::Invalid {
+            path: game_data.list_path::<Level>(),
+            message: "no active level".to_owned(),
+        });
+    };
+    let level: Level = game_data.load(entry.id)?;
+    let mut npc_list = Vec::new();
+    for npc in level.npc_list.iter() {
+        let constructor: NpcConstructor = game_data.load(npc.id)?;
+        let attacks = game_data.npc_attacks(&constructor)?;
+        npc_list.push(Boss::spawn(npc, constructor, attacks));
     }
+    Ok((level, npc_list))
 }
@@ -0,0 +1,147 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// a shorter secret can be guessed and used to sign tokens for any account
+pub const MIN_SECRET_LENGTH: usize = 32;
+
+// Signs session tokens given out by /login and /register,
+// a token is `account_id.expires_at.signature` where
+// signature is hex of hmac-sha256 of the first two parts
+pub struct Tokens {
+    secret: Vec<u8>,
+    lifetime: Duration,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl Tokens {
+    // without a secret tokens are signed with a random one,
+    // and are valid only until the server restarts
+    pub fn new(secret: Option<&str>, lifetime: Duration) -> Self {
+        let secret = match secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => {
+                tracing::warn!("session_secret is not set, logins are lost on restart");
+                let mut secret = vec![0; 32];
+                OsRng.fill_bytes(&mut secret);
+                secret
+            }
+        };
+        Self { secret, lifetime }
+    }
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("Hmac should accept any key length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+    pub fn issue(&self, account_id: u32) -> String {
+        let expires_at = now() + self.lifetime.as_secs();
+        let payload = format!("{account_id}.{expires_at}");
+        let signature = self.mac(&payload).finalize().into_bytes();
+        format!("{payload}.{}", to_hex(&signature))
+    }
+    // account id of a valid token which hasn't expired yet
+    pub fn verify(&self, token: &str) -> Option<u32> {
+        let (payload, signature) = token.rsplit_once('.')?;
+        self.mac(payload).verify_slice(&from_hex(signature)?).ok()?;
+        let (account_id, expires_at) = payload.split_once('.')?;
+        if expires_at.parse::<u64>().ok()? < now() {
+            return None;
+        }
+        account_id.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn tokens() -> Tokens {
+        Tokens::new(Some(SECRET), Duration::from_secs(60))
+    }
+
+    fn signed(tokens: &Tokens, payload: &str) -> String {
+        let signature = tokens.mac(payload).finalize().into_bytes();
+        format!("{payload}.{}", to_hex(&signature))
+    }
+
+    #[test]
+    fn verify_issued_token() {
+        let tokens = tokens();
+        let token = tokens.issue(42);
+        assert_eq!(tokens.verify(&token), Some(42));
+    }
+
+    #[test]
+    fn reject_token_of_another_secret() {
+        let other = Tokens::new(Some(&SECRET.repeat(2)), Duration::from_secs(60));
+        assert_eq!(tokens().verify(&other.issue(42)), None);
+    }
+
+    #[test]
+    fn reject_tampered_payload() {
+        let tokens = tokens();
+        let token = tokens.issue(42);
+        let tampered = token.replacen("42.", "43.", 1);
+        assert_eq!(tokens.verify(&tampered), None);
+    }
+
+    #[test]
+    fn reject_tampered_signature() {
+        let tokens = tokens();
+        let mut token = tokens.issue(42);
+        let last = if token.ends_with('0') { "1" } else { "0" };
+        token.pop();
+        token.push_str(last);
+        assert_eq!(tokens.verify(&token), None);
+    }
+
+    #[test]
+    fn reject_malformed_signature() {
+        let tokens = tokens();
+        let token = tokens.issue(42);
+        let (payload, signature) = token.rsplit_once('.').unwrap();
+        let odd_length = format!("{payload}.{}", &signature[1..]);
+        assert_eq!(tokens.verify(&odd_length), None);
+        let not_hex = format!("{payload}.zz{}", &signature[2..]);
+        assert_eq!(tokens.verify(&not_hex), None);
+        assert_eq!(tokens.verify(payload), None);
+        assert_eq!(tokens.verify(""), None);
+    }
+
+    #[test]
+    fn reject_expired_token() {
+        let tokens = tokens();
+        let token = signed(&tokens, &format!("42.{}", now() - 1));
+        assert_eq!(tokens.verify(&token), None);
+        let token = signed(&tokens, &format!("42.{}", now() + 60));
+        assert_eq!(tokens.verify(&token), Some(42));
+    }
+}
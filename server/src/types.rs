@@ -1,4 +1,4 @@
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 use network::client;
 
@@ -6,10 +6,15 @@ use crate::broadcaster::Message as BroadcasterMessage;
 
 pub type GameLoopSender = mpsc::Sender<LoopMessage>;
 pub type GameLoopReceiver = mpsc::Receiver<LoopMessage>;
+// errors found in the new data, if any
+pub type ReloadResult = Result<(), Vec<String>>;
 
 pub enum LoopMessage {
     Broadcaster(Box<BroadcasterMessage>),
-    Client(u128, Box<client::Message>),
-    Join(u128),
-    Leave(u128),
+    Client(u128, u64, Box<client::Message>), // account id and connection number
+    // account id, replied with the connection number
+    Open(u128, oneshot::Sender<u64>),
+    Connect(u128),
+    Leave(u128, u64),
+    ReloadData(Option<oneshot::Sender<ReloadResult>>),
 }
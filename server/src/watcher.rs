@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use tokio::time;
+
+use crate::types::{GameLoopSender, LoopMessage};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// path, modification time and size of every file in the data folder
+type Snapshot = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+fn take_snapshot(root: &Path) -> Snapshot {
+    let mut snapshot = Vec::new();
+    if root.is_file() {
+        // a data bundle
+        if let Ok(metadata) = root.metadata() {
+            snapshot.push((root.to_owned(), metadata.modified().ok(), metadata.len()));
+        }
+        return snapshot;
+    }
+    let Ok(folders) = std::fs::read_dir(root) else {
+        return snapshot;
+    };
+    for folder in folders.filter_map(|entry| entry.ok()) {
+        let Ok(files) = std::fs::read_dir(folder.path()) else {
+            continue;
+        };
+        for file in files.filter_map(|entry| entry.ok()) {
+            let Ok(metadata) = file.metadata() else {
+                continue;
+            };
+            snapshot.push((file.path(), metadata.modified().ok(), metadata.len()));
+        }
+    }
+    snapshot.sort();
+    snapshot
+}
+
+async fn snapshot_of(root: &Path) -> Snapshot {
+    let root = root.to_owned();
+    tokio::task::spawn_blocking(move || take_snapshot(&root))
+        .await
+        .unwrap_or_default()
+}
+
+// Polls the data folder and asks the game loop to reload when files change.
+// The editor writes an item and the list separately, so we wait
+// until nothing changes for one more interval before reloading
+pub async fn watch_data(root: PathBuf, sender: GameLoopSender) {
+    let mut loaded = snapshot_of(&root).await;
+    let mut previous = loaded.clone();
+    loop {
+        time::sleep(POLL_INTERVAL).await;
+        let current = snapshot_of(&root).await;
+        if current == previous && current != loaded {
+            if let Err(e) = sender.send(LoopMessage::ReloadData(None)).await {
+                tracing::error!("Failed to send ReloadData to game loop: {e}");
+                return;
+            }
+            loaded = current.clone();
+        }
+        previous = current;
+    }
+}
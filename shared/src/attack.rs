@@ -6,13 +6,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::character::Character;
 use crate::check_hit;
+use crate::position::direction_from;
+use crate::validation::{FieldError, Validate};
 
 pub trait ReceiveDamage {
     fn receive_damage(&mut self, value: u32);
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ComplexAttackConstructor {
+    #[serde(default)]
+    pub name: String,
     pub range: AttackRange,
     pub sequences: Vec<AttackSequenceConstructor>,
 }
@@ -26,6 +30,15 @@ pub struct ComplexAttack {
     pub direction_angle: f32,
 }
 
+impl ComplexAttackConstructor {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Self::default()
+        }
+    }
+}
+
 impl ComplexAttack {
     pub fn from_constructor(
         constructor: ComplexAttackConstructor,
@@ -55,6 +68,29 @@ impl ComplexAttack {
             direction_angle,
         }
     }
+    // Start an attack from `attacker_position` aimed at `target_position`
+    pub fn aimed(
+        constructor: ComplexAttackConstructor,
+        attacker_position: Point2<f32>,
+        target_position: Point2<f32>,
+    ) -> Self {
+        let dx = attacker_position.x - target_position.x;
+        let dy = attacker_position.y - target_position.y;
+        let direction_angle = dy.atan2(dx) + std::f32::consts::PI;
+
+        let mut direction = direction_from(&attacker_position, &target_position);
+        if direction.norm() > 0.000_001 {
+            direction.normalize_mut();
+        }
+        let direction = Vector2::new(-direction.x, -direction.y);
+        ComplexAttack::from_constructor(
+            constructor,
+            attacker_position,
+            target_position,
+            direction,
+            direction_angle,
+        )
+    }
     pub fn update(&mut self, dt: u128) {
         for sequence in self.sequences.iter_mut() {
             sequence.update(dt);
@@ -65,7 +101,7 @@ impl ComplexAttack {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AttackSequenceConstructor {
     pub position_offset: Point2<f32>,
     pub parts: Vec<AttackPartConstructor>,
@@ -135,7 +171,7 @@ impl AttackSequence {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CircleConstructor {
     pub radius: f32,
     pub time_to_complete: u128,
@@ -184,7 +220,7 @@ impl Circle {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PizzaConstructor {
     pub radius: f32,
     pub width_angle: f32,
@@ -299,7 +335,7 @@ impl Pizza {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum AttackShapeConstructor {
     Circle(CircleConstructor),
     Pizza(PizzaConstructor),
@@ -345,7 +381,7 @@ impl AttackShape {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AttackDamageConstructor {
     pub value: u32,
     pub instances: u32,
@@ -391,7 +427,7 @@ impl AttackDamage {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AttackPartConstructor {
     pub time_to_complete: u128,
     pub shape: AttackShapeConstructor,
@@ -399,6 +435,53 @@ pub struct AttackPartConstructor {
     pub damage: Option<AttackDamageConstructor>,
 }
 
+impl AttackPartConstructor {
+    fn validate_at(&self, field: &str, errors: &mut Vec<FieldError>) {
+        if self.time_to_complete == 0 {
+            let field = format!("{field}.time_to_complete");
+            errors.push(FieldError::new(field, "should be greater than 0"));
+        }
+        if self.radius <= 0.0 {
+            let message = format!("should be positive, got {}", self.radius);
+            errors.push(FieldError::new(format!("{field}.radius"), message));
+        }
+        match &self.shape {
+            AttackShapeConstructor::Circle(circle) if circle.radius <= 0.0 => {
+                let message = format!("should be positive, got {}", circle.radius);
+                errors.push(FieldError::new(format!("{field}.shape.radius"), message));
+            }
+            AttackShapeConstructor::Pizza(pizza) if pizza.width_angle < 0.0 => {
+                let message = format!("is negative: {}", pizza.width_angle);
+                errors.push(FieldError::new(
+                    format!("{field}.shape.width_angle"),
+                    message,
+                ));
+            }
+            _ => (),
+        }
+        if let Some(damage) = &self.damage
+            && damage.instances == 0
+        {
+            let field = format!("{field}.damage.instances");
+            errors.push(FieldError::new(field, "should be greater than 0"));
+        }
+    }
+}
+
+impl Default for AttackPartConstructor {
+    fn default() -> Self {
+        Self {
+            time_to_complete: 1000,
+            shape: AttackShapeConstructor::Circle(CircleConstructor {
+                radius: 20.0,
+                time_to_complete: 1000,
+            }),
+            radius: 20.0,
+            damage: None,
+        }
+    }
+}
+
 // #[derive(Debug, Clone, Deserialize, Serialize)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AttackPart {
@@ -558,11 +641,9 @@ pub enum AttackState {
     Attacking,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct AttackConstructor {
     pub name: String,
-    pub position: Point2<f32>,
-    pub direction: Vector2<f32>,
     pub delay: u128,
     pub time_to_complete: u128, // ms
     pub aftercast: u128,
@@ -570,10 +651,9 @@ pub struct AttackConstructor {
     pub order: AttackOrder,
     pub range: AttackRange,
     pub width_angle: f32,
-    pub state: AttackState,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
 pub struct AttackRange {
     pub from: f32,
     pub to: f32,
@@ -583,6 +663,57 @@ impl AttackRange {
     pub fn in_range(&self, value: f32) -> bool {
         self.from <= value && value <= self.to
     }
+    fn validate_at(&self, field: &str, errors: &mut Vec<FieldError>) {
+        if self.from < 0.0 {
+            let message = format!("is negative: {}", self.from);
+            errors.push(FieldError::new(format!("{field}.from"), message));
+        }
+        if self.from > self.to {
+            let message = format!("{} is less than {field}.from {}", self.to, self.from);
+            errors.push(FieldError::new(format!("{field}.to"), message));
+        }
+    }
+}
+
+impl Validate for AttackConstructor {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.time_to_complete == 0 {
+            errors.push(FieldError::new(
+                "time_to_complete",
+                "should be greater than 0",
+            ));
+        }
+        self.range.validate_at("range", &mut errors);
+        if self.width_angle < 0.0 {
+            let message = format!("is negative: {}", self.width_angle);
+            errors.push(FieldError::new("width_angle", message));
+        }
+        errors
+    }
+}
+
+impl Validate for ComplexAttackConstructor {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        self.range.validate_at("range", &mut errors);
+        if self.sequences.is_empty() {
+            errors.push(FieldError::new("sequences", "should not be empty"));
+        }
+        for (index, sequence) in self.sequences.iter().enumerate() {
+            if sequence.parts.is_empty() {
+                let field = format!("sequences.{index}.parts");
+                errors.push(FieldError::new(field, "should not be empty"));
+            }
+            for (part_index, part) in sequence.parts.iter().enumerate() {
+                part.validate_at(
+                    &format!("sequences.{index}.parts.{part_index}"),
+                    &mut errors,
+                );
+            }
+        }
+        errors
+    }
 }
 
 impl AttackConstructor {
@@ -598,8 +729,6 @@ impl Default for AttackConstructor {
     fn default() -> Self {
         Self {
             name: String::new(),
-            position: Point2::default(),
-            direction: Vector2::default(),
             delay: 0,
             time_to_complete: 0,
             aftercast: 0,
@@ -607,7 +736,6 @@ impl Default for AttackConstructor {
             order: AttackOrder::CloseToFar,
             range: AttackRange::default(),
             width_angle: 0.0,
-            state: AttackState::Selected,
         }
     }
 }
@@ -637,15 +765,12 @@ impl AttackInfo {
         distance: f32,
     ) -> Self {
         let AttackConstructor {
-            // position,
-            // direction,
             delay,
             time_to_complete,
             aftercast,
             kind,
             order,
             width_angle,
-            state,
             ..
         } = constructor;
         AttackInfo {
@@ -660,10 +785,40 @@ impl AttackInfo {
             order,
             distance,
             width_angle,
-            state,
+            state: AttackState::Selected,
             damage_done: false,
         }
     }
+    // Start an attack from `attacker_position` aimed at `target_position`.
+    // `reach` is the radius of arcs and the maximum cast distance
+    // for attacks placed at the target
+    pub fn aimed(
+        constructor: AttackConstructor,
+        attacker_position: Point2<f32>,
+        target_position: Point2<f32>,
+        reach: f32,
+    ) -> Self {
+        let mut direction = direction_from(&attacker_position, &target_position);
+        let target_distance = direction.norm();
+        if target_distance > 0.000_001 {
+            direction.normalize_mut();
+        }
+        match &constructor.order {
+            AttackOrder::ExpandingCircle => {
+                let position = if target_distance > reach {
+                    attacker_position - direction * reach
+                } else {
+                    target_position
+                };
+                AttackInfo::from_constructor(constructor, position, direction, 70.0)
+            }
+            AttackOrder::ProjectileFromCaster => {
+                let direction = Vector2::new(-direction.x, -direction.y);
+                AttackInfo::from_constructor(constructor, attacker_position, direction, 20.0)
+            }
+            _ => AttackInfo::from_constructor(constructor, attacker_position, direction, reach),
+        }
+    }
     fn new(
         position: Point2<f32>,
         direction: Vector2<f32>,
@@ -867,17 +1022,12 @@ impl AttackInfo {
             }
         }
     }
-    pub fn check_damage_for_hero<T>(&mut self, melee_attack_distance: f32, npc: &mut [T])
+    pub fn check_damage_for_hero<T>(&mut self, npc: &mut [T])
     where
         T: Character,
     {
         for boss in npc.iter_mut() {
-            if check_hit(
-                self,
-                melee_attack_distance,
-                boss.get_position(),
-                boss.get_size(),
-            ) {
+            if check_hit(self, self.distance, boss.get_position(), boss.get_size()) {
                 boss.receive_damage();
                 self.damage_done = true;
             }
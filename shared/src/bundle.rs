@@ -0,0 +1,89 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::attack::{AttackConstructor, ComplexAttackConstructor};
+use crate::character::{CharacterDefinition, CharacterSettings};
+use crate::level::Level;
+use crate::list::EntryList;
+use crate::npc::NpcConstructor;
+use crate::resource::ResourceConstructor;
+
+// bump when the bundle layout or any of the stored structs change,
+// data files use the same version, see game-data migrations
+pub const SCHEMA_VERSION: u32 = 6;
+
+// the whole data folder in one file, packed by game-data
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct DataBundle {
+    pub schema_version: u32,
+    pub hash: String, // sha1 of encoded contents, hex
+    pub contents: BundleContents,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct BundleContents {
+    pub attacks: BundleSection<AttackConstructor>,
+    pub complex_attacks: BundleSection<ComplexAttackConstructor>,
+    pub npc: BundleSection<NpcConstructor>,
+    pub levels: BundleSection<Level>,
+    pub characters: BundleSection<CharacterSettings>,
+    pub resources: BundleSection<ResourceConstructor>,
+}
+
+// Lookups used by clients, which get the contents from the server
+// instead of reading data files
+impl BundleContents {
+    pub fn character_definition(&self, id: u32) -> Option<CharacterDefinition> {
+        let settings = self.characters.items.get(&id)?.clone();
+        let mut attacks = HashMap::new();
+        let mut resources = HashMap::new();
+        for ability in settings.abilities.iter() {
+            let attack_id = ability.attack.id;
+            attacks.insert(attack_id, self.attacks.items.get(&attack_id)?.clone());
+            if let Some(resource) = &ability.resource {
+                let constructor = self.resources.items.get(&resource.id)?.clone();
+                resources.insert(resource.id, constructor);
+            }
+        }
+        Some(CharacterDefinition {
+            id,
+            settings,
+            attacks,
+            resources,
+        })
+    }
+    // all characters which are not hidden in the list
+    pub fn character_definitions(&self) -> Vec<CharacterDefinition> {
+        self.characters
+            .list
+            .list
+            .iter()
+            .filter(|item| item.status.is_active())
+            .filter_map(|item| self.character_definition(item.id))
+            .collect()
+    }
+    // attacks which exist, missing ones are skipped
+    pub fn npc_attacks(&self, npc: &NpcConstructor) -> Vec<AttackConstructor> {
+        npc.attacks
+            .iter()
+            .filter_map(|item| self.attacks.items.get(&item.id).cloned())
+            .collect()
+    }
+}
+
+// one data folder: list.json and every item from it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BundleSection<T> {
+    pub list: EntryList,
+    pub items: BTreeMap<u32, T>,
+}
+
+impl<T> Default for BundleSection<T> {
+    fn default() -> Self {
+        Self {
+            list: EntryList::default(),
+            items: BTreeMap::new(),
+        }
+    }
+}
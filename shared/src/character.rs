@@ -1,12 +1,151 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
 use nalgebra::Point2;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+use crate::attack::AttackConstructor;
+use crate::list::{EntryInfo, EntryList};
+use crate::resource::ResourceConstructor;
+use crate::validation::{FieldError, Validate};
+
+pub type CharacterList = EntryList;
+pub type CharacterInfo = EntryInfo;
+
+// a hero class, defines stats and abilities
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct CharacterSettings {
+    pub name: String,
+    pub hp: i32,
+    pub speed: f32, // pixels per ms
+    pub size: f32,
+    pub melee_attack_distance: f32,
+    pub ranged_attack_distance: f32,
     pub dash_duration: u128,
     pub dash_distance: u128,
+    pub dash_cooldown: u128,
+    pub abilities: Vec<HeroAbility>,
+}
+
+impl Default for CharacterSettings {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl CharacterSettings {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            hp: 1000,
+            speed: 0.1,
+            size: 20.0,
+            melee_attack_distance: 100.0,
+            ranged_attack_distance: 300.0,
+            dash_duration: 100,
+            dash_distance: 150,
+            dash_cooldown: 200,
+            abilities: Vec::new(),
+        }
+    }
+    pub fn ability(&self, slot: AbilitySlot) -> Option<&HeroAbility> {
+        self.abilities.iter().find(|item| item.slot == slot)
+    }
+}
+
+impl Validate for CharacterSettings {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.hp <= 0 {
+            let message = format!("should be positive, got {}", self.hp);
+            errors.push(FieldError::new("hp", message));
+        }
+        if self.speed <= 0.0 {
+            let message = format!("should be positive, got {}", self.speed);
+            errors.push(FieldError::new("speed", message));
+        }
+        if self.size <= 0.0 {
+            let message = format!("should be positive, got {}", self.size);
+            errors.push(FieldError::new("size", message));
+        }
+        let mut slots = Vec::new();
+        for (index, ability) in self.abilities.iter().enumerate() {
+            if slots.contains(&ability.slot) {
+                let message = format!("{} is taken by another ability", ability.slot);
+                errors.push(FieldError::new(format!("abilities.{index}.slot"), message));
+            }
+            slots.push(ability.slot);
+        }
+        errors
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum AbilitySlot {
+    Primary,
+    Ability1,
+    Ability2,
+    Ability3,
+}
+
+impl AbilitySlot {
+    pub const fn options() -> [AbilitySlot; 4] {
+        use AbilitySlot::*;
+        [Primary, Ability1, Ability2, Ability3]
+    }
+}
+
+impl Display for AbilitySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HeroAbility {
+    pub slot: AbilitySlot,
+    pub attack: AbilityAttackInfo,
+    pub cooldown: u128, // ms
+    pub resource: Option<AbilityResourceInfo>,
+    pub cost: u32,
+}
+
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AbilityAttackInfo {
+    pub id: u32,
+    pub name: String,
+}
+
+impl Display for AbilityAttackInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.name)
+    }
 }
 
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct AbilityResourceInfo {
+    pub id: u32,
+    pub name: String,
+}
+
+impl Display for AbilityResourceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.id, self.name)
+    }
+}
+
+// a character class with all referenced attacks and resources resolved
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct CharacterDefinition {
+    pub id: u32,
+    pub settings: CharacterSettings,
+    pub attacks: HashMap<u32, AttackConstructor>,
+    pub resources: HashMap<u32, ResourceConstructor>,
+}
+
+// hp taken by one hit of a simple attack
+pub const HIT_DAMAGE: i32 = 35;
+
 pub trait Character {
     fn receive_damage(&mut self);
     fn get_position(&self) -> Point2<f32>;
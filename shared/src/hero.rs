@@ -11,13 +11,15 @@ pub struct Moving {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DashInfo {
     pub direction: Vector2<f32>,
+    pub distance: f32,
     pub time_passed: u128,
     pub time_to_complete: u128,
 }
 impl DashInfo {
-    pub fn new(direction: Vector2<f32>, time_to_complete: u128) -> Self {
+    pub fn new(direction: Vector2<f32>, distance: f32, time_to_complete: u128) -> Self {
         Self {
             direction,
+            distance,
             time_passed: 0,
             time_to_complete,
         }
@@ -25,7 +27,18 @@ impl DashInfo {
     pub fn update(&mut self, dt: u128) {
         self.time_passed += dt;
     }
+    // advances the dash and returns the offset for this step,
+    // the sum of all steps is exactly `distance` regardless of dt
+    pub fn step(&mut self, dt: u128) -> Vector2<f32> {
+        let before = self.percent_completed().min(1.0);
+        self.update(dt);
+        let after = self.percent_completed().min(1.0);
+        self.direction * (after - before) * self.distance
+    }
     pub fn percent_completed(&self) -> f32 {
+        if self.time_to_complete == 0 {
+            return 1.0;
+        }
         self.time_passed as f32 / self.time_to_complete as f32
     }
     pub fn completed(&self) -> bool {
@@ -1,26 +1,77 @@
 use std::fmt::{self, Display};
 
+use nalgebra::Point2;
 use serde::{Deserialize, Serialize};
 
-use crate::list::EntryStatus;
+use crate::list::{EntryInfo, EntryList};
+use crate::validation::{FieldError, Validate};
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-pub struct LevelList {
-    pub last_id: u32,
-    pub list: Vec<LevelInfo>,
+pub type LevelList = EntryList;
+pub type LevelInfo = EntryInfo;
+
+const DEFAULT_WIDTH: f32 = 1024.0;
+const DEFAULT_HEIGHT: f32 = 768.0;
+
+// when an npc starts to attack
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub enum ActivationTrigger {
+    #[default]
+    OnStart,
+    PlayerInRange {
+        distance: f32,
+    },
+    AfterDelay {
+        delay: u128, // ms since the level started
+    },
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct LevelInfo {
-    pub id: u32,
-    pub name: String,
-    pub status: EntryStatus,
+impl ActivationTrigger {
+    pub fn options() -> [ActivationTrigger; 3] {
+        use ActivationTrigger::*;
+        [
+            OnStart,
+            PlayerInRange { distance: 300.0 },
+            AfterDelay { delay: 5000 },
+        ]
+    }
+    pub fn same_kind(&self, other: &ActivationTrigger) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 }
 
-#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+impl Display for ActivationTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ActivationTrigger::OnStart => "On start",
+            ActivationTrigger::PlayerInRange { .. } => "Player in range",
+            ActivationTrigger::AfterDelay { .. } => "After delay",
+        };
+        write!(f, "{name}")
+    }
+}
+
+// an npc spawn, hp overrides the hp of the npc
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct LevelNpcInfo {
     pub id: u32,
     pub name: String,
+    pub position: Point2<f32>,
+    pub facing: f32, // radians
+    pub hp: Option<i32>,
+    pub trigger: ActivationTrigger,
+}
+
+impl Default for LevelNpcInfo {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: String::new(),
+            position: Point2::new(DEFAULT_WIDTH / 2.0, DEFAULT_HEIGHT / 2.0),
+            facing: 0.0,
+            hp: None,
+            trigger: ActivationTrigger::OnStart,
+        }
+    }
 }
 
 impl Display for LevelNpcInfo {
@@ -29,17 +80,178 @@ impl Display for LevelNpcInfo {
     }
 }
 
-#[derive(Default, Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct LevelBounds {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for LevelBounds {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+        }
+    }
+}
+
+impl LevelBounds {
+    pub fn contains(&self, position: Point2<f32>) -> bool {
+        (0.0..=self.width).contains(&position.x) && (0.0..=self.height).contains(&position.y)
+    }
+    // keeps a circle of the given radius inside
+    pub fn clamp(&self, position: Point2<f32>, radius: f32) -> Point2<f32> {
+        let x = position.x.clamp(radius, (self.width - radius).max(radius));
+        let y = position.y.clamp(radius, (self.height - radius).max(radius));
+        Point2::new(x, y)
+    }
+}
+
+// a rectangle which can't be walked through, position is the top left corner
+#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+pub struct Obstacle {
+    pub position: Point2<f32>,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Obstacle {
+    pub fn new(position: Point2<f32>) -> Self {
+        Self {
+            position,
+            width: 100.0,
+            height: 50.0,
+        }
+    }
+    pub fn contains(&self, position: Point2<f32>) -> bool {
+        (self.position.x..=self.position.x + self.width).contains(&position.x)
+            && (self.position.y..=self.position.y + self.height).contains(&position.y)
+    }
+    // moves a circle out of the rectangle by the shortest way
+    pub fn push_out(&self, position: Point2<f32>, radius: f32) -> Point2<f32> {
+        let left = self.position.x - radius;
+        let right = self.position.x + self.width + radius;
+        let top = self.position.y - radius;
+        let bottom = self.position.y + self.height + radius;
+        if position.x <= left || position.x >= right || position.y <= top || position.y >= bottom {
+            return position;
+        }
+        let moves = [
+            (position.x - left, Point2::new(left, position.y)),
+            (right - position.x, Point2::new(right, position.y)),
+            (position.y - top, Point2::new(position.x, top)),
+            (bottom - position.y, Point2::new(position.x, bottom)),
+        ];
+        moves
+            .into_iter()
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, moved)| moved)
+            .unwrap_or(position)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Level {
     pub name: String,
+    pub bounds: LevelBounds,
+    pub player_spawns: Vec<Point2<f32>>,
     pub npc_list: Vec<LevelNpcInfo>,
+    pub obstacles: Vec<Obstacle>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            bounds: LevelBounds::default(),
+            player_spawns: vec![Point2::new(250.0, 200.0)],
+            npc_list: Vec::new(),
+            obstacles: Vec::new(),
+        }
+    }
 }
 
 impl Level {
     pub fn new(name: String) -> Self {
         Self {
             name,
-            npc_list: Vec::new(),
+            ..Self::default()
+        }
+    }
+    // players take spawns in turns
+    pub fn player_spawn(&self, index: usize) -> Point2<f32> {
+        if self.player_spawns.is_empty() {
+            return Level::default().player_spawns[0];
+        }
+        self.player_spawns[index % self.player_spawns.len()]
+    }
+    // where a circle ends up after moving to `position`
+    pub fn keep_inside(&self, position: Point2<f32>, radius: f32) -> Point2<f32> {
+        let position = self.obstacles.iter().fold(position, |position, obstacle| {
+            obstacle.push_out(position, radius)
+        });
+        self.bounds.clamp(position, radius)
+    }
+}
+
+impl Validate for Level {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.bounds.width <= 0.0 {
+            let message = format!("should be positive, got {}", self.bounds.width);
+            errors.push(FieldError::new("bounds.width", message));
+        }
+        if self.bounds.height <= 0.0 {
+            let message = format!("should be positive, got {}", self.bounds.height);
+            errors.push(FieldError::new("bounds.height", message));
+        }
+        if self.player_spawns.is_empty() {
+            errors.push(FieldError::new(
+                "player_spawns",
+                "should have at least one spawn",
+            ));
+        }
+        for (index, spawn) in self.player_spawns.iter().enumerate() {
+            if !self.bounds.contains(*spawn) {
+                let field = format!("player_spawns.{index}");
+                errors.push(FieldError::new(field, "should be inside the bounds"));
+            }
+        }
+        if self.npc_list.is_empty() {
+            errors.push(FieldError::new("npc_list", "should have at least one npc"));
+        }
+        for (index, npc) in self.npc_list.iter().enumerate() {
+            if !self.bounds.contains(npc.position) {
+                let field = format!("npc_list.{index}.position");
+                errors.push(FieldError::new(field, "should be inside the bounds"));
+            }
+            if let Some(hp) = npc.hp
+                && hp <= 0
+            {
+                let message = format!("should be positive, got {hp}");
+                errors.push(FieldError::new(format!("npc_list.{index}.hp"), message));
+            }
+            match npc.trigger {
+                ActivationTrigger::PlayerInRange { distance } if distance <= 0.0 => {
+                    let message = format!("should be positive, got {distance}");
+                    let field = format!("npc_list.{index}.trigger.distance");
+                    errors.push(FieldError::new(field, message));
+                }
+                _ => (),
+            }
+        }
+        for (index, obstacle) in self.obstacles.iter().enumerate() {
+            if obstacle.width <= 0.0 {
+                let message = format!("should be positive, got {}", obstacle.width);
+                let field = format!("obstacles.{index}.width");
+                errors.push(FieldError::new(field, message));
+            }
+            if obstacle.height <= 0.0 {
+                let message = format!("should be positive, got {}", obstacle.height);
+                let field = format!("obstacles.{index}.height");
+                errors.push(FieldError::new(field, message));
+            }
         }
+        errors
     }
 }
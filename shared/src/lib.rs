@@ -2,6 +2,7 @@ use nalgebra::Point2;
 
 pub mod action;
 pub mod attack;
+pub mod bundle;
 pub mod character;
 pub mod effect;
 pub mod hero;
@@ -11,6 +12,7 @@ pub mod npc;
 pub mod position;
 pub mod projectile;
 pub mod resource;
+pub mod validation;
 
 use attack::{AttackInfo, AttackKind};
 
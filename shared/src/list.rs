@@ -14,3 +14,33 @@ impl EntryStatus {
         }
     }
 }
+
+// contents of a list.json file, same for every kind of entity
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+pub struct EntryList {
+    pub last_id: u32,
+    pub list: Vec<EntryInfo>,
+}
+
+impl EntryList {
+    // ids are never reused, so references to a deleted entity
+    // can't point to a new one
+    pub fn next_id(&mut self) -> u32 {
+        let max_id = self.list.iter().map(|entry| entry.id).max();
+        self.last_id = self.last_id.max(max_id.unwrap_or_default()) + 1;
+        self.last_id
+    }
+    // keeps last_id as it is
+    pub fn remove(&mut self, id: u32) -> Option<EntryInfo> {
+        let index = self.list.iter().position(|entry| entry.id == id)?;
+        Some(self.list.remove(index))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EntryInfo {
+    pub id: u32,
+    pub name: String,
+    pub status: EntryStatus,
+    pub tags: Vec<String>, // set in the editor to group entries
+}
@@ -3,20 +3,46 @@ use std::fmt::{self, Display};
 use nalgebra::Point2;
 use serde::{Deserialize, Serialize};
 
-use crate::attack::{AttackConstructor, ComplexAttackConstructor};
+use crate::attack::AttackConstructor;
+use crate::validation::{FieldError, Validate};
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+const DEFAULT_SIZE: f32 = 30.0;
+const DEFAULT_HP: i32 = 100;
+
+fn default_size() -> f32 {
+    DEFAULT_SIZE
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct NpcConstructor {
     pub name: String,
     pub respawn_time: u128,
     pub attacks: Vec<NpcAttackInfo>,
     pub hp: i32,
+    #[serde(default = "default_size")]
+    pub size: f32,
+    pub speed: f32, // pixels per ms, 0 for npc which don't move
 }
 
-#[derive(Default, Debug, Deserialize, Serialize, PartialEq, Clone)]
+// attacks are picked at random among the ready ones in range,
+// an attack with twice the weight is picked twice as often
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct NpcAttackInfo {
     pub id: u32,
     pub name: String,
+    pub weight: u32,
+    pub cooldown: u128, // ms from the start of the attack until it can be used again
+}
+
+impl Default for NpcAttackInfo {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            name: String::new(),
+            weight: 1,
+            cooldown: 0,
+        }
+    }
 }
 
 impl Display for NpcAttackInfo {
@@ -29,11 +55,38 @@ impl NpcConstructor {
     pub fn new(name: String) -> Self {
         Self {
             name,
+            hp: DEFAULT_HP,
+            size: DEFAULT_SIZE,
             ..Self::default()
         }
     }
 }
 
+impl Validate for NpcConstructor {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.hp <= 0 {
+            let message = format!("should be positive, got {}", self.hp);
+            errors.push(FieldError::new("hp", message));
+        }
+        if self.size <= 0.0 {
+            let message = format!("should be positive, got {}", self.size);
+            errors.push(FieldError::new("size", message));
+        }
+        if self.speed < 0.0 {
+            let message = format!("is negative: {}", self.speed);
+            errors.push(FieldError::new("speed", message));
+        }
+        for (index, attack) in self.attacks.iter().enumerate() {
+            if attack.weight == 0 {
+                let field = format!("attacks.{index}.weight");
+                errors.push(FieldError::new(field, "should be greater than 0"));
+            }
+        }
+        errors
+    }
+}
+
 pub struct NpcInfo {
     pub position: Point2<f32>,
     pub attacks: Vec<AttackConstructor>,
@@ -41,38 +94,14 @@ pub struct NpcInfo {
     pub max_hp: i32,
 }
 
-fn load_attack_by_id(id: u32) -> AttackConstructor {
-    let file_path = format!("../data/attack/attack_{id}.json");
-    let contents = std::fs::read(file_path).expect("Should read AttackConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode AttackConstructor")
-}
-
-pub fn load_attacks(attack_info: Vec<NpcAttackInfo>) -> Vec<AttackConstructor> {
-    attack_info
-        .into_iter()
-        .map(|item| load_attack_by_id(item.id))
-        .collect()
-}
-
-fn load_complex_attack_by_id(id: u32) -> ComplexAttackConstructor {
-    let file_path = format!("../data/attack/complex_attack_{id}.json");
-    let contents =
-        std::fs::read(file_path).expect("Should read ComplexAttackConstructor from a file");
-    serde_json::from_slice(&contents).expect("Should decode ComplexAttackConstructor")
-}
-
-pub fn load_complex_attacks(attack_info: Vec<NpcAttackInfo>) -> Vec<ComplexAttackConstructor> {
-    attack_info
-        .into_iter()
-        .map(|item| load_complex_attack_by_id(item.id))
-        .collect()
-}
-
 impl NpcInfo {
     #[allow(unused)]
-    fn from_constructor(constructor: NpcConstructor, position: Point2<f32>) -> Self {
-        let NpcConstructor { attacks, hp, .. } = constructor;
-        let attacks = load_attacks(attacks);
+    fn from_constructor(
+        constructor: NpcConstructor,
+        attacks: Vec<AttackConstructor>,
+        position: Point2<f32>,
+    ) -> Self {
+        let NpcConstructor { hp, .. } = constructor;
         NpcInfo {
             position,
             attacks,
@@ -1,16 +1,139 @@
+use nalgebra::Point2;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+use crate::list::{EntryInfo, EntryList};
+use crate::position::distance_between;
+use crate::validation::{FieldError, Validate};
+
+pub const PICKUP_SIZE: f32 = 8.0;
+pub const PICKUP_LIFETIME: u128 = 30_000; // ms before an uncollected pickup disappears
+
+pub type ResourceList = EntryList;
+pub type ResourceInfo = EntryInfo;
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct ResourceConstructor {
     pub name: String,
+    pub max: f32,
+    pub regen: f32, // per second
+    pub pickup_amount: f32,
+    pub drop_count: u32, // pickups dropped by each defeated npc
 }
 
 impl ResourceConstructor {
     pub fn new(name: String) -> Self {
-        Self { name }
+        Self {
+            name,
+            max: 100.0,
+            regen: 5.0,
+            pickup_amount: 25.0,
+            drop_count: 0,
+        }
     }
 }
 
-pub struct ResourceInfo {
+impl Validate for ResourceConstructor {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.max <= 0.0 {
+            let message = format!("should be positive, got {}", self.max);
+            errors.push(FieldError::new("max", message));
+        }
+        if self.regen < 0.0 {
+            let message = format!("is negative: {}", self.regen);
+            errors.push(FieldError::new("regen", message));
+        }
+        if self.pickup_amount < 0.0 {
+            let message = format!("is negative: {}", self.pickup_amount);
+            errors.push(FieldError::new("pickup_amount", message));
+        }
+        errors
+    }
+}
+
+// hero mana, stamina, rage, etc.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ResourcePool {
+    pub id: u32,
     pub name: String,
+    pub current: f32,
+    pub max: f32,
+    pub regen: f32, // per second
+}
+
+impl ResourcePool {
+    pub fn new(id: u32, constructor: &ResourceConstructor) -> Self {
+        Self {
+            id,
+            name: constructor.name.clone(),
+            current: constructor.max,
+            max: constructor.max,
+            regen: constructor.regen,
+        }
+    }
+    pub fn update(&mut self, dt: u128) {
+        self.restore(self.regen * dt as f32 / 1000.0);
+    }
+    pub fn restore(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+    pub fn try_spend(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        true
+    }
+    pub fn percent_left(&self) -> f32 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+        self.current / self.max
+    }
+}
+
+// what a defeated npc leaves behind
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ResourceDrop {
+    pub resource_id: u32,
+    pub amount: f32,
+    pub count: u32,
+}
+
+impl ResourceDrop {
+    pub fn new(resource_id: u32, constructor: &ResourceConstructor) -> Self {
+        Self {
+            resource_id,
+            amount: constructor.pickup_amount,
+            count: constructor.drop_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Pickup {
+    pub resource_id: u32,
+    pub amount: f32,
+    pub position: Point2<f32>,
+    pub time_left: u128,
+}
+
+impl Pickup {
+    pub fn new(resource_id: u32, amount: f32, position: Point2<f32>) -> Self {
+        Self {
+            resource_id,
+            amount,
+            position,
+            time_left: PICKUP_LIFETIME,
+        }
+    }
+    pub fn update(&mut self, dt: u128) {
+        self.time_left = self.time_left.saturating_sub(dt);
+    }
+    pub fn expired(&self) -> bool {
+        self.time_left == 0
+    }
+    pub fn touches(&self, position: Point2<f32>, size: f32) -> bool {
+        distance_between(&self.position, &position) < size + PICKUP_SIZE
+    }
 }
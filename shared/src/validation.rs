@@ -0,0 +1,33 @@
+use std::fmt::{self, Display};
+
+// a broken rule of stored data, `field` is a path like `range.from`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+// rules for the data files, checked by the editor before saving
+// and by the validator of game-data
+pub trait Validate {
+    fn validate(&self) -> Vec<FieldError>;
+
+    fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+}